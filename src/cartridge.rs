@@ -0,0 +1,120 @@
+use crate::chip8::octo;
+use crate::chip8::quirks::Platform;
+use crate::config::{Profile, QuirkFlags, QuirksConfig};
+use serde::Deserialize;
+
+/// The magic numbers GIF files start with.
+const GIF_SIGNATURES: [&[u8]; 2] = [b"GIF87a", b"GIF89a"];
+
+/// The `maxSize` Octo gives XO-CHIP programs, which have 64 KiB of memory.
+const XO_CHIP_MAX_SIZE: u32 = 65024;
+
+/// The `maxSize` Octo gives SUPER-CHIP programs.
+const SCHIP_MAX_SIZE: u32 = 3583;
+
+/// Whether a file is a GIF, and so possibly an Octo cartridge.
+pub fn is_cartridge(bytes: &[u8]) -> bool {
+    GIF_SIGNATURES
+        .iter()
+        .any(|signature| bytes.starts_with(signature))
+}
+
+/// A game shared from Octo as a "cartridge": a GIF image with the source of the program and the options it runs
+/// with hidden in the low two bits of each pixel.
+#[derive(Debug, Deserialize)]
+pub struct Cartridge {
+    #[serde(default)]
+    options: Options,
+    /// The program, in Octo's assembly language.
+    program: String,
+}
+
+/// The options of an Octo cartridge that the emulator understands. Octo keeps key bindings in the player's settings
+/// rather than in cartridges, so cartridges never change the key map.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Options {
+    /// The number of instructions run each frame.
+    tickrate: Option<u32>,
+    background_color: Option<String>,
+    fill_color: Option<String>,
+    /// Whether shifts ignore VY.
+    shift_quirks: Option<bool>,
+    /// Whether FX55 and FX65 leave I unchanged.
+    load_store_quirks: Option<bool>,
+    jump_quirks: Option<bool>,
+    clip_quirks: Option<bool>,
+    logic_quirks: Option<bool>,
+    /// The largest program the platform can load, which is how Octo tells its platforms apart.
+    max_size: Option<u32>,
+}
+
+impl Cartridge {
+    /// Reads the program and options out of a cartridge.
+    pub fn decode(bytes: &[u8]) -> Result<Cartridge, String> {
+        let invalid = |err: gif::DecodingError| format!("the image can't be read: {}", err);
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::Indexed);
+        let mut decoder = options.read_info(bytes).map_err(invalid)?;
+
+        let mut pixels = Vec::new();
+        while let Some(frame) = decoder.read_next_frame().map_err(invalid)? {
+            pixels.extend_from_slice(&frame.buffer);
+        }
+
+        // Each byte is spread over four pixels, two bits to a pixel with the highest bits first.
+        let payload: Vec<u8> = pixels
+            .chunks_exact(4)
+            .map(|chunk| chunk.iter().fold(0, |byte, pixel| byte << 2 | pixel & 3))
+            .collect();
+        let length = match payload.get(..4) {
+            Some(length) => {
+                u32::from_be_bytes([length[0], length[1], length[2], length[3]]) as usize
+            }
+            None => return Err(String::from("the image is too small to be a cartridge")),
+        };
+        let json = payload
+            .get(4..4 + length)
+            .ok_or("the image is too small to hold the program it says it has")?;
+
+        // Octo writes the JSON one character to a byte.
+        let json: String = json.iter().map(|&byte| byte as char).collect();
+        serde_json::from_str(&json)
+            .map_err(|err| format!("the image isn't an Octo cartridge: {}", err))
+    }
+
+    /// Compiles the program of the cartridge into a ROM.
+    pub fn compile(&self) -> Result<Vec<u8>, String> {
+        octo::compile(&self.program)
+    }
+
+    /// The settings the cartridge asks for.
+    pub fn profile(&self) -> Profile {
+        let options = &self.options;
+        let platform = options.max_size.map(|max_size| match max_size {
+            XO_CHIP_MAX_SIZE => Platform::XoChip,
+            SCHIP_MAX_SIZE => Platform::Schip,
+            _ => Platform::Chip8,
+        });
+        Profile {
+            platform,
+            quirks: Some(QuirksConfig::Flags(QuirkFlags {
+                shift_uses_vy: options.shift_quirks.map(|quirk| !quirk),
+                load_store_increments_i: options.load_store_quirks.map(|quirk| !quirk),
+                jump_uses_vx: options.jump_quirks,
+                logic_resets_vf: options.logic_quirks,
+                clip_sprites: options.clip_quirks,
+            })),
+            ips: options.tickrate.map(|tickrate| tickrate * 60),
+            bg: options
+                .background_color
+                .as_ref()
+                .and_then(|color| color.parse().ok()),
+            fg: options
+                .fill_color
+                .as_ref()
+                .and_then(|color| color.parse().ok()),
+            ..Profile::default()
+        }
+    }
+}
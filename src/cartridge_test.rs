@@ -0,0 +1,75 @@
+#[cfg(test)]
+mod octo_cartridge_test {
+    use crate::cartridge::{is_cartridge, Cartridge};
+    use crate::chip8::quirks::Platform;
+    use crate::config::QuirksConfig;
+
+    /// Builds a cartridge the way Octo does, with the payload in the low two bits of each pixel.
+    fn cartridge(json: &str) -> Vec<u8> {
+        let mut payload = (json.len() as u32).to_be_bytes().to_vec();
+        payload.extend(json.bytes());
+        let mut pixels: Vec<u8> = payload
+            .iter()
+            .flat_map(|&byte| (0..4).rev().map(move |pair| 4 | byte >> (pair * 2) & 3))
+            .collect();
+        let width = 64;
+        pixels.resize(pixels.len().div_ceil(width) * width, 4);
+        let height = (pixels.len() / width) as u16;
+
+        let palette: Vec<u8> = (0..8).flat_map(|color| [color * 32, 0, 0]).collect();
+        let mut gif = Vec::new();
+        {
+            let mut encoder = gif::Encoder::new(&mut gif, width as u16, height, &palette).unwrap();
+            let frame = gif::Frame::from_palette_pixels(
+                width as u16,
+                height,
+                pixels,
+                palette.clone(),
+                None,
+            );
+            encoder.write_frame(&frame).unwrap();
+        }
+        gif
+    }
+
+    #[test]
+    fn cartridge_decode_test() {
+        let gif = cartridge(
+            r##"{"options":{"tickrate":20,"fillColor":"#FFCC00","backgroundColor":"#996600",
+            "shiftQuirks":true,"loadStoreQuirks":false,"clipQuirks":true,"maxSize":3583},
+            "program":": main\n  v0 := 5\n  jump main"}"##,
+        );
+        assert!(is_cartridge(&gif));
+        assert!(!is_cartridge(&[0x12, 0x00]));
+
+        let cartridge = Cartridge::decode(&gif).unwrap();
+        assert_eq!(
+            cartridge.compile(),
+            Ok(vec![0x12, 0x02, 0x60, 0x05, 0x12, 0x02])
+        );
+
+        let profile = cartridge.profile();
+        assert_eq!(profile.platform, Some(Platform::Schip));
+        assert_eq!(profile.ips, Some(1200));
+        assert_eq!(profile.fg, "#FFCC00".parse().ok());
+        assert_eq!(profile.bg, "#996600".parse().ok());
+        match profile.quirks {
+            Some(QuirksConfig::Flags(flags)) => {
+                assert_eq!(flags.shift_uses_vy, Some(false));
+                assert_eq!(flags.load_store_increments_i, Some(true));
+                assert_eq!(flags.clip_sprites, Some(true));
+                assert_eq!(flags.jump_uses_vx, None);
+            }
+            quirks => panic!("expected quirk flags, got {:?}", quirks),
+        }
+        assert!(profile.keymap.is_none());
+    }
+
+    #[test]
+    fn cartridge_reject_test() {
+        let gif = cartridge("not json");
+        assert!(Cartridge::decode(&gif)
+            .unwrap_err()
+            .starts_with("the image isn't an Octo cartridge"));
+    }
+}
@@ -0,0 +1,223 @@
+use super::instructions::Instruction;
+use std::collections::{BTreeMap, BTreeSet};
+use std::convert::TryFrom;
+
+/// The address programs are loaded at.
+const PROGRAM_START: u16 = 0x200;
+
+/// How many instructions are looked back through to find the key a register holds when it is checked.
+const KEY_LOOKBACK: usize = 8;
+
+/// A run of addresses that hold either code or data.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Region {
+    pub start: u16,
+    /// The last address of the region.
+    pub end: u16,
+    pub code: bool,
+}
+
+/// What can be worked out about a program without running it. Only the code reachable from the start of the program
+/// is looked at, so anything reached through computed jumps or written by the program itself is missed.
+#[derive(Debug, Default)]
+pub struct Analysis {
+    /// How many times each kind of instruction appears in the code, by the name of its `Instruction` variant.
+    pub opcodes: BTreeMap<String, usize>,
+    /// The address and value of each word in the code that isn't a CHIP-8 instruction.
+    pub invalid: Vec<(u16, u16)>,
+    /// The keys checked with EX9E and EXA1, where the key could be worked out from the code before the check.
+    pub keys: BTreeSet<u8>,
+    /// The number of key checks whose key couldn't be worked out.
+    pub unknown_key_checks: usize,
+    /// Whether the program waits for a key with FX0A.
+    pub waits_for_key: bool,
+    /// The addresses of computed jumps (BNNN), which behave differently depending on the jump quirk.
+    pub computed_jumps: Vec<u16>,
+    /// The addresses of FX1E instructions followed by a check of VF. Some interpreters set VF when I goes past 0xFFF,
+    /// and the check suggests the program relies on it.
+    pub index_overflow_checks: Vec<u16>,
+    /// Addresses in the code that I is pointed at in a program that stores registers in memory, which suggests the
+    /// program changes its own code.
+    pub code_writes: Vec<u16>,
+    /// The code and data of the program, in order.
+    pub regions: Vec<Region>,
+}
+
+/// Follows a program from its start through its jumps, calls and skips, returning the offset of each instruction that
+/// can be reached, in order.
+pub fn reachable(rom: &[u8]) -> Vec<usize> {
+    let mut visited = vec![false; rom.len()];
+    let mut pending = vec![0];
+    while let Some(offset) = pending.pop() {
+        if offset + 1 >= rom.len() || visited[offset] {
+            continue;
+        }
+        visited[offset] = true;
+
+        let opcode = word(rom, offset);
+        let next = offset + 2;
+        let target = ((opcode & 0xFFF) as usize).checked_sub(PROGRAM_START as usize);
+        match opcode {
+            // Returning and exiting end the path, as do computed jumps, which can't be followed.
+            0x00EE | 0x00FD | 0xB000..=0xBFFF => {}
+            0x1000..=0x1FFF => pending.extend(target),
+            0x2000..=0x2FFF => {
+                pending.extend(target);
+                pending.push(next);
+            }
+            // The long XO-CHIP load of I is four bytes long, which skips take into account.
+            _ if is_skip(opcode) => {
+                pending.push(next);
+                pending.push(if word(rom, next) == 0xF000 {
+                    next + 4
+                } else {
+                    next + 2
+                });
+            }
+            0xF000 => pending.push(next + 2),
+            _ => pending.push(next),
+        }
+    }
+    (0..rom.len()).filter(|&offset| visited[offset]).collect()
+}
+
+/// Looks through a program for what it needs from the emulator.
+pub fn analyze(rom: &[u8]) -> Analysis {
+    let mut analysis = Analysis::default();
+    let code = reachable(rom);
+    let mut is_code = vec![false; rom.len()];
+    let mut stores = false;
+    let mut index_targets = Vec::new();
+
+    for (i, &offset) in code.iter().enumerate() {
+        let address = PROGRAM_START + offset as u16;
+        let opcode = word(rom, offset);
+        let length = if opcode == 0xF000 { 4 } else { 2 };
+        for covered in is_code.iter_mut().skip(offset).take(length) {
+            *covered = true;
+        }
+
+        let instruction = match Instruction::try_from(opcode) {
+            Ok(instruction) => instruction,
+            Err(_) => {
+                analysis.invalid.push((address, opcode));
+                continue;
+            }
+        };
+        let name = format!("{:?}", instruction);
+        let name = name.split('(').next().unwrap_or_default();
+        *analysis.opcodes.entry(name.to_string()).or_default() += 1;
+
+        match instruction {
+            Instruction::KeyOpKeyPressed(x) | Instruction::KeyOpKeyNotPressed(x) => {
+                match key_in_register(rom, &code[..i], x) {
+                    Some(key) => {
+                        analysis.keys.insert(key);
+                    }
+                    None => analysis.unknown_key_checks += 1,
+                }
+            }
+            Instruction::KeyOpGetKey(_) => analysis.waits_for_key = true,
+            Instruction::FlowJumpOffsetV0(_) => analysis.computed_jumps.push(address),
+            Instruction::MemAddIVx(_) => {
+                let next = Instruction::try_from(word(rom, offset + 2));
+                if next.is_ok_and(checks_vf) {
+                    analysis.index_overflow_checks.push(address);
+                }
+            }
+            Instruction::MemSetIAddress(target) => index_targets.push(target),
+            Instruction::MemRegisterDump(_) | Instruction::BCDSave(_) => stores = true,
+            _ => {}
+        }
+    }
+
+    if stores {
+        analysis.code_writes = index_targets
+            .into_iter()
+            .filter(|&target| {
+                let offset = target.wrapping_sub(PROGRAM_START) as usize;
+                is_code.get(offset) == Some(&true)
+            })
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+    }
+
+    for (offset, &code) in is_code.iter().enumerate() {
+        let address = PROGRAM_START + offset as u16;
+        match analysis.regions.last_mut() {
+            Some(region) if region.code == code => region.end = address,
+            _ => analysis.regions.push(Region {
+                start: address,
+                end: address,
+                code,
+            }),
+        }
+    }
+    analysis
+}
+
+/// Returns the big-endian word at an offset of the ROM, or 0 past its end.
+fn word(rom: &[u8], offset: usize) -> u16 {
+    match rom.get(offset..offset + 2) {
+        Some(bytes) => u16::from_be_bytes([bytes[0], bytes[1]]),
+        None => 0,
+    }
+}
+
+/// Whether an opcode skips the next instruction on some condition.
+fn is_skip(opcode: u16) -> bool {
+    match opcode >> 12 {
+        0x3 | 0x4 => true,
+        0x5 | 0x9 => opcode & 0xF == 0,
+        0xE => matches!(opcode & 0xFF, 0x9E | 0xA1),
+        _ => false,
+    }
+}
+
+/// Works out which key register X holds from the instructions just before a key check, which usually load the key
+/// into the register. `before` holds the offsets of the instructions before the check.
+fn key_in_register(rom: &[u8], before: &[usize], x: usize) -> Option<u8> {
+    for &offset in before.iter().rev().take(KEY_LOOKBACK) {
+        match Instruction::try_from(word(rom, offset)) {
+            Ok(Instruction::ConstVxNN(register, key)) if register == x => {
+                return (key < 16).then_some(key);
+            }
+            Ok(instruction) if writes_register(instruction, x) => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Whether an instruction can change register X.
+fn writes_register(instruction: Instruction, x: usize) -> bool {
+    match instruction {
+        Instruction::ConstVxNN(register, _)
+        | Instruction::ConstVxAddNN(register, _)
+        | Instruction::AssignVxVy(register, _)
+        | Instruction::BitOpOR(register, _)
+        | Instruction::BitOpAND(register, _)
+        | Instruction::BitOpXOR(register, _)
+        | Instruction::MathVxVyAdd(register, _)
+        | Instruction::MathVxVySub(register, _)
+        | Instruction::MathVyVxSub(register, _)
+        | Instruction::BitOpShiftRight(register, _)
+        | Instruction::BitOpShiftLeft(register, _)
+        | Instruction::RandomANDVxNN(register, _)
+        | Instruction::DelayTimerSaveVx(register)
+        | Instruction::KeyOpGetKey(register) => register == x,
+        Instruction::MemRegisterLoad(last) => x <= last,
+        _ => false,
+    }
+}
+
+/// Whether an instruction reads VF to decide what to do next.
+fn checks_vf(instruction: Instruction) -> bool {
+    match instruction {
+        Instruction::CondVxNNEq(x, _) | Instruction::CondVxNNNeq(x, _) => x == 0xF,
+        Instruction::CondVxVyEq(x, y) | Instruction::CondVxVyNeq(x, y) => x == 0xF || y == 0xF,
+        Instruction::AssignVxVy(_, y) => y == 0xF,
+        _ => false,
+    }
+}
@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod rom_analysis_test {
+    use crate::chip8::analysis::{analyze, reachable, Region};
+    use crate::chip8::asm::assemble;
+
+    #[test]
+    fn reachable_code_test() {
+        let rom = assemble("CALL sub\nSE V0, 1\nJP end\nCLS\nend: JP end\nsub: RET\nDB 0x12, 0x34")
+            .unwrap();
+        assert_eq!(reachable(&rom), vec![0, 2, 4, 6, 8, 10]);
+
+        let rom = assemble("JP start\nDW 0x00FF\nstart: JP start").unwrap();
+        assert_eq!(reachable(&rom), vec![0, 4]);
+    }
+
+    #[test]
+    fn program_requirements_test() {
+        let rom = assemble(
+            "LD I, loop
+            loop: LD V0, 4
+            SKNP V0
+            ADD V1, 1
+            LD V2, K
+            SKP V2
+            ADD I, V1
+            SE VF, 1
+            LD [I], V1
+            JP V0, loop
+            sprite: DB 0xFF, 0x81",
+        )
+        .unwrap();
+        let analysis = analyze(&rom);
+
+        assert_eq!(analysis.keys.iter().copied().collect::<Vec<_>>(), vec![4]);
+        assert_eq!(analysis.unknown_key_checks, 1);
+        assert!(analysis.waits_for_key);
+        assert_eq!(analysis.computed_jumps, vec![0x212]);
+        assert_eq!(analysis.index_overflow_checks, vec![0x20C]);
+        assert_eq!(analysis.code_writes, vec![0x202]);
+        assert_eq!(analysis.opcodes["ConstVxAddNN"], 1);
+        assert!(analysis.invalid.is_empty());
+        assert_eq!(
+            analysis.regions,
+            vec![
+                Region {
+                    start: 0x200,
+                    end: 0x213,
+                    code: true
+                },
+                Region {
+                    start: 0x214,
+                    end: 0x215,
+                    code: false
+                },
+            ]
+        );
+    }
+}
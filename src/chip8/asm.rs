@@ -0,0 +1,214 @@
+use super::instructions::Instruction;
+use std::collections::HashMap;
+
+/// The address programs are loaded at, which is where labels start counting from.
+const PROGRAM_START: u16 = 0x200;
+
+/// An operand of an instruction, after any labels have been replaced by their addresses.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Operand {
+    Register(usize),
+    I,
+    /// `[I]`, the memory I points to.
+    IndirectI,
+    DelayTimer,
+    SoundTimer,
+    Key,
+    Font,
+    Bcd,
+    Value(u16),
+}
+
+/// Assembles a program written with the mnemonics instructions are displayed with (e.g. `LD VA, 0x02`) into the bytes
+/// of a ROM.
+///
+/// Each line holds at most one instruction, and may start with a label such as `loop:`. Labels can be used anywhere an
+/// address is expected. Numbers are decimal, or hex with `0x` or `#`, or binary with `0b`. `DB` and `DW` insert bytes
+/// and 16-bit words, and `;` starts a comment.
+pub fn assemble(source: &str) -> Result<Vec<u8>, String> {
+    // The first pass works out the address of every label, so that the second can use labels defined further down.
+    let labels = labels(source)?;
+    let mut rom = Vec::new();
+    for (number, line) in source.lines().enumerate() {
+        if let (_, Some((mnemonic, operands))) = split_label(line) {
+            assemble_statement(mnemonic, &operands, &labels, &mut rom)
+                .map_err(|err| format!("line {}: {}", number + 1, err))?;
+        }
+    }
+    Ok(rom)
+}
+
+/// Works out the address of every label of a program.
+pub fn labels(source: &str) -> Result<HashMap<String, u16>, String> {
+    let mut labels = HashMap::new();
+    let mut address = PROGRAM_START;
+    for (number, line) in source.lines().enumerate() {
+        let (label, statement) = split_label(line);
+        if let Some(label) = label {
+            if labels.insert(label.to_string(), address).is_some() {
+                return Err(format!(
+                    "line {}: label '{}' is defined twice",
+                    number + 1,
+                    label
+                ));
+            }
+        }
+        if let Some((mnemonic, operands)) = statement {
+            address += match mnemonic.to_ascii_uppercase().as_str() {
+                "DB" => operands.len() as u16,
+                "DW" => 2 * operands.len() as u16,
+                _ => 2,
+            };
+        }
+    }
+    Ok(labels)
+}
+
+/// Splits a line into its label and its statement, which is the mnemonic followed by its operands.
+fn split_label(line: &str) -> (Option<&str>, Option<(&str, Vec<&str>)>) {
+    let line = line.split(';').next().unwrap_or_default().trim();
+    let (label, rest) = match line.find(':') {
+        Some(colon) => (Some(line[..colon].trim()), line[colon + 1..].trim()),
+        None => (None, line),
+    };
+    if rest.is_empty() {
+        return (label, None);
+    }
+
+    let mut parts = rest.splitn(2, char::is_whitespace);
+    let mnemonic = parts.next().unwrap_or_default();
+    let operands = match parts.next() {
+        Some(operands) => operands.split(',').map(str::trim).collect(),
+        None => Vec::new(),
+    };
+    (label, Some((mnemonic, operands)))
+}
+
+fn assemble_statement(
+    mnemonic: &str,
+    operands: &[&str],
+    labels: &HashMap<String, u16>,
+    rom: &mut Vec<u8>,
+) -> Result<(), String> {
+    let operands = operands
+        .iter()
+        .map(|operand| parse_operand(operand, labels))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mnemonic = mnemonic.to_ascii_uppercase();
+
+    match mnemonic.as_str() {
+        "DB" => {
+            for operand in operands {
+                rom.push(value(operand, 0xFF)? as u8);
+            }
+        }
+        "DW" => {
+            for operand in operands {
+                rom.extend_from_slice(&value(operand, 0xFFFF)?.to_be_bytes());
+            }
+        }
+        _ => {
+            let instruction = instruction(&mnemonic, &operands)?;
+            rom.extend_from_slice(&instruction.opcode().to_be_bytes());
+        }
+    }
+    Ok(())
+}
+
+fn parse_operand(operand: &str, labels: &HashMap<String, u16>) -> Result<Operand, String> {
+    let upper = operand.to_ascii_uppercase();
+    let operand = match upper.as_str() {
+        "I" => Operand::I,
+        "[I]" => Operand::IndirectI,
+        "DT" => Operand::DelayTimer,
+        "ST" => Operand::SoundTimer,
+        "K" => Operand::Key,
+        "F" => Operand::Font,
+        "B" => Operand::Bcd,
+        _ if upper.len() == 2 && upper.starts_with('V') => {
+            let register = usize::from_str_radix(&upper[1..], 16)
+                .map_err(|_| format!("'{}' is not a register", operand))?;
+            Operand::Register(register)
+        }
+        _ => {
+            let number = if let Some(hex) = upper.strip_prefix("0X").or(upper.strip_prefix('#')) {
+                u16::from_str_radix(hex, 16).ok()
+            } else if let Some(binary) = upper.strip_prefix("0B") {
+                u16::from_str_radix(binary, 2).ok()
+            } else if upper.starts_with(|c: char| c.is_ascii_digit()) {
+                upper.parse().ok()
+            } else {
+                let label = labels
+                    .get(operand)
+                    .ok_or_else(|| format!("unknown label '{}'", operand))?;
+                Some(*label)
+            };
+            Operand::Value(number.ok_or_else(|| format!("'{}' is not a number", operand))?)
+        }
+    };
+    Ok(operand)
+}
+
+/// Returns the number in an operand, checking that it isn't larger than `max`.
+fn value(operand: Operand, max: u16) -> Result<u16, String> {
+    match operand {
+        Operand::Value(value) if value <= max => Ok(value),
+        Operand::Value(value) => Err(format!("{:#X} is larger than {:#X}", value, max)),
+        _ => Err(format!("expected a number, found {:?}", operand)),
+    }
+}
+
+fn instruction(mnemonic: &str, operands: &[Operand]) -> Result<Instruction, String> {
+    use Operand::*;
+
+    let byte = |operand| value(operand, 0xFF).map(|value| value as u8);
+    let address = |operand| value(operand, 0xFFF);
+    let instruction = match (mnemonic, operands) {
+        ("CLS", []) => Instruction::DisplayClear,
+        ("RET", []) => Instruction::FlowReturn,
+        ("JP", [Register(0), target]) => Instruction::FlowJumpOffsetV0(address(*target)? as usize),
+        ("JP", [target]) => Instruction::FlowJump(address(*target)? as usize),
+        ("CALL", [target]) => Instruction::FlowCall(address(*target)?),
+        ("SE", [Register(x), Register(y)]) => Instruction::CondVxVyEq(*x, *y),
+        ("SE", [Register(x), nn]) => Instruction::CondVxNNEq(*x, byte(*nn)?),
+        ("SNE", [Register(x), Register(y)]) => Instruction::CondVxVyNeq(*x, *y),
+        ("SNE", [Register(x), nn]) => Instruction::CondVxNNNeq(*x, byte(*nn)?),
+        ("LD", [Register(x), Register(y)]) => Instruction::AssignVxVy(*x, *y),
+        ("LD", [Register(x), DelayTimer]) => Instruction::DelayTimerSaveVx(*x),
+        ("LD", [Register(x), Key]) => Instruction::KeyOpGetKey(*x),
+        ("LD", [Register(x), IndirectI]) => Instruction::MemRegisterLoad(*x),
+        ("LD", [Register(x), nn]) => Instruction::ConstVxNN(*x, byte(*nn)?),
+        ("LD", [I, target]) => Instruction::MemSetIAddress(address(*target)?),
+        ("LD", [DelayTimer, Register(x)]) => Instruction::DelayTimerSetVx(*x),
+        ("LD", [SoundTimer, Register(x)]) => Instruction::SoundTimerSetVx(*x),
+        ("LD", [Font, Register(x)]) => Instruction::MemSetISprite(*x),
+        ("LD", [Bcd, Register(x)]) => Instruction::BCDSave(*x),
+        ("LD", [IndirectI, Register(x)]) => Instruction::MemRegisterDump(*x),
+        ("ADD", [Register(x), Register(y)]) => Instruction::MathVxVyAdd(*x, *y),
+        ("ADD", [Register(x), nn]) => Instruction::ConstVxAddNN(*x, byte(*nn)?),
+        ("ADD", [I, Register(x)]) => Instruction::MemAddIVx(*x),
+        ("OR", [Register(x), Register(y)]) => Instruction::BitOpOR(*x, *y),
+        ("AND", [Register(x), Register(y)]) => Instruction::BitOpAND(*x, *y),
+        ("XOR", [Register(x), Register(y)]) => Instruction::BitOpXOR(*x, *y),
+        ("SUB", [Register(x), Register(y)]) => Instruction::MathVxVySub(*x, *y),
+        ("SUBN", [Register(x), Register(y)]) => Instruction::MathVyVxSub(*x, *y),
+        ("SHR", [Register(x), Register(y)]) => Instruction::BitOpShiftRight(*x, *y),
+        ("SHR", [Register(x)]) => Instruction::BitOpShiftRight(*x, *x),
+        ("SHL", [Register(x), Register(y)]) => Instruction::BitOpShiftLeft(*x, *y),
+        ("SHL", [Register(x)]) => Instruction::BitOpShiftLeft(*x, *x),
+        ("RND", [Register(x), nn]) => Instruction::RandomANDVxNN(*x, byte(*nn)?),
+        ("DRW", [Register(x), Register(y), n]) => {
+            Instruction::DrawSprite(*x, *y, value(*n, 0xF)? as usize)
+        }
+        ("SKP", [Register(x)]) => Instruction::KeyOpKeyPressed(*x),
+        ("SKNP", [Register(x)]) => Instruction::KeyOpKeyNotPressed(*x),
+        _ => {
+            return Err(format!(
+                "'{}' does not take {} operand(s) of these types",
+                mnemonic,
+                operands.len()
+            ))
+        }
+    };
+    Ok(instruction)
+}
@@ -0,0 +1,36 @@
+#[cfg(test)]
+mod assemble_test {
+    use crate::chip8::asm::assemble;
+
+    #[test]
+    fn assemble_test() {
+        let source = "
+            start:  CLS             ; clear the screen
+                    LD VA, 0x02
+                    LD I, sprite
+                    DRW VA, VA, 2
+                    JP start
+            sprite: DB 0xFF, #81
+                    DW 0x1234
+        ";
+        let rom = assemble(source).unwrap();
+        assert_eq!(
+            rom,
+            vec![
+                0x00, 0xE0, 0x6A, 0x02, 0xA2, 0x0A, 0xDA, 0xA2, 0x12, 0x00, 0xFF, 0x81, 0x12, 0x34
+            ]
+        );
+    }
+
+    #[test]
+    fn assemble_error_line_test() {
+        assert_eq!(
+            assemble("CLS\nLD V0, 0x100"),
+            Err(String::from("line 2: 0x100 is larger than 0xFF"))
+        );
+        assert_eq!(
+            assemble("JP nowhere"),
+            Err(String::from("line 1: unknown label 'nowhere'"))
+        );
+    }
+}
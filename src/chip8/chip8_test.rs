@@ -0,0 +1,100 @@
+#[cfg(test)]
+mod machine_test {
+    use crate::chip8::asm::assemble;
+    use crate::chip8::instructions::Instruction;
+    use crate::chip8::{Chip8, CHIP8_MAX_ROM_SIZE};
+
+    #[test]
+    fn reload_rom_test() {
+        let mut emu = Chip8::new();
+        emu.load_rom_bytes(
+            &assemble("LD V3, 7\nLD I, 0x300\nCALL 0x208\nDB 0xAB, 0xCD, 0xEF").unwrap(),
+        )
+        .unwrap();
+        for _ in 0..3 {
+            emu.emulate_cycle().unwrap();
+        }
+        assert_eq!(emu.call_stack().len(), 1);
+
+        emu.load_rom_bytes(&[0x00, 0xE0]).unwrap();
+        assert_eq!(emu.program_counter(), 0x200);
+        assert_eq!(emu.registers()[3], 0);
+        assert_eq!(emu.index(), 0);
+        assert!(emu.call_stack().is_empty());
+        // The bytes of the longer ROM that was loaded before are gone.
+        assert_eq!(emu.read_memory(0x206), 0);
+        // The font is still there.
+        assert_eq!(emu.read_memory(0x50), 0xF0);
+    }
+
+    #[test]
+    fn reset_test() {
+        let mut emu = Chip8::new();
+        let rom = assemble("LD V0, 9\nLD F, V0\nDRW V0, V0, 5\nLD DT, V0\nCALL 0x200").unwrap();
+        emu.load_rom_bytes(&rom).unwrap();
+        emu.display.take_dirty();
+        for _ in 0..5 {
+            emu.emulate_cycle().unwrap();
+        }
+        assert_eq!(emu.timers().0, 9);
+        assert!(emu.display.screen.iter().flatten().any(|&pixel| pixel != 0));
+
+        emu.reset();
+        assert_eq!(emu.program_counter(), 0x200);
+        assert_eq!(emu.registers()[0], 0);
+        assert_eq!(emu.timers(), (0, 0));
+        assert!(emu.call_stack().is_empty());
+        assert!(emu.display.screen.iter().flatten().all(|&pixel| pixel == 0));
+        // The cleared screen has to be drawn.
+        assert!(emu.display.should_draw());
+        assert_eq!(emu.read_memory(0x200), rom[0]);
+    }
+
+    #[test]
+    fn rom_too_large_test() {
+        let mut emu = Chip8::new();
+        assert!(emu.load_rom_bytes(&vec![0; CHIP8_MAX_ROM_SIZE]).is_ok());
+        let err = emu
+            .load_rom_bytes(&vec![0; CHIP8_MAX_ROM_SIZE + 2])
+            .unwrap_err();
+        assert!(err.contains("2 bytes more"), "{}", err);
+    }
+
+    #[test]
+    fn index_wraps_around_memory_test() {
+        let mut emu = Chip8::new();
+        emu.load_rom_bytes(
+            &assemble("LD I, 0xFFF\nDRW V0, V0, 2\nLD B, V0\nLD [I], V3\nLD V3, [I]").unwrap(),
+        )
+        .unwrap();
+        emu.write_memory(0xFFF, 0x80);
+        emu.write_memory(0x000, 0x80);
+        for _ in 0..5 {
+            emu.emulate_cycle().unwrap();
+        }
+        // The sprite was read from 0xFFF and then from the start of memory.
+        assert_eq!(emu.display.screen[0][..2], [1, 1]);
+        assert_eq!(emu.read_memory(0x000), 0);
+        assert_eq!(emu.program_counter(), 0x20A);
+
+        emu.load_rom_bytes(&assemble("LD I, 0xFFF\nLD V0, 0xFF\nADD I, V0").unwrap())
+            .unwrap();
+        for _ in 0..3 {
+            emu.emulate_cycle().unwrap();
+        }
+        assert_eq!(emu.index(), 0x10FE);
+    }
+
+    #[test]
+    fn program_counter_wraps_around_memory_test() {
+        let mut emu = Chip8::new();
+        emu.load_rom_bytes(&assemble("JP 0xFFF").unwrap()).unwrap();
+        emu.write_memory(0xFFF, 0x00);
+        emu.write_memory(0x000, 0xE0);
+        emu.emulate_cycle().unwrap();
+        assert_eq!(emu.program_counter(), 0xFFF);
+        assert_eq!(emu.next_instruction(), Ok(Instruction::DisplayClear));
+        emu.emulate_cycle().unwrap();
+        assert_eq!(emu.program_counter(), 0x001);
+    }
+}
@@ -0,0 +1,31 @@
+use super::instructions::Instruction;
+use std::convert::TryFrom;
+use std::fmt::Write;
+
+/// Disassembles a ROM, one instruction per line, each followed by a comment with its address and opcode. Words that
+/// aren't valid instructions are written as `DW`, so that assembling the output gives back the same ROM.
+pub fn disassemble(rom: &[u8]) -> String {
+    let mut listing = String::new();
+    for (i, word) in rom.chunks(2).enumerate() {
+        let address = 0x200 + 2 * i;
+        let (text, encoded) = match *word {
+            [hi, lo] => {
+                let opcode = u16::from_be_bytes([hi, lo]);
+                (disassemble_opcode(opcode), format!("{:04X}", opcode))
+            }
+            [byte] => (format!("DB 0x{:02X}", byte), format!("{:02X}", byte)),
+            _ => unreachable!(),
+        };
+        let _ = writeln!(listing, "    {:<20} ; {:03X}: {}", text, address, encoded);
+    }
+    listing
+}
+
+/// Disassembles a single opcode, as `DW` if it isn't a valid instruction.
+pub fn disassemble_opcode(opcode: u16) -> String {
+    match Instruction::try_from(opcode) {
+        // Some opcodes have bits the instruction ignores, which would be lost if assembled again.
+        Ok(instruction) if instruction.opcode() == opcode => instruction.to_string(),
+        _ => format!("DW 0x{:04X}", opcode),
+    }
+}
@@ -0,0 +1,23 @@
+#[cfg(test)]
+mod disassemble_test {
+    use crate::chip8::asm::assemble;
+    use crate::chip8::disasm::disassemble;
+
+    #[test]
+    fn disassemble_test() {
+        let listing = disassemble(&[0x6A, 0x02, 0xF1, 0x55]);
+        assert_eq!(
+            listing,
+            "    LD VA, 0x02          ; 200: 6A02\n    LD [I], V1           ; 202: F155\n"
+        );
+    }
+
+    #[test]
+    fn disassemble_round_trip_test() {
+        // Includes an invalid opcode, an opcode with ignored bits and a trailing odd byte.
+        let rom = vec![
+            0x00, 0xE0, 0xFF, 0xFF, 0x51, 0x21, 0xB3, 0x00, 0xD1, 0x25, 0x7F,
+        ];
+        assert_eq!(assemble(&disassemble(&rom)).unwrap(), rom);
+    }
+}
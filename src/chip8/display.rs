@@ -1,71 +1,107 @@
-use sdl2::pixels::Color;
-use sdl2::rect::Rect;
-use sdl2::render::WindowCanvas;
+pub const CHIP8_SCREEN_WIDTH: usize = 64;
+pub const CHIP8_SCREEN_HEIGHT: usize = 32;
 
-pub struct Display {
-    pub canvas: WindowCanvas,
-    pub screen: [[u8; 32]; 64],
-    scale: u32,
-    should_draw: bool,
+/// A rectangle of the framebuffer, in pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Region {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
 }
 
-impl Display {
-    pub fn new(ctx: &sdl2::Sdl, window_scale: u32) -> Self {
-        let video = ctx.video().unwrap();
-        let window = video
-            .window("CHIP-8 Emulator", 64 * window_scale, 32 * window_scale)
-            .position_centered()
-            .opengl()
-            .build()
-            .unwrap();
+impl Region {
+    /// The region covering the whole screen.
+    pub fn screen() -> Self {
+        Region {
+            x: 0,
+            y: 0,
+            width: CHIP8_SCREEN_WIDTH,
+            height: CHIP8_SCREEN_HEIGHT,
+        }
+    }
 
-        let mut canvas = window.into_canvas().present_vsync().build().unwrap();
-        canvas.clear();
-        canvas.present();
+    /// Returns the smallest region covering both regions.
+    pub fn union(&self, other: &Region) -> Region {
+        let (x, y) = (self.x.min(other.x), self.y.min(other.y));
+        let right = (self.x + self.width).max(other.x + other.width);
+        let bottom = (self.y + self.height).max(other.y + other.height);
+        Region {
+            x,
+            y,
+            width: right - x,
+            height: bottom - y,
+        }
+    }
+}
 
+/// Represents the monochrome framebuffer of the CHIP-8. The framebuffer does not know how it gets presented; frontends
+/// read it after each frame and draw it however they like.
+pub struct Display {
+    pub screen: [[u8; CHIP8_SCREEN_HEIGHT]; CHIP8_SCREEN_WIDTH],
+    /// The part of the screen that was drawn to since the frontend last looked at it.
+    dirty: Option<Region>,
+}
+
+impl Display {
+    pub fn new() -> Self {
         Display {
-            canvas,
-            screen: [[0; 32]; 64],
-            scale: window_scale,
-            should_draw: false,
+            screen: [[0; CHIP8_SCREEN_HEIGHT]; CHIP8_SCREEN_WIDTH],
+            dirty: None,
         }
     }
 
-    pub fn set_should_draw(&mut self, should_draw: bool) {
-        self.should_draw = should_draw
+    /// Marks part of the screen as drawn to. Regions that reach past the edge of the screen wrap around, so they mark
+    /// the whole width or height of the screen instead.
+    pub fn mark_dirty(&mut self, region: Region) {
+        let mut region = region;
+        if region.x + region.width > CHIP8_SCREEN_WIDTH {
+            region.x = 0;
+            region.width = CHIP8_SCREEN_WIDTH;
+        }
+        if region.y + region.height > CHIP8_SCREEN_HEIGHT {
+            region.y = 0;
+            region.height = CHIP8_SCREEN_HEIGHT;
+        }
+
+        self.dirty = Some(match self.dirty {
+            Some(dirty) => dirty.union(&region),
+            None => region,
+        });
     }
 
+    /// Whether anything was drawn since the frontend last looked at the screen.
     pub fn should_draw(&self) -> bool {
-        self.should_draw
+        self.dirty.is_some()
     }
 
-    pub fn clear_screen(&mut self) {
-        for x in 0..self.screen.len() {
-            for y in 0..self.screen[x].len() {
-                self.screen[x][y] = 0;
+    /// Returns the part of the screen that was drawn to since the last call, if anything was.
+    pub fn take_dirty(&mut self) -> Option<Region> {
+        self.dirty.take()
+    }
+
+    /// Returns the value of the pixel at the given coordinate, which is 0 when the pixel is turned off.
+    pub fn get_pixel(&self, x: usize, y: usize) -> u8 {
+        self.screen[x][y]
+    }
+
+    /// Draws the screen as text, with `#` for pixels that are on and `.` for pixels that are off, one line per row.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for y in 0..CHIP8_SCREEN_HEIGHT {
+            for x in 0..CHIP8_SCREEN_WIDTH {
+                text.push(if self.screen[x][y] != 0 { '#' } else { '.' });
             }
+            text.push('\n');
         }
+        text
     }
 
-    pub fn draw_screen(&mut self) {
+    pub fn clear_screen(&mut self) {
         for x in 0..self.screen.len() {
             for y in 0..self.screen[x].len() {
-                let x_pos = x as u32 * self.scale;
-                let y_pos = y as u32 * self.scale;
-
-                self.canvas.set_draw_color(if self.screen[x][y] == 1 {
-                    Color::WHITE
-                } else {
-                    Color::BLACK
-                });
-                let _ = self.canvas.fill_rect(Rect::new(
-                    x_pos as i32,
-                    y_pos as i32,
-                    self.scale,
-                    self.scale,
-                ));
+                self.screen[x][y] = 0;
             }
         }
-        self.canvas.present();
     }
 }
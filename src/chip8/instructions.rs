@@ -1,4 +1,5 @@
 use std::convert::TryFrom;
+use std::fmt;
 
 /// The instructions available on the CHIP-8 CPU. Each enum contains all the necessary information needed to carry out
 /// the instruction (memory address, register number, byte value, etc.), but does not perform the instruction itself.
@@ -30,7 +31,7 @@ use std::convert::TryFrom;
 ///
 /// See https://en.wikipedia.org/wiki/CHIP-8#Opcode_table for more information, as well as verification that these
 /// opcodes are being decoded correctly.
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Instruction {
     /// Clears the display.
     /// Opcode: 00E0
@@ -92,7 +93,8 @@ pub enum Instruction {
     /// Opcode: 8XY5
     MathVxVySub(usize, usize),
 
-    /// Stores the least significant bit of Vx in VF and then shifts Vx to the right by 1.
+    /// Stores the least significant bit of Vx in VF and then shifts Vx to the right by 1. With the shift quirk, Vy is
+    /// shifted into Vx instead.
     /// Opcode: 8XY6
     BitOpShiftRight(usize, usize),
 
@@ -100,7 +102,8 @@ pub enum Instruction {
     /// Opcode: 8XY7
     MathVyVxSub(usize, usize),
 
-    /// Stores the most significant bit of Vx in VF and then shifts Vx to the left by 1.
+    /// Stores the most significant bit of Vx in VF and then shifts Vx to the left by 1. With the shift quirk, Vy is
+    /// shifted into Vx instead.
     /// Opcode: 8XYE
     BitOpShiftLeft(usize, usize),
 
@@ -187,6 +190,98 @@ impl Instruction {
     }
 }
 
+impl Instruction {
+    /// Encodes the instruction back into its opcode.
+    pub fn opcode(&self) -> u16 {
+        let xy = |high: u16, x: usize, y: usize, low: u16| {
+            high | (x as u16) << 8 | (y as u16) << 4 | low
+        };
+        let xnn = |high: u16, x: usize, byte: u8| high | (x as u16) << 8 | byte as u16;
+        let address = |high: u16, addr: u16| high | (addr & 0xFFF);
+
+        match *self {
+            Instruction::DisplayClear => 0x00E0,
+            Instruction::FlowReturn => 0x00EE,
+            Instruction::FlowJump(addr) => address(0x1000, addr as u16),
+            Instruction::FlowCall(addr) => address(0x2000, addr),
+            Instruction::CondVxNNEq(x, byte) => xnn(0x3000, x, byte),
+            Instruction::CondVxNNNeq(x, byte) => xnn(0x4000, x, byte),
+            Instruction::CondVxVyEq(x, y) => xy(0x5000, x, y, 0x0),
+            Instruction::ConstVxNN(x, byte) => xnn(0x6000, x, byte),
+            Instruction::ConstVxAddNN(x, byte) => xnn(0x7000, x, byte),
+            Instruction::AssignVxVy(x, y) => xy(0x8000, x, y, 0x0),
+            Instruction::BitOpOR(x, y) => xy(0x8000, x, y, 0x1),
+            Instruction::BitOpAND(x, y) => xy(0x8000, x, y, 0x2),
+            Instruction::BitOpXOR(x, y) => xy(0x8000, x, y, 0x3),
+            Instruction::MathVxVyAdd(x, y) => xy(0x8000, x, y, 0x4),
+            Instruction::MathVxVySub(x, y) => xy(0x8000, x, y, 0x5),
+            Instruction::BitOpShiftRight(x, y) => xy(0x8000, x, y, 0x6),
+            Instruction::MathVyVxSub(x, y) => xy(0x8000, x, y, 0x7),
+            Instruction::BitOpShiftLeft(x, y) => xy(0x8000, x, y, 0xE),
+            Instruction::CondVxVyNeq(x, y) => xy(0x9000, x, y, 0x0),
+            Instruction::MemSetIAddress(addr) => address(0xA000, addr),
+            Instruction::FlowJumpOffsetV0(addr) => address(0xB000, addr as u16),
+            Instruction::RandomANDVxNN(x, byte) => xnn(0xC000, x, byte),
+            Instruction::DrawSprite(x, y, height) => xy(0xD000, x, y, height as u16),
+            Instruction::KeyOpKeyPressed(x) => xnn(0xE000, x, 0x9E),
+            Instruction::KeyOpKeyNotPressed(x) => xnn(0xE000, x, 0xA1),
+            Instruction::DelayTimerSaveVx(x) => xnn(0xF000, x, 0x07),
+            Instruction::KeyOpGetKey(x) => xnn(0xF000, x, 0x0A),
+            Instruction::DelayTimerSetVx(x) => xnn(0xF000, x, 0x15),
+            Instruction::SoundTimerSetVx(x) => xnn(0xF000, x, 0x18),
+            Instruction::MemAddIVx(x) => xnn(0xF000, x, 0x1E),
+            Instruction::MemSetISprite(x) => xnn(0xF000, x, 0x29),
+            Instruction::BCDSave(x) => xnn(0xF000, x, 0x33),
+            Instruction::MemRegisterDump(x) => xnn(0xF000, x, 0x55),
+            Instruction::MemRegisterLoad(x) => xnn(0xF000, x, 0x65),
+        }
+    }
+}
+
+/// Formats instructions as assembly, using the mnemonics from Cowgod's Chip-8 Technical Reference, e.g. `LD VA, 0x02`.
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Instruction::DisplayClear => write!(f, "CLS"),
+            Instruction::FlowReturn => write!(f, "RET"),
+            Instruction::FlowJump(addr) => write!(f, "JP 0x{:03X}", addr),
+            Instruction::FlowCall(addr) => write!(f, "CALL 0x{:03X}", addr),
+            Instruction::CondVxNNEq(x, byte) => write!(f, "SE V{:X}, 0x{:02X}", x, byte),
+            Instruction::CondVxNNNeq(x, byte) => write!(f, "SNE V{:X}, 0x{:02X}", x, byte),
+            Instruction::CondVxVyEq(x, y) => write!(f, "SE V{:X}, V{:X}", x, y),
+            Instruction::ConstVxNN(x, byte) => write!(f, "LD V{:X}, 0x{:02X}", x, byte),
+            Instruction::ConstVxAddNN(x, byte) => write!(f, "ADD V{:X}, 0x{:02X}", x, byte),
+            Instruction::AssignVxVy(x, y) => write!(f, "LD V{:X}, V{:X}", x, y),
+            Instruction::BitOpOR(x, y) => write!(f, "OR V{:X}, V{:X}", x, y),
+            Instruction::BitOpAND(x, y) => write!(f, "AND V{:X}, V{:X}", x, y),
+            Instruction::BitOpXOR(x, y) => write!(f, "XOR V{:X}, V{:X}", x, y),
+            Instruction::MathVxVyAdd(x, y) => write!(f, "ADD V{:X}, V{:X}", x, y),
+            Instruction::MathVxVySub(x, y) => write!(f, "SUB V{:X}, V{:X}", x, y),
+            Instruction::BitOpShiftRight(x, y) => write!(f, "SHR V{:X}, V{:X}", x, y),
+            Instruction::MathVyVxSub(x, y) => write!(f, "SUBN V{:X}, V{:X}", x, y),
+            Instruction::BitOpShiftLeft(x, y) => write!(f, "SHL V{:X}, V{:X}", x, y),
+            Instruction::CondVxVyNeq(x, y) => write!(f, "SNE V{:X}, V{:X}", x, y),
+            Instruction::MemSetIAddress(addr) => write!(f, "LD I, 0x{:03X}", addr),
+            Instruction::FlowJumpOffsetV0(addr) => write!(f, "JP V0, 0x{:03X}", addr),
+            Instruction::RandomANDVxNN(x, byte) => write!(f, "RND V{:X}, 0x{:02X}", x, byte),
+            Instruction::DrawSprite(x, y, height) => {
+                write!(f, "DRW V{:X}, V{:X}, {}", x, y, height)
+            }
+            Instruction::KeyOpKeyPressed(x) => write!(f, "SKP V{:X}", x),
+            Instruction::KeyOpKeyNotPressed(x) => write!(f, "SKNP V{:X}", x),
+            Instruction::DelayTimerSaveVx(x) => write!(f, "LD V{:X}, DT", x),
+            Instruction::KeyOpGetKey(x) => write!(f, "LD V{:X}, K", x),
+            Instruction::DelayTimerSetVx(x) => write!(f, "LD DT, V{:X}", x),
+            Instruction::SoundTimerSetVx(x) => write!(f, "LD ST, V{:X}", x),
+            Instruction::MemAddIVx(x) => write!(f, "ADD I, V{:X}", x),
+            Instruction::MemSetISprite(x) => write!(f, "LD F, V{:X}", x),
+            Instruction::BCDSave(x) => write!(f, "LD B, V{:X}", x),
+            Instruction::MemRegisterDump(x) => write!(f, "LD [I], V{:X}", x),
+            Instruction::MemRegisterLoad(x) => write!(f, "LD V{:X}, [I]", x),
+        }
+    }
+}
+
 impl TryFrom<u16> for Instruction {
     type Error = String;
 
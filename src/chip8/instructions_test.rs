@@ -226,3 +226,28 @@ mod instructions_parse_test {
         }
     }
 }
+
+#[cfg(test)]
+mod instructions_encode_test {
+    use crate::chip8::instructions::Instruction;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn opcode_round_trip_test() {
+        for &opcode in &[
+            0x00E0, 0x1FA3, 0x3B22, 0x8296, 0xB123, 0xD125, 0xE3A1, 0xF765,
+        ] {
+            let instr = Instruction::try_from(opcode).unwrap();
+            assert_eq!(instr.opcode(), opcode, "{:?}", instr);
+        }
+    }
+
+    #[test]
+    fn display_instruction_test() {
+        let format = |opcode| Instruction::try_from(opcode).unwrap().to_string();
+        assert_eq!(format(0x2A02), "CALL 0xA02");
+        assert_eq!(format(0x8AB4), "ADD VA, VB");
+        assert_eq!(format(0xD125), "DRW V1, V2, 5");
+        assert_eq!(format(0xF065), "LD V0, [I]");
+    }
+}
@@ -1,7 +1,7 @@
-use std::fs;
-
-const CHIP8_MEM_SIZE: usize = 0x1000;
+pub const CHIP8_MEM_SIZE: usize = 0x1000;
 const CHIP8_MEM_START: usize = 0x200;
+/// The largest ROM that fits in memory, which is everything after the memory the interpreter used to live in.
+pub const CHIP8_MAX_ROM_SIZE: usize = CHIP8_MEM_SIZE - CHIP8_MEM_START;
 const CHIP8_MEM_FONT_START: usize = 0x50;
 
 const CHIP8_FONT_SET: [u8; 80] = [
@@ -48,37 +48,51 @@ impl Memory {
         self.program_counter
     }
 
-    /// Loads a CHIP-8 rom file into the memory.
-    pub fn load_rom(&mut self, rom: &str) {
-        let buffer = fs::read(rom).unwrap();
-        for (i, char) in buffer.iter().enumerate() {
-            self.mem[CHIP8_MEM_START + i] = *char;
+    /// Loads a CHIP-8 rom into the memory, clearing anything a previous rom left behind. Fails without changing the
+    /// memory if the rom is too large to fit.
+    pub fn load_rom(&mut self, rom: &[u8]) -> Result<(), String> {
+        if rom.len() > CHIP8_MAX_ROM_SIZE {
+            return Err(format!(
+                "The ROM is {} bytes, which is {} bytes more than the {} bytes of memory programs can use",
+                rom.len(),
+                rom.len() - CHIP8_MAX_ROM_SIZE,
+                CHIP8_MAX_ROM_SIZE
+            ));
         }
-        self.program_counter = CHIP8_MEM_START;
+        *self = Memory::new();
+        self.mem[CHIP8_MEM_START..CHIP8_MEM_START + rom.len()].copy_from_slice(rom);
+        Ok(())
     }
 
-    /// Fetches the next opcode in memory.
+    /// Fetches the next opcode in memory. An opcode at the last address takes its second byte from address 0.
     pub fn fetch_opcode(&self) -> u16 {
-        let hi = (self.mem[self.program_counter] as u16) << 8;
-        let lo = self.mem[self.program_counter + 1] as u16;
+        let hi = (self.get_mem(self.program_counter) as u16) << 8;
+        let lo = self.get_mem(self.program_counter + 1) as u16;
         hi | lo
     }
 
-    /// Jumps to the specified memory address.
+    /// Jumps to the specified memory address. Addresses past the end of memory wrap around.
     pub fn jump(&mut self, addr: usize) {
-        self.program_counter = addr;
+        self.program_counter = addr % CHIP8_MEM_SIZE;
     }
 
     /// Skips the next opcode in memory.
     pub fn next_instruction(&mut self) {
-        self.program_counter += 2;
+        self.jump(self.program_counter + 2);
     }
 
+    /// Writes a byte of memory. Addresses past the end of memory wrap around.
     pub fn set_mem(&mut self, addr: usize, value: u8) {
-        self.mem[addr] = value;
+        self.mem[addr % CHIP8_MEM_SIZE] = value;
     }
 
+    /// Reads a byte of memory. Addresses past the end of memory wrap around.
     pub fn get_mem(&self, addr: usize) -> u8 {
-        self.mem[addr]
+        self.mem[addr % CHIP8_MEM_SIZE]
+    }
+
+    /// All of memory, from address 0.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.mem
     }
 }
@@ -1,13 +1,37 @@
-mod display;
-mod input;
-mod instructions;
+pub mod analysis;
+mod analysis_test;
+pub mod asm;
+mod asm_test;
+mod chip8_test;
+pub mod disasm;
+mod disasm_test;
+pub mod display;
+pub mod input;
+pub mod instructions;
 mod instructions_test;
 mod memory;
-mod stack;
+pub mod octo;
+mod octo_test;
+pub mod quirks;
+mod quirks_test;
+pub mod search;
+mod search_test;
+pub mod stack;
+mod stack_test;
+pub mod symbols;
 
+use display::{Region, CHIP8_SCREEN_HEIGHT, CHIP8_SCREEN_WIDTH};
 use instructions::Instruction;
-use rand::Rng;
+use quirks::Quirks;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use stack::{Call, StackDepth};
 use std::convert::TryFrom;
+use std::fmt::Write;
+use std::mem;
+use symbols::Symbols;
+
+pub use memory::CHIP8_MAX_ROM_SIZE;
 
 pub struct Chip8 {
     memory: memory::Memory,
@@ -16,38 +40,188 @@ pub struct Chip8 {
     delay_timer: u8,
     sound_timer: u8,
     i: u16,
-    rand: rand::rngs::ThreadRng,
+    rand: StdRng,
+    quirks: Quirks,
+    /// Names for the addresses of the ROM, used to describe calls.
+    symbols: Symbols,
+    /// The ROM that was loaded last, which a reset loads again.
+    rom: Vec<u8>,
     pub input: input::Input,
     pub display: display::Display,
 }
 
 impl Chip8 {
     pub fn new() -> Self {
-        let sdl_ctx = sdl2::init().unwrap();
-
         Chip8 {
             memory: memory::Memory::new(),
-            stack: stack::Stack::new(),
+            stack: stack::Stack::new(StackDepth::default()),
             registers: [0; 16],
             delay_timer: 0,
             sound_timer: 0,
             i: 0,
-            rand: rand::thread_rng(),
-            input: input::Input::new(&sdl_ctx),
-            display: display::Display::new(&sdl_ctx, 10),
+            rand: StdRng::from_entropy(),
+            quirks: Quirks::default(),
+            symbols: Symbols::default(),
+            rom: Vec::new(),
+            input: input::Input::new(),
+            display: display::Display::new(),
         }
     }
 
-    pub fn emulate_cycle(&mut self) {
+    /// Runs the instruction at the program counter. Fails without changing the machine if the opcode isn't a valid
+    /// instruction, or if a call or return would overflow or underflow the stack.
+    pub fn emulate_cycle(&mut self) -> Result<(), String> {
         let opcode = self.fetch_opcode();
-        let instr = self.decode_opcode(opcode).unwrap();
-        self.execute_instruction(instr);
-        self.update_timers();
+        let instr = self.decode_opcode(opcode)?;
+        self.execute_instruction(instr)
+    }
+
+    /// Seeds the random number generator used by CXNN, so that runs given the same input behave the same way.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rand = StdRng::seed_from_u64(seed);
+    }
+
+    /// Changes how the instructions that differ between platforms behave.
+    pub fn set_quirks(&mut self, quirks: Quirks) {
+        self.quirks = quirks;
+    }
+
+    /// Changes how many calls the stack can hold. It is meant to be set before a ROM is loaded, as any calls already
+    /// on the stack are forgotten.
+    pub fn set_stack_depth(&mut self, depth: StackDepth) {
+        self.stack = stack::Stack::new(depth);
+    }
+
+    /// Names the addresses of the ROM, so that calls can be described by the names of their subroutines.
+    pub fn set_symbols(&mut self, symbols: Symbols) {
+        self.symbols = symbols;
+    }
+
+    /// Loads a CHIP-8 rom and starts it from the beginning. Everything a previous rom left behind is cleared, from
+    /// memory and registers to the screen, leaving only the settings and the random number generator as they were. Fails
+    /// without changing the machine if the rom is too large to fit in memory.
+    pub fn load_rom_bytes(&mut self, rom: &[u8]) -> Result<(), String> {
+        let mut memory = memory::Memory::new();
+        memory.load_rom(rom)?;
+        let rand = mem::replace(&mut self.rand, StdRng::seed_from_u64(0));
+        *self = Chip8 {
+            memory,
+            stack: stack::Stack::new(self.stack.depth()),
+            rand,
+            quirks: self.quirks,
+            symbols: mem::take(&mut self.symbols),
+            rom: rom.to_vec(),
+            ..Chip8::new()
+        };
+        // The screen was cleared, which frontends have to be told about like any other drawing.
+        self.display.mark_dirty(Region::screen());
+        Ok(())
+    }
+
+    /// Starts the loaded rom again from the beginning, as if the machine had just been switched on.
+    pub fn reset(&mut self) {
+        let rom = mem::take(&mut self.rom);
+        self.load_rom_bytes(&rom)
+            .expect("the rom fitted in memory when it was loaded");
+    }
+
+    /// The address of the next instruction to run.
+    pub fn program_counter(&self) -> u16 {
+        self.memory.get_program_counter() as u16
+    }
+
+    /// The instruction at the program counter, or an error if the opcode there isn't a valid instruction.
+    pub fn next_instruction(&self) -> Result<Instruction, String> {
+        self.decode_opcode(self.fetch_opcode())
+    }
+
+    /// The registers V0 to VF.
+    pub fn registers(&self) -> &[u8; 16] {
+        &self.registers
+    }
+
+    /// The address register I.
+    pub fn index(&self) -> u16 {
+        self.i
+    }
+
+    /// The delay and sound timers.
+    pub fn timers(&self) -> (u8, u8) {
+        (self.delay_timer, self.sound_timer)
+    }
+
+    /// The calls of the subroutines being run, outermost first.
+    pub fn call_stack(&self) -> &[Call] {
+        self.stack.calls()
+    }
+
+    /// The names of the ROM's addresses.
+    pub fn symbols(&self) -> &Symbols {
+        &self.symbols
+    }
+
+    /// The subroutines being run, innermost first, one per line with the address each was called from, e.g.
+    /// `draw_player (0x2B0) called from 0x20A`.
+    pub fn call_chain(&self) -> Vec<String> {
+        self.stack
+            .calls()
+            .iter()
+            .rev()
+            .map(|call| self.describe_call(call))
+            .collect()
+    }
+
+    fn describe_call(&self, call: &Call) -> String {
+        format!(
+            "{} called from {}",
+            self.symbols.describe(call.to),
+            self.symbols.describe(call.from)
+        )
     }
 
-    /// Loads a CHIP-8 rom file into the memory.
-    pub fn load_rom(&mut self, file_path: &str) {
-        self.memory.load_rom(file_path);
+    /// Explains a stack overflow or underflow, listing the most recent calls that led up to it.
+    fn stack_error(&self, err: String) -> String {
+        let mut message = err;
+        let recent: Vec<_> = self.stack.recent_calls().collect();
+        if recent.is_empty() {
+            message.push_str("\nNo subroutines have been called");
+        } else {
+            let _ = write!(
+                message,
+                "\nThe last {} calls, most recent first:",
+                recent.len()
+            );
+            for call in recent {
+                let _ = write!(message, "\n  {}", self.describe_call(call));
+            }
+        }
+        message
+    }
+
+    /// Reads a byte of memory. Addresses past the end of memory wrap around.
+    pub fn read_memory(&self, addr: u16) -> u8 {
+        self.memory.get_mem(addr as usize)
+    }
+
+    /// The rom that was loaded, as it was before it started running.
+    pub fn rom(&self) -> &[u8] {
+        &self.rom
+    }
+
+    /// All of memory, from address 0.
+    pub fn memory(&self) -> &[u8] {
+        self.memory.as_slice()
+    }
+
+    /// Writes a byte of memory, for cheats and tools that change a game from outside. Addresses past the end of memory
+    /// wrap around.
+    pub fn write_memory(&mut self, addr: u16, value: u8) {
+        self.memory.set_mem(addr as usize, value);
+    }
+
+    /// Whether the buzzer should be sounding.
+    pub fn is_sound_playing(&self) -> bool {
+        self.sound_timer > 0
     }
 
     /// Fetches the opcode at the current program counter.
@@ -61,22 +235,33 @@ impl Chip8 {
     }
 
     /// Executes the specified instruction of the CPU.
-    fn execute_instruction(&mut self, instr: Instruction) {
+    fn execute_instruction(&mut self, instr: Instruction) -> Result<(), String> {
         match instr {
             Instruction::DisplayClear => {
                 self.display.clear_screen();
+                self.display.mark_dirty(display::Region::screen());
                 self.memory.next_instruction();
             }
             Instruction::FlowReturn => {
-                self.stack.pop();
-                self.memory.jump(self.stack.peek() as usize);
+                let call = self.stack.pop().map_err(|err| self.stack_error(err))?;
+                self.memory.jump(call.from as usize);
                 self.memory.next_instruction();
             }
             Instruction::FlowJump(addr) => {
                 self.memory.jump(addr);
             }
             Instruction::FlowCall(addr) => {
-                self.stack.push(self.memory.get_program_counter() as u16);
+                let call = Call {
+                    from: self.program_counter(),
+                    to: addr,
+                };
+                self.stack.push(call).map_err(|err| {
+                    self.stack_error(format!(
+                        "{}, and CALL {} would be one more",
+                        err,
+                        self.symbols.describe(addr)
+                    ))
+                })?;
                 self.memory.jump(addr as usize);
             }
             Instruction::CondVxNNEq(reg, byte) => {
@@ -110,15 +295,24 @@ impl Chip8 {
                 self.memory.next_instruction();
             }
             Instruction::BitOpOR(x, y) => {
-                self.registers[x] = self.registers[x] | self.registers[y];
+                self.registers[x] |= self.registers[y];
+                if self.quirks.logic_resets_vf {
+                    self.registers[0xF] = 0;
+                }
                 self.memory.next_instruction();
             }
             Instruction::BitOpAND(x, y) => {
-                self.registers[x] = self.registers[x] & self.registers[y];
+                self.registers[x] &= self.registers[y];
+                if self.quirks.logic_resets_vf {
+                    self.registers[0xF] = 0;
+                }
                 self.memory.next_instruction();
             }
             Instruction::BitOpXOR(x, y) => {
-                self.registers[x] = self.registers[x] ^ self.registers[y];
+                self.registers[x] ^= self.registers[y];
+                if self.quirks.logic_resets_vf {
+                    self.registers[0xF] = 0;
+                }
                 self.memory.next_instruction();
             }
             Instruction::MathVxVyAdd(x, y) => {
@@ -145,10 +339,10 @@ impl Chip8 {
                 self.registers[x] = res;
                 self.memory.next_instruction();
             }
-            Instruction::BitOpShiftRight(x, _) => {
-                let lsb = self.registers[x] & 0b0000_0001;
-                self.registers[0xF] = lsb;
-                self.registers[x] >>= 1;
+            Instruction::BitOpShiftRight(x, y) => {
+                let value = self.shift_source(x, y);
+                self.registers[x] = value >> 1;
+                self.registers[0xF] = value & 0b0000_0001;
                 self.memory.next_instruction();
             }
             Instruction::MathVyVxSub(x, y) => {
@@ -163,10 +357,10 @@ impl Chip8 {
                 self.registers[x] = res;
                 self.memory.next_instruction();
             }
-            Instruction::BitOpShiftLeft(x, _) => {
-                let msb = self.registers[x] & 0b1000_0000;
-                self.registers[0xF] = msb >> 7;
-                self.registers[x] <<= 1;
+            Instruction::BitOpShiftLeft(x, y) => {
+                let value = self.shift_source(x, y);
+                self.registers[x] = value << 1;
+                self.registers[0xF] = value >> 7;
                 self.memory.next_instruction();
             }
             Instruction::CondVxVyNeq(x, y) => {
@@ -180,24 +374,38 @@ impl Chip8 {
                 self.memory.next_instruction();
             }
             Instruction::FlowJumpOffsetV0(addr) => {
-                self.memory.jump(addr + (self.registers[0] as usize));
+                let reg = if self.quirks.jump_uses_vx {
+                    addr >> 8
+                } else {
+                    0
+                };
+                self.memory.jump(addr + (self.registers[reg] as usize));
             }
             Instruction::RandomANDVxNN(reg, byte) => {
                 self.registers[reg] = self.rand.gen::<u8>() & byte;
                 self.memory.next_instruction();
             }
             Instruction::DrawSprite(x, y, height) => {
-                let (x_pos, y_pos) = (self.registers[x] as usize, self.registers[y] as usize);
+                // The sprite always starts on screen, even when its coordinates are past the edges.
+                let x_pos = self.registers[x] as usize % CHIP8_SCREEN_WIDTH;
+                let y_pos = self.registers[y] as usize % CHIP8_SCREEN_HEIGHT;
                 self.registers[0xF] = 0;
 
                 for curr_height in 0..height {
-                    // Gets the y position of the pixel. If the pixel goes off screen, we need to get the position it
-                    // would be at on the other side of the screen, so we mod it by our screen height. This same
-                    // logic follows for the x position, but we mod it by the screen width instead.
-                    let y = (y_pos + curr_height) % 32;
+                    // Parts of the sprite that go off screen are either cut off, or drawn on the other side of the
+                    // screen by taking the position modulo the size of the screen.
+                    let y = y_pos + curr_height;
+                    if self.quirks.clip_sprites && y >= CHIP8_SCREEN_HEIGHT {
+                        break;
+                    }
+                    let y = y % CHIP8_SCREEN_HEIGHT;
+                    let pixel = self.memory.get_mem(self.i as usize + curr_height);
                     for curr_bit in 0..8 {
-                        let x = (x_pos + curr_bit) % 64;
-                        let pixel = self.memory.get_mem(self.i as usize + curr_height);
+                        let x = x_pos + curr_bit;
+                        if self.quirks.clip_sprites && x >= CHIP8_SCREEN_WIDTH {
+                            break;
+                        }
+                        let x = x % CHIP8_SCREEN_WIDTH;
                         let is_bit_set = pixel & (0x80 >> curr_bit) as u8 != 0;
                         if is_bit_set {
                             self.registers[0xF] = 1;
@@ -206,7 +414,17 @@ impl Chip8 {
                     }
                 }
 
-                self.display.set_should_draw(true);
+                let (mut width, mut height) = (8, height);
+                if self.quirks.clip_sprites {
+                    width = width.min(CHIP8_SCREEN_WIDTH - x_pos);
+                    height = height.min(CHIP8_SCREEN_HEIGHT - y_pos);
+                }
+                self.display.mark_dirty(display::Region {
+                    x: x_pos,
+                    y: y_pos,
+                    width,
+                    height,
+                });
                 self.memory.next_instruction();
             }
             Instruction::KeyOpKeyPressed(reg) => {
@@ -226,18 +444,12 @@ impl Chip8 {
                 self.memory.next_instruction();
             }
             Instruction::KeyOpGetKey(reg) => {
-                'key_wait: loop {
-                    self.input.get_inputs();
-                    for i in 0..16 as usize {
-                        if self.input.is_pressed(i) {
-                            self.registers[reg] = i as u8;
-                            break 'key_wait;
-                        } else if self.input.should_quit() {
-                            break 'key_wait;
-                        }
-                    }
+                // The program counter is only advanced once a key is pressed, so this instruction keeps getting
+                // executed until then. This lets the frontend keep polling for input while the program waits.
+                if let Some(key) = self.input.get_pressed() {
+                    self.registers[reg] = key as u8;
+                    self.memory.next_instruction();
                 }
-                self.memory.next_instruction();
             }
             Instruction::DelayTimerSetVx(reg) => {
                 self.delay_timer = self.registers[reg];
@@ -248,7 +460,7 @@ impl Chip8 {
                 self.memory.next_instruction();
             }
             Instruction::MemAddIVx(reg) => {
-                self.i += self.registers[reg] as u16;
+                self.i = self.i.wrapping_add(self.registers[reg] as u16);
                 self.memory.next_instruction();
             }
             Instruction::MemSetISprite(reg) => {
@@ -260,7 +472,7 @@ impl Chip8 {
                 let mut value: u8 = self.registers[reg];
 
                 for offset in (0..3).rev() {
-                    self.memory.set_mem((self.i + offset) as usize, value % 10);
+                    self.memory.set_mem(self.i as usize + offset, value % 10);
                     value /= 10;
                 }
                 self.memory.next_instruction();
@@ -268,24 +480,42 @@ impl Chip8 {
             Instruction::MemRegisterDump(reg_end) => {
                 for reg in 0..(reg_end + 1) {
                     self.memory
-                        .set_mem((self.i + reg as u16) as usize, self.registers[reg]);
+                        .set_mem(self.i as usize + reg, self.registers[reg]);
+                }
+                if self.quirks.load_store_increments_i {
+                    self.i = self.i.wrapping_add(reg_end as u16 + 1);
                 }
                 self.memory.next_instruction();
             }
             Instruction::MemRegisterLoad(reg_end) => {
                 for reg in 0..(reg_end + 1) {
-                    self.registers[reg] = self.memory.get_mem((self.i + reg as u16) as usize);
+                    self.registers[reg] = self.memory.get_mem(self.i as usize + reg);
+                }
+                if self.quirks.load_store_increments_i {
+                    self.i = self.i.wrapping_add(reg_end as u16 + 1);
                 }
                 self.memory.next_instruction();
             }
         }
+        Ok(())
     }
 
-    /// Updates both the sound and delay timers of the CPU.
-    fn update_timers(&mut self) {
+    /// Updates both the sound and delay timers of the CPU. This should be called at a rate of 60Hz.
+    pub fn update_timers(&mut self) {
         if self.delay_timer > 0 {
             self.delay_timer -= 1;
         }
-        // TODO: Implement sound
+        if self.sound_timer > 0 {
+            self.sound_timer -= 1;
+        }
+    }
+
+    /// Returns the value 8XY6 and 8XYE shift, which depends on the shift quirk.
+    fn shift_source(&self, x: usize, y: usize) -> u8 {
+        if self.quirks.shift_uses_vy {
+            self.registers[y]
+        } else {
+            self.registers[x]
+        }
     }
 }
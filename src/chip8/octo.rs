@@ -0,0 +1,591 @@
+use std::collections::HashMap;
+
+/// The address programs are loaded at.
+const PROGRAM_START: u16 = 0x200;
+
+/// Instructions of later platforms, which Octo knows but which aren't emulated.
+const UNSUPPORTED_INSTRUCTIONS: [&str; 13] = [
+    "hires",
+    "lores",
+    "scroll-down",
+    "scroll-up",
+    "scroll-left",
+    "scroll-right",
+    "exit",
+    "saveflags",
+    "loadflags",
+    "plane",
+    "audio",
+    "pitch",
+    "bighex",
+];
+
+/// Compiles a program written in Octo, the assembly language of the Octo IDE, into the bytes of a ROM.
+///
+/// The core of the language is supported: labels, `:const`, `:alias`, `:unpack`, `:next`, `:org` and `:byte`, all of
+/// the CHIP-8 statements, and `if`, `loop` and `while`. Macros, `:calc`, string modes and comparisons with `<` and `>`
+/// aren't, nor are the instructions of SUPER-CHIP and XO-CHIP.
+pub fn compile(source: &str) -> Result<Vec<u8>, String> {
+    let mut compiler = Compiler::new(source);
+    compiler
+        .compile()
+        .map_err(|err| format!("line {}: {}", compiler.line, err))?;
+    compiler.finish()
+}
+
+/// Works out the address of every label of a program, by compiling it.
+pub fn labels(source: &str) -> Result<HashMap<String, u16>, String> {
+    let mut compiler = Compiler::new(source);
+    compiler
+        .compile()
+        .map_err(|err| format!("line {}: {}", compiler.line, err))?;
+    Ok(compiler
+        .labels
+        .into_iter()
+        .map(|(label, addr)| (label.to_string(), addr))
+        .collect())
+}
+
+/// A part of the program that is filled in once the address of a label is known.
+#[derive(Clone, Copy)]
+enum Patch {
+    /// The low 12 bits of the instruction at the offset.
+    Address,
+    /// The low nibble of the byte at the offset, which gets the top nibble of the address.
+    HighNibble,
+    /// The byte at the offset, which gets the low byte of the address.
+    LowByte,
+}
+
+struct Fixup {
+    offset: usize,
+    patch: Patch,
+    label: String,
+    line: usize,
+}
+
+/// A block that has been opened and not yet closed.
+enum Block {
+    /// `if ... begin`, with the offset of the jump past the block.
+    If(usize),
+    /// `else`, with the offset of the jump past the block.
+    Else(usize),
+    /// `loop`, with its address and the offsets of the jumps out of it made by `while`.
+    Loop(u16, Vec<usize>),
+}
+
+/// What an `if` or `while` checks, as the skip instructions that skip when it's true and when it's false.
+struct Condition {
+    skip_if_true: u16,
+    skip_if_false: u16,
+}
+
+struct Compiler<'a> {
+    /// The tokens of the program, each with its line number.
+    tokens: Vec<(usize, &'a str)>,
+    next: usize,
+    /// The line of the token being compiled.
+    line: usize,
+    rom: Vec<u8>,
+    /// Where the next instruction goes, as an offset into the ROM.
+    position: usize,
+    labels: HashMap<&'a str, u16>,
+    constants: HashMap<&'a str, u16>,
+    aliases: HashMap<&'a str, u16>,
+    fixups: Vec<Fixup>,
+    blocks: Vec<Block>,
+}
+
+impl<'a> Compiler<'a> {
+    fn new(source: &'a str) -> Self {
+        let tokens = source
+            .lines()
+            .enumerate()
+            .flat_map(|(number, line)| {
+                let code = line.split('#').next().unwrap_or_default();
+                code.split_whitespace()
+                    .map(move |token| (number + 1, token))
+            })
+            .collect();
+        Compiler {
+            tokens,
+            next: 0,
+            line: 0,
+            rom: Vec::new(),
+            position: 0,
+            labels: HashMap::new(),
+            constants: HashMap::new(),
+            aliases: HashMap::new(),
+            fixups: Vec::new(),
+            blocks: Vec::new(),
+        }
+    }
+
+    fn compile(&mut self) -> Result<(), String> {
+        // Octo programs start at the label main, so the program begins with a jump there.
+        self.emit_address(0x1000, "main")?;
+        while let Some(token) = self.token() {
+            self.statement(token)?;
+        }
+        Ok(())
+    }
+
+    /// Fills in the addresses of labels used before they were defined.
+    fn finish(mut self) -> Result<Vec<u8>, String> {
+        if let Some(block) = self.blocks.last() {
+            let name = match block {
+                Block::If(_) | Block::Else(_) => "'begin' is missing its 'end'",
+                Block::Loop(..) => "'loop' is missing its 'again'",
+            };
+            return Err(format!("line {}: {}", self.line, name));
+        }
+        for fixup in &self.fixups {
+            let address = match self.labels.get(fixup.label.as_str()) {
+                Some(&address) => address,
+                None if fixup.label == "main" => {
+                    return Err(String::from(
+                        "the program has no ': main' label to start at",
+                    ))
+                }
+                None => {
+                    return Err(format!(
+                        "line {}: unknown label '{}'",
+                        fixup.line, fixup.label
+                    ))
+                }
+            };
+            match fixup.patch {
+                Patch::Address => {
+                    self.rom[fixup.offset] |= (address >> 8) as u8 & 0xF;
+                    self.rom[fixup.offset + 1] = address as u8;
+                }
+                Patch::HighNibble => self.rom[fixup.offset] |= (address >> 8) as u8 & 0xF,
+                Patch::LowByte => self.rom[fixup.offset] = address as u8,
+            }
+        }
+        Ok(self.rom)
+    }
+
+    fn token(&mut self) -> Option<&'a str> {
+        let (line, token) = *self.tokens.get(self.next)?;
+        self.next += 1;
+        self.line = line;
+        Some(token)
+    }
+
+    fn expect_token(&mut self) -> Result<&'a str, String> {
+        self.token()
+            .ok_or_else(|| String::from("the program ends in the middle of a statement"))
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), String> {
+        match self.expect_token()? {
+            token if token == expected => Ok(()),
+            token => Err(format!("expected '{}', found '{}'", expected, token)),
+        }
+    }
+
+    fn address(&self) -> u16 {
+        PROGRAM_START + self.position as u16
+    }
+
+    fn emit_byte(&mut self, byte: u8) -> Result<(), String> {
+        if self.address() as usize >= 0x1000 {
+            return Err(String::from("the program doesn't fit in memory"));
+        }
+        if self.position >= self.rom.len() {
+            self.rom.resize(self.position + 1, 0);
+        }
+        self.rom[self.position] = byte;
+        self.position += 1;
+        Ok(())
+    }
+
+    fn emit(&mut self, opcode: u16) -> Result<(), String> {
+        self.emit_byte((opcode >> 8) as u8)?;
+        self.emit_byte(opcode as u8)
+    }
+
+    /// Emits an instruction taking an address, which is filled in later if the label isn't defined yet.
+    fn emit_address(&mut self, opcode: u16, target: &str) -> Result<(), String> {
+        match self.number(target) {
+            Some(address) if address <= 0xFFF => self.emit(opcode | address),
+            Some(address) => Err(format!("{:#X} is not an address", address)),
+            None => self.emit_fixup(opcode, Patch::Address, target),
+        }
+    }
+
+    fn emit_fixup(&mut self, opcode: u16, patch: Patch, label: &str) -> Result<(), String> {
+        if !is_identifier(label) {
+            return Err(format!("'{}' is not a number or a label", label));
+        }
+        let offset = match patch {
+            Patch::Address => self.position,
+            Patch::HighNibble | Patch::LowByte => self.position + 1,
+        };
+        self.fixups.push(Fixup {
+            offset,
+            patch,
+            label: label.to_string(),
+            line: self.line,
+        });
+        self.emit(opcode)
+    }
+
+    /// Emits a jump whose address is filled in with `patch_jump` once it is known.
+    fn emit_jump(&mut self) -> Result<usize, String> {
+        let offset = self.position;
+        self.emit(0x1000)?;
+        Ok(offset)
+    }
+
+    fn patch_jump(&mut self, offset: usize) {
+        let address = self.address();
+        self.rom[offset] = 0x10 | (address >> 8) as u8;
+        self.rom[offset + 1] = address as u8;
+    }
+
+    /// The value of a number, constant or label that is already defined.
+    fn number(&self, token: &str) -> Option<u16> {
+        let (negative, digits) = match token.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, token),
+        };
+        let value = if let Some(hex) = digits.strip_prefix("0x") {
+            u16::from_str_radix(hex, 16).ok()
+        } else if let Some(binary) = digits.strip_prefix("0b") {
+            u16::from_str_radix(binary, 2).ok()
+        } else if digits.starts_with(|c: char| c.is_ascii_digit()) {
+            digits.parse().ok()
+        } else if !negative {
+            self.constants
+                .get(token)
+                .or_else(|| self.labels.get(token))
+                .copied()
+        } else {
+            None
+        };
+        if negative {
+            value.map(|value| 0u16.wrapping_sub(value))
+        } else {
+            value
+        }
+    }
+
+    /// The value of a byte operand, which may be negative.
+    fn byte(&mut self) -> Result<u8, String> {
+        let token = self.expect_token()?;
+        match self.number(token) {
+            Some(value) if value <= 0xFF || value >= 0xFF80 => Ok(value as u8),
+            Some(value) => Err(format!("{} doesn't fit in a byte", value as i16)),
+            None => Err(format!("'{}' is not a number or a constant", token)),
+        }
+    }
+
+    fn register(&self, token: &str) -> Option<u16> {
+        if let Some(&register) = self.aliases.get(token) {
+            return Some(register);
+        }
+        let digit = token.strip_prefix('v').or(token.strip_prefix('V'))?;
+        if digit.len() != 1 {
+            return None;
+        }
+        u16::from_str_radix(digit, 16).ok()
+    }
+
+    fn expect_register(&mut self) -> Result<u16, String> {
+        let token = self.expect_token()?;
+        self.register(token)
+            .ok_or_else(|| format!("expected a register, found '{}'", token))
+    }
+
+    fn define(&mut self, name: &'a str, value: u16, label: bool) -> Result<(), String> {
+        if !is_identifier(name) {
+            return Err(format!("'{}' can't be used as a name", name));
+        }
+        if self.labels.contains_key(name) || self.constants.contains_key(name) {
+            return Err(format!("'{}' is defined twice", name));
+        }
+        if label {
+            self.labels.insert(name, value);
+        } else {
+            self.constants.insert(name, value);
+        }
+        Ok(())
+    }
+
+    fn statement(&mut self, token: &'a str) -> Result<(), String> {
+        if let Some(x) = self.register(token) {
+            return self.assignment(x);
+        }
+
+        match token {
+            ":" => {
+                let name = self.expect_token()?;
+                self.define(name, self.address(), true)?;
+            }
+            ":const" => {
+                let name = self.expect_token()?;
+                let token = self.expect_token()?;
+                let value = self
+                    .number(token)
+                    .ok_or_else(|| format!("'{}' is not a number or a constant", token))?;
+                self.define(name, value, false)?;
+            }
+            ":alias" => {
+                let name = self.expect_token()?;
+                let register = self.expect_register()?;
+                self.aliases.insert(name, register);
+            }
+            ":unpack" => {
+                let nibble = self.byte()? as u16;
+                let target = self.expect_token()?;
+                match self.number(target) {
+                    Some(address) => {
+                        self.emit(0x6000 | (nibble & 0xF) << 4 | address >> 8 & 0xF)?;
+                        self.emit(0x6100 | address & 0xFF)?;
+                    }
+                    None => {
+                        self.emit_fixup(0x6000 | (nibble & 0xF) << 4, Patch::HighNibble, target)?;
+                        self.emit_fixup(0x6100, Patch::LowByte, target)?;
+                    }
+                }
+            }
+            ":next" => {
+                let name = self.expect_token()?;
+                self.define(name, self.address() + 1, true)?;
+            }
+            ":org" => {
+                let token = self.expect_token()?;
+                match self.number(token) {
+                    Some(address) if (PROGRAM_START..0x1000).contains(&address) => {
+                        self.position = (address - PROGRAM_START) as usize;
+                    }
+                    _ => return Err(format!("can't put the program at '{}'", token)),
+                }
+            }
+            ":byte" => {
+                let byte = self.byte()?;
+                self.emit_byte(byte)?;
+            }
+            ":breakpoint" => {
+                self.expect_token()?;
+            }
+            ":monitor" => {
+                self.expect_token()?;
+                self.expect_token()?;
+            }
+            "return" | ";" => self.emit(0x00EE)?,
+            "clear" => self.emit(0x00E0)?,
+            "bcd" => self.register_instruction(0xF033)?,
+            "save" => self.register_instruction(0xF055)?,
+            "load" => self.register_instruction(0xF065)?,
+            "sprite" => {
+                let x = self.expect_register()?;
+                let y = self.expect_register()?;
+                let height = self.byte()? as u16;
+                if height > 0xF {
+                    return Err(format!("sprites can't be {} rows tall", height));
+                }
+                self.emit(0xD000 | x << 8 | y << 4 | height)?;
+            }
+            "jump" => {
+                let target = self.expect_token()?;
+                self.emit_address(0x1000, target)?;
+            }
+            "jump0" => {
+                let target = self.expect_token()?;
+                self.emit_address(0xB000, target)?;
+            }
+            "native" => {
+                let target = self.expect_token()?;
+                self.emit_address(0x0000, target)?;
+            }
+            "i" => {
+                let operator = self.expect_token()?;
+                let operand = self.expect_token()?;
+                match (operator, operand) {
+                    (":=", "hex") => self.register_instruction(0xF029)?,
+                    (":=", "long") => {
+                        return Err(String::from(
+                            "'i := long' is an XO-CHIP instruction, which isn't emulated",
+                        ))
+                    }
+                    (":=", target) => self.emit_address(0xA000, target)?,
+                    ("+=", operand) => match self.register(operand) {
+                        Some(x) => self.emit(0xF01E | x << 8)?,
+                        None => return Err(format!("expected a register, found '{}'", operand)),
+                    },
+                    _ => return Err(format!("'i {}' is not a statement", operator)),
+                }
+            }
+            "delay" | "buzzer" => {
+                self.expect(":=")?;
+                let x = self.expect_register()?;
+                self.emit(if token == "delay" { 0xF015 } else { 0xF018 } | x << 8)?;
+            }
+            "if" => {
+                let condition = self.condition()?;
+                match self.expect_token()? {
+                    "then" => self.emit(condition.skip_if_false)?,
+                    "begin" => {
+                        self.emit(condition.skip_if_true)?;
+                        let jump = self.emit_jump()?;
+                        self.blocks.push(Block::If(jump));
+                    }
+                    token => return Err(format!("expected 'then' or 'begin', found '{}'", token)),
+                }
+            }
+            "else" => match self.blocks.pop() {
+                Some(Block::If(jump)) => {
+                    let end = self.emit_jump()?;
+                    self.patch_jump(jump);
+                    self.blocks.push(Block::Else(end));
+                }
+                _ => return Err(String::from("'else' without 'if ... begin'")),
+            },
+            "end" => match self.blocks.pop() {
+                Some(Block::If(jump)) | Some(Block::Else(jump)) => self.patch_jump(jump),
+                _ => return Err(String::from("'end' without 'if ... begin'")),
+            },
+            "loop" => self.blocks.push(Block::Loop(self.address(), Vec::new())),
+            "while" => {
+                let condition = self.condition()?;
+                self.emit(condition.skip_if_true)?;
+                let jump = self.emit_jump()?;
+                match self
+                    .blocks
+                    .iter_mut()
+                    .rev()
+                    .find(|block| matches!(block, Block::Loop(..)))
+                {
+                    Some(Block::Loop(_, breaks)) => breaks.push(jump),
+                    _ => return Err(String::from("'while' outside of a loop")),
+                }
+            }
+            "again" => match self.blocks.pop() {
+                Some(Block::Loop(start, breaks)) => {
+                    self.emit(0x1000 | start)?;
+                    for jump in breaks {
+                        self.patch_jump(jump);
+                    }
+                }
+                _ => return Err(String::from("'again' without 'loop'")),
+            },
+            _ if UNSUPPORTED_INSTRUCTIONS.contains(&token) => {
+                return Err(format!(
+                    "'{}' is a SUPER-CHIP or XO-CHIP instruction, which isn't emulated",
+                    token
+                ))
+            }
+            _ if token.starts_with(':') => {
+                return Err(format!("the directive '{}' isn't supported", token))
+            }
+            _ => match self.number(token) {
+                // Numbers on their own are data, as are constants, but labels on their own are calls.
+                Some(value) if !self.labels.contains_key(token) => {
+                    if value > 0xFF && value < 0xFF80 {
+                        return Err(format!("{} doesn't fit in a byte", value as i16));
+                    }
+                    self.emit_byte(value as u8)?;
+                }
+                _ => self.emit_address(0x2000, token)?,
+            },
+        }
+        Ok(())
+    }
+
+    /// Compiles an instruction whose only operand is a register in its second nibble.
+    fn register_instruction(&mut self, opcode: u16) -> Result<(), String> {
+        let x = self.expect_register()?;
+        self.emit(opcode | x << 8)
+    }
+
+    /// Compiles a statement that starts with register X, such as `v0 += 1`.
+    fn assignment(&mut self, x: u16) -> Result<(), String> {
+        let operator = self.expect_token()?;
+        let operand = self.expect_token()?;
+        let x = x << 8;
+        if let Some(y) = self.register(operand) {
+            let y = y << 4;
+            let opcode = match operator {
+                ":=" => 0x8000,
+                "|=" => 0x8001,
+                "&=" => 0x8002,
+                "^=" => 0x8003,
+                "+=" => 0x8004,
+                "-=" => 0x8005,
+                ">>=" => 0x8006,
+                "=-" => 0x8007,
+                "<<=" => 0x800E,
+                _ => return Err(format!("'{}' can't be used with two registers", operator)),
+            };
+            return self.emit(opcode | x | y);
+        }
+
+        match (operator, operand) {
+            (":=", "delay") => self.emit(0xF007 | x),
+            (":=", "key") => self.emit(0xF00A | x),
+            (":=", "random") => {
+                let mask = self.byte()? as u16;
+                self.emit(0xC000 | x | mask)
+            }
+            (":=" | "+=" | "-=", _) => {
+                // Put the operand back so that it is read as a byte.
+                self.next -= 1;
+                let byte = self.byte()? as u16;
+                match operator {
+                    ":=" => self.emit(0x6000 | x | byte),
+                    "+=" => self.emit(0x7000 | x | byte),
+                    _ => self.emit(0x7000 | x | (0x100 - byte) & 0xFF),
+                }
+            }
+            _ => Err(format!(
+                "'{}' can't be used with a register and a number",
+                operator
+            )),
+        }
+    }
+
+    /// Compiles the condition of an `if` or `while`.
+    fn condition(&mut self) -> Result<Condition, String> {
+        let x = self.expect_register()? << 8;
+        let operator = self.expect_token()?;
+        let (skip_if_true, skip_if_false) = match operator {
+            "key" => (0xE09E | x, 0xE0A1 | x),
+            "-key" => (0xE0A1 | x, 0xE09E | x),
+            "==" | "!=" => {
+                let operand = self.expect_token()?;
+                let (equal, not_equal) = match self.register(operand) {
+                    Some(y) => (0x5000 | x | y << 4, 0x9000 | x | y << 4),
+                    None => {
+                        self.next -= 1;
+                        let byte = self.byte()? as u16;
+                        (0x3000 | x | byte, 0x4000 | x | byte)
+                    }
+                };
+                if operator == "==" {
+                    (equal, not_equal)
+                } else {
+                    (not_equal, equal)
+                }
+            }
+            "<" | ">" | "<=" | ">=" => {
+                return Err(format!("comparisons with '{}' aren't supported", operator))
+            }
+            _ => return Err(format!("'{}' is not a condition", operator)),
+        };
+        Ok(Condition {
+            skip_if_true,
+            skip_if_false,
+        })
+    }
+}
+
+/// Whether a token can be the name of a label or constant.
+fn is_identifier(token: &str) -> bool {
+    token.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && token
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
@@ -0,0 +1,78 @@
+#[cfg(test)]
+mod octo_compiler_test {
+    use crate::chip8::octo::compile;
+
+    #[test]
+    fn octo_compile_test() {
+        let source = "
+            :const SPEED 2
+            :alias x v1
+            : main
+                clear
+                x := 0
+                loop
+                    x += SPEED
+                    if x == 10 then x := 0
+                    if v2 key begin
+                        v3 -= 1
+                    else
+                        v3 := random 0xF0
+                    end
+                    i := sprite
+                    sprite x x 1
+                    draw
+                again
+            : draw
+                return
+            : sprite
+                0xFF 0b10000001
+        ";
+        let rom = compile(source).unwrap();
+        assert_eq!(
+            rom,
+            vec![
+                0x12, 0x02, // jump main
+                0x00, 0xE0, // clear
+                0x61, 0x00, // x := 0
+                0x71, 0x02, // x += SPEED
+                0x41, 0x0A, 0x61, 0x00, // if x == 10 then x := 0
+                0xE2, 0x9E, 0x12, 0x14, // if v2 key begin
+                0x73, 0xFF, 0x12, 0x16, // v3 -= 1, else
+                0xC3, 0xF0, // v3 := random 0xF0
+                0xA2, 0x20, 0xD1, 0x11, 0x22, 0x1E, // i := sprite, sprite x x 1, draw
+                0x12, 0x06, // again
+                0x00, 0xEE, // return
+                0xFF, 0x81,
+            ]
+        );
+    }
+
+    #[test]
+    fn octo_forward_label_test() {
+        let rom = compile(": main\n  :unpack 0xA data\n  i := data\n: data\n  :byte 7").unwrap();
+        assert_eq!(
+            rom,
+            vec![0x12, 0x02, 0x60, 0xA2, 0x61, 0x08, 0xA2, 0x08, 0x07]
+        );
+    }
+
+    #[test]
+    fn octo_error_line_test() {
+        assert_eq!(
+            compile(": main\n  v0 := 1\n  hires"),
+            Err(String::from(
+                "line 3: 'hires' is a SUPER-CHIP or XO-CHIP instruction, which isn't emulated"
+            ))
+        );
+        assert_eq!(
+            compile(": main\n  jump nowhere"),
+            Err(String::from("line 2: unknown label 'nowhere'"))
+        );
+        assert_eq!(
+            compile("v0 := 1"),
+            Err(String::from(
+                "the program has no ': main' label to start at"
+            ))
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use super::analysis;
+use super::stack::StackDepth;
+use std::fmt;
+use std::str::FromStr;
+
+/// The machines CHIP-8 programs were written for. Later machines changed how some instructions behave, so programs
+/// written for one may not run correctly with the behaviour of another.
+///
+/// Platforms are ordered by how much they added, so that a later platform can run the programs of an earlier one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Platform {
+    /// The original interpreter on the COSMAC VIP.
+    #[default]
+    Chip8,
+    /// SUPER-CHIP 1.1 on the HP48 calculators.
+    Schip,
+    /// XO-CHIP, as implemented by Octo.
+    XoChip,
+}
+
+impl Platform {
+    /// The number of instructions per second programs for the platform usually expect.
+    pub fn default_ips(self) -> u32 {
+        match self {
+            Platform::Chip8 => 600,
+            Platform::Schip => 1800,
+            Platform::XoChip => 60000,
+        }
+    }
+
+    /// The number of calls the platform's stack holds. The VIP interpreter kept 12 return addresses, and the HP48
+    /// interpreters 16.
+    pub fn stack_depth(self) -> StackDepth {
+        match self {
+            Platform::Chip8 => StackDepth::Limited(12),
+            Platform::Schip | Platform::XoChip => StackDepth::Limited(16),
+        }
+    }
+
+    /// Guesses the platform a ROM was written for from the instructions it uses, or returns `None` when it only uses
+    /// instructions every platform has.
+    ///
+    /// Sprites and other data often look like instructions, so only the code that can be reached by following the
+    /// program from its start is looked at. Computed jumps (BNNN) can't be followed, so code only reached through
+    /// them is missed.
+    pub fn detect(rom: &[u8]) -> Option<Platform> {
+        analysis::reachable(rom)
+            .into_iter()
+            .map(|offset| u16::from_be_bytes([rom[offset], rom[offset + 1]]))
+            .filter_map(|opcode| {
+                if is_xo_chip_only(opcode) {
+                    Some(Platform::XoChip)
+                } else if is_schip_only(opcode) {
+                    Some(Platform::Schip)
+                } else {
+                    None
+                }
+            })
+            .max()
+    }
+}
+
+impl FromStr for Platform {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().replace('-', "").as_str() {
+            "chip8" | "vip" => Ok(Platform::Chip8),
+            "schip" | "superchip" => Ok(Platform::Schip),
+            "xochip" => Ok(Platform::XoChip),
+            _ => Err(format!(
+                "Unknown platform '{}', expected chip8, schip or xochip",
+                name
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Platform::Chip8 => "CHIP-8",
+            Platform::Schip => "SUPER-CHIP",
+            Platform::XoChip => "XO-CHIP",
+        })
+    }
+}
+
+/// The behaviours that differ between platforms. Each is named after what happens when it is turned on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Quirks {
+    /// 8XY6 and 8XYE shift Vy and store the result in Vx, rather than shifting Vx in place.
+    pub shift_uses_vy: bool,
+    /// FX55 and FX65 leave I pointing just past the last register stored or loaded.
+    pub load_store_increments_i: bool,
+    /// BNNN jumps to NNN plus VX, where X is the highest nibble of NNN, rather than NNN plus V0.
+    pub jump_uses_vx: bool,
+    /// 8XY1, 8XY2 and 8XY3 set VF to 0.
+    pub logic_resets_vf: bool,
+    /// Sprites are cut off at the edges of the screen rather than wrapping around to the other side.
+    pub clip_sprites: bool,
+}
+
+impl Quirks {
+    /// The behaviour of the given platform.
+    pub fn for_platform(platform: Platform) -> Self {
+        match platform {
+            Platform::Chip8 => Quirks {
+                shift_uses_vy: true,
+                load_store_increments_i: true,
+                jump_uses_vx: false,
+                logic_resets_vf: true,
+                clip_sprites: true,
+            },
+            Platform::Schip => Quirks {
+                shift_uses_vy: false,
+                load_store_increments_i: false,
+                jump_uses_vx: true,
+                logic_resets_vf: false,
+                clip_sprites: true,
+            },
+            Platform::XoChip => Quirks {
+                shift_uses_vy: true,
+                load_store_increments_i: true,
+                jump_uses_vx: false,
+                logic_resets_vf: false,
+                clip_sprites: false,
+            },
+        }
+    }
+}
+
+impl Default for Quirks {
+    fn default() -> Self {
+        Quirks::for_platform(Platform::default())
+    }
+}
+
+impl FromStr for Quirks {
+    type Err = String;
+
+    /// Parses a preset, which is the name of the platform whose behaviour to use.
+    fn from_str(preset: &str) -> Result<Self, Self::Err> {
+        preset.parse().map(Quirks::for_platform).map_err(|_| {
+            format!(
+                "Unknown quirks preset '{}', expected chip8, schip or xochip",
+                preset
+            )
+        })
+    }
+}
+
+/// Whether an opcode was added by SUPER-CHIP: scrolling, switching resolution, big sprites and fonts, and saving
+/// registers to the calculator's flags.
+fn is_schip_only(opcode: u16) -> bool {
+    match opcode >> 12 {
+        0x0 => matches!(opcode & 0xFFF0, 0x00C0) || matches!(opcode, 0x00FB..=0x00FF),
+        0xD => opcode & 0xF == 0,
+        0xF => matches!(opcode & 0xFF, 0x30 | 0x75 | 0x85),
+        _ => false,
+    }
+}
+
+/// Whether an opcode was added by XO-CHIP: saving ranges of registers, the long load of I, bit planes, audio and
+/// scrolling up.
+fn is_xo_chip_only(opcode: u16) -> bool {
+    match opcode >> 12 {
+        0x0 => opcode & 0xFFF0 == 0x00D0,
+        0x5 => matches!(opcode & 0xF, 0x2 | 0x3),
+        0xF => opcode == 0xF000 || opcode == 0xF002 || matches!(opcode & 0xFF, 0x01 | 0x3A),
+        _ => false,
+    }
+}
@@ -0,0 +1,37 @@
+#[cfg(test)]
+mod platform_quirks_test {
+    use crate::chip8::asm::assemble;
+    use crate::chip8::quirks::{Platform, Quirks};
+
+    #[test]
+    fn parse_platform_test() {
+        assert_eq!("CHIP-8".parse(), Ok(Platform::Chip8));
+        assert_eq!("superchip".parse(), Ok(Platform::Schip));
+        assert_eq!("xo-chip".parse(), Ok(Platform::XoChip));
+        assert!("chip48".parse::<Platform>().is_err());
+    }
+
+    #[test]
+    fn quirks_preset_test() {
+        let quirks: Quirks = "schip".parse().unwrap();
+        assert_eq!(quirks, Quirks::for_platform(Platform::Schip));
+        assert!(quirks.jump_uses_vx);
+        assert!(!quirks.load_store_increments_i);
+    }
+
+    #[test]
+    fn detect_platform_test() {
+        let chip8 = assemble("LD V0, 1\nloop: SKP V0\nJP loop\nCALL sub\nsub: RET").unwrap();
+        assert_eq!(Platform::detect(&chip8), None);
+
+        // 00FF switches SUPER-CHIP to high resolution, and F002 loads an XO-CHIP audio pattern.
+        let schip = assemble("CALL sub\nloop: JP loop\nsub: DW 0x00FF\nRET").unwrap();
+        assert_eq!(Platform::detect(&schip), Some(Platform::Schip));
+        let xo_chip = assemble("SE V0, 1\nDW 0xF002\nDW 0x00FF\nloop: JP loop").unwrap();
+        assert_eq!(Platform::detect(&xo_chip), Some(Platform::XoChip));
+
+        // Sprite data after the program that happens to look like a SUPER-CHIP instruction is never run.
+        let sprite = assemble("loop: JP loop\nDB 0x00, 0xFF, 0x00, 0xFF").unwrap();
+        assert_eq!(Platform::detect(&sprite), None);
+    }
+}
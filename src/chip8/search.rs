@@ -0,0 +1,95 @@
+use super::Chip8;
+use std::str::FromStr;
+
+/// How the bytes at the candidate addresses are compared with the last snapshot.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SearchFilter {
+    /// The byte is now the given value.
+    Equal(u8),
+    Changed,
+    Unchanged,
+    Increased,
+    Decreased,
+}
+
+impl SearchFilter {
+    fn matches(self, before: u8, now: u8) -> bool {
+        match self {
+            SearchFilter::Equal(value) => now == value,
+            SearchFilter::Changed => now != before,
+            SearchFilter::Unchanged => now == before,
+            SearchFilter::Increased => now > before,
+            SearchFilter::Decreased => now < before,
+        }
+    }
+}
+
+impl FromStr for SearchFilter {
+    type Err = String;
+
+    /// Parses `changed`, `unchanged`, `increased`, `decreased`, or a value to look for, in hex with a `0x` prefix or
+    /// otherwise in decimal.
+    fn from_str(filter: &str) -> Result<Self, Self::Err> {
+        match filter {
+            "changed" => Ok(SearchFilter::Changed),
+            "unchanged" => Ok(SearchFilter::Unchanged),
+            "increased" => Ok(SearchFilter::Increased),
+            "decreased" => Ok(SearchFilter::Decreased),
+            _ => {
+                let value = match filter.strip_prefix("0x") {
+                    Some(hex) => u8::from_str_radix(hex, 16),
+                    None => filter.parse(),
+                };
+                value.map(SearchFilter::Equal).map_err(|_| {
+                    format!(
+                        "'{}' is not a byte or one of changed, unchanged, increased and decreased",
+                        filter
+                    )
+                })
+            }
+        }
+    }
+}
+
+/// Narrows down where a game keeps a value, such as its score or lives, by comparing snapshots of memory taken as the
+/// game runs. Every address starts out as a candidate, and each filter keeps only the candidates whose bytes compare
+/// with the last snapshot as asked.
+pub struct MemorySearch {
+    snapshot: Vec<u8>,
+    candidates: Vec<u16>,
+}
+
+impl MemorySearch {
+    /// Starts a search with a snapshot of memory, with every address as a candidate.
+    pub fn start(emu: &Chip8) -> Self {
+        let snapshot = emu.memory().to_vec();
+        let candidates = (0..snapshot.len() as u16).collect();
+        MemorySearch {
+            snapshot,
+            candidates,
+        }
+    }
+
+    /// Keeps the candidates whose bytes pass the filter, then takes a new snapshot to compare the next filter with.
+    /// Returns the number of candidates left.
+    pub fn filter(&mut self, emu: &Chip8, filter: SearchFilter) -> usize {
+        let memory = emu.memory();
+        let snapshot = &self.snapshot;
+        self.candidates.retain(|&addr| {
+            let addr = addr as usize;
+            filter.matches(snapshot[addr], memory[addr])
+        });
+        self.snapshot = memory.to_vec();
+        self.candidates.len()
+    }
+
+    /// The addresses that passed every filter so far, in order.
+    pub fn candidates(&self) -> &[u16] {
+        &self.candidates
+    }
+
+    /// The byte at an address when the last snapshot was taken.
+    pub fn snapshot_value(&self, addr: u16) -> u8 {
+        self.snapshot[addr as usize]
+    }
+}
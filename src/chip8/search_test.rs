@@ -0,0 +1,31 @@
+#[cfg(test)]
+mod memory_search_test {
+    use crate::chip8::search::{MemorySearch, SearchFilter};
+    use crate::chip8::Chip8;
+
+    #[test]
+    fn memory_search_test() {
+        let mut emu = Chip8::new();
+        emu.write_memory(0x300, 3);
+        emu.write_memory(0x301, 3);
+        let mut search = MemorySearch::start(&emu);
+        assert_eq!(search.candidates().len(), 0x1000);
+
+        assert_eq!(search.filter(&emu, SearchFilter::Equal(3)), 2);
+        emu.write_memory(0x300, 2);
+        assert_eq!(search.filter(&emu, SearchFilter::Decreased), 1);
+        assert_eq!(search.candidates(), &[0x300]);
+        assert_eq!(search.snapshot_value(0x300), 2);
+
+        assert_eq!(search.filter(&emu, SearchFilter::Changed), 0);
+    }
+
+    #[test]
+    fn parse_search_filter_test() {
+        assert_eq!("changed".parse(), Ok(SearchFilter::Changed));
+        assert_eq!("increased".parse(), Ok(SearchFilter::Increased));
+        assert_eq!("0x1F".parse(), Ok(SearchFilter::Equal(0x1F)));
+        assert_eq!("12".parse(), Ok(SearchFilter::Equal(12)));
+        assert!("256".parse::<SearchFilter>().is_err());
+    }
+}
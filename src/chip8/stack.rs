@@ -1,31 +1,110 @@
-const CHIP8_STACK_SIZE: usize = 16;
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+
+/// The number of calls kept for diagnostics, whether or not they have returned.
+const HISTORY_LENGTH: usize = 8;
+
+/// How many calls the stack can hold before it overflows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StackDepth {
+    Limited(usize),
+    /// Never overflows, for debugging programs that recurse deeper than they should.
+    Unlimited,
+}
+
+impl Default for StackDepth {
+    fn default() -> Self {
+        StackDepth::Limited(16)
+    }
+}
+
+impl FromStr for StackDepth {
+    type Err = String;
+
+    fn from_str(depth: &str) -> Result<Self, Self::Err> {
+        match depth.to_ascii_lowercase().as_str() {
+            "unlimited" => Ok(StackDepth::Unlimited),
+            _ => match depth.parse() {
+                Ok(depth) if depth > 0 => Ok(StackDepth::Limited(depth)),
+                _ => Err(format!(
+                    "'{}' is not a stack depth, expected a number of calls or unlimited",
+                    depth
+                )),
+            },
+        }
+    }
+}
+
+impl fmt::Display for StackDepth {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StackDepth::Limited(depth) => write!(f, "{}", depth),
+            StackDepth::Unlimited => f.write_str("unlimited"),
+        }
+    }
+}
+
+/// A call of a subroutine: the address of the CALL instruction and the address of the subroutine.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Call {
+    pub from: u16,
+    pub to: u16,
+}
 
 /// Represents the stack in the CHIP-8.
 pub struct Stack {
-    stack: [u16; CHIP8_STACK_SIZE],
-    stack_pointer: usize,
+    calls: Vec<Call>,
+    depth: StackDepth,
+    /// The most recent calls, newest last, including those that have since returned.
+    history: VecDeque<Call>,
 }
 
 impl Stack {
-    pub fn new() -> Self {
+    pub fn new(depth: StackDepth) -> Self {
         Stack {
-            stack: [0; CHIP8_STACK_SIZE],
-            stack_pointer: 0,
+            calls: Vec::new(),
+            depth,
+            history: VecDeque::with_capacity(HISTORY_LENGTH),
+        }
+    }
+
+    pub fn depth(&self) -> StackDepth {
+        self.depth
+    }
+
+    /// Pushes a call onto the stack. Fails without changing the stack if it is already full.
+    pub fn push(&mut self, call: Call) -> Result<(), String> {
+        if let StackDepth::Limited(depth) = self.depth {
+            if self.calls.len() >= depth {
+                return Err(format!(
+                    "Stack overflow: the stack only holds {} calls",
+                    depth
+                ));
+            }
+        }
+        self.calls.push(call);
+        if self.history.len() == HISTORY_LENGTH {
+            self.history.pop_front();
         }
+        self.history.push_back(call);
+        Ok(())
     }
 
-    /// Pushes a new memory address onto the stack and increases the stack pointer.
-    pub fn push(&mut self, addr: u16) {
-        self.stack[self.stack_pointer] = addr;
-        self.stack_pointer += 1;
+    /// Pops the innermost call off the stack. Fails if there is nothing to return from.
+    pub fn pop(&mut self) -> Result<Call, String> {
+        self.calls
+            .pop()
+            .ok_or_else(|| String::from("Stack underflow: there is no subroutine to return from"))
     }
 
-    /// Decreases the stack pointer.
-    pub fn pop(&mut self) {
-        self.stack_pointer -= 1;
+    /// The calls that have been pushed and not yet popped, outermost first.
+    pub fn calls(&self) -> &[Call] {
+        &self.calls
     }
 
-    pub fn peek(&self) -> u16 {
-        self.stack[self.stack_pointer]
+    /// The most recent calls, newest first, including those that have since returned.
+    pub fn recent_calls(&self) -> impl Iterator<Item = &Call> {
+        self.history.iter().rev()
     }
 }
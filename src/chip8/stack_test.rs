@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod call_stack_test {
+    use crate::chip8::asm::assemble;
+    use crate::chip8::stack::StackDepth;
+    use crate::chip8::symbols::Symbols;
+    use crate::chip8::Chip8;
+    use std::path::Path;
+
+    const PROGRAM: &str = "
+        main:    CALL descend
+                 JP main
+        descend: CALL descend
+    ";
+
+    fn machine(depth: StackDepth) -> Chip8 {
+        let mut emu = Chip8::new();
+        emu.set_stack_depth(depth);
+        emu.set_symbols(Symbols::from_source(Path::new("descend.asm"), PROGRAM).unwrap());
+        emu.load_rom_bytes(&assemble(PROGRAM).unwrap()).unwrap();
+        emu
+    }
+
+    #[test]
+    fn stack_overflow_test() {
+        let mut emu = machine(StackDepth::Limited(12));
+        for _ in 0..12 {
+            emu.emulate_cycle().unwrap();
+        }
+        assert_eq!(
+            emu.call_chain()[..2],
+            [
+                "descend (0x204) called from descend (0x204)",
+                "descend (0x204) called from descend (0x204)"
+            ]
+        );
+        assert_eq!(
+            emu.call_chain().last().unwrap(),
+            "descend (0x204) called from main (0x200)"
+        );
+
+        let err = emu.emulate_cycle().unwrap_err();
+        let lines: Vec<_> = err.lines().collect();
+        assert_eq!(
+            lines[..2],
+            [
+                "Stack overflow: the stack only holds 12 calls, and CALL descend (0x204) would be one more",
+                "The last 8 calls, most recent first:"
+            ]
+        );
+        assert_eq!(lines.len(), 10);
+        // The machine is left as it was, so that it can be looked at.
+        assert_eq!(emu.program_counter(), 0x204);
+        assert_eq!(emu.call_stack().len(), 12);
+    }
+
+    #[test]
+    fn unlimited_stack_test() {
+        let mut emu = machine(StackDepth::Unlimited);
+        for _ in 0..1000 {
+            emu.emulate_cycle().unwrap();
+        }
+        assert_eq!(emu.call_stack().len(), 1000);
+    }
+
+    #[test]
+    fn stack_underflow_test() {
+        let mut emu = Chip8::new();
+        emu.load_rom_bytes(&assemble("RET").unwrap()).unwrap();
+        assert_eq!(
+            emu.emulate_cycle(),
+            Err(String::from(
+                "Stack underflow: there is no subroutine to return from\nNo subroutines have been called"
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_stack_depth_test() {
+        assert_eq!("12".parse(), Ok(StackDepth::Limited(12)));
+        assert_eq!("Unlimited".parse(), Ok(StackDepth::Unlimited));
+        assert!("0".parse::<StackDepth>().is_err());
+    }
+}
@@ -0,0 +1,50 @@
+use super::{asm, octo};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// Names for the addresses of a program, taken from the labels of its source, so that subroutines can be shown by
+/// name rather than by address.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Symbols {
+    names: BTreeMap<u16, String>,
+}
+
+impl Symbols {
+    /// Reads the labels of the source a ROM was built from: Octo for `.8o` files, and otherwise the assembly `asm`
+    /// reads. Where several labels share an address, the first in alphabetical order is used.
+    pub fn from_source(path: &Path, source: &str) -> Result<Self, String> {
+        let labels = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("8o") => octo::labels(source)?,
+            _ => asm::labels(source)?,
+        };
+        Ok(Symbols::from_labels(labels))
+    }
+
+    fn from_labels(labels: HashMap<String, u16>) -> Self {
+        let mut names = BTreeMap::new();
+        for (label, addr) in labels {
+            names
+                .entry(addr)
+                .and_modify(|name: &mut String| {
+                    if label < *name {
+                        *name = label.clone();
+                    }
+                })
+                .or_insert(label);
+        }
+        Symbols { names }
+    }
+
+    /// The name of an address, if it has one.
+    pub fn name(&self, addr: u16) -> Option<&str> {
+        self.names.get(&addr).map(String::as_str)
+    }
+
+    /// An address along with its name, e.g. `draw_player (0x2B0)`, or just the address if it has no name.
+    pub fn describe(&self, addr: u16) -> String {
+        match self.name(addr) {
+            Some(name) => format!("{} ({:#05X})", name, addr),
+            None => format!("{:#05X}", addr),
+        }
+    }
+}
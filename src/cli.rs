@@ -0,0 +1,287 @@
+use crate::chip8::quirks::{Platform, Quirks};
+use crate::chip8::stack::StackDepth;
+use crate::config::Profile;
+use crate::debugger::parse_address;
+use crate::frontend::filter::Filters;
+use crate::frontend::playback::Speed;
+use crate::frontend::terminal::RenderMode;
+use crate::frontend::{KeyMap, Palette, Persistence, Rgb};
+use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
+
+/// A CHIP-8 emulator.
+#[derive(Parser)]
+#[command(name = "rusty-chip", version)]
+pub struct Cli {
+    /// Read settings from this file instead of rusty-chip/config.toml in the user's configuration directory
+    #[arg(long, global = true, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Run a ROM in a window or the terminal
+    Run(Box<RunArgs>),
+    /// Step through a ROM in an interactive debugger
+    Debug(DebugArgs),
+    /// Print the instructions of a ROM as assembly
+    Disasm {
+        /// The ROM to disassemble
+        rom: PathBuf,
+    },
+    /// Assemble a program into a ROM
+    Asm(AsmArgs),
+    /// Show information about a ROM without running it
+    Info {
+        /// The ROM to look at
+        rom: PathBuf,
+
+        /// Apply an IPS or BPS patch to the ROM (can be repeated, applied in order)
+        #[arg(long = "patch", value_name = "FILE")]
+        patches: Vec<PathBuf>,
+    },
+    /// Run a ROM without any input or output and print the screen it ends on
+    Test(TestArgs),
+}
+
+/// Options for how the machine behaves, shared by every command that runs a ROM.
+#[derive(Args)]
+pub struct MachineArgs {
+    /// The ROM to run
+    pub rom: PathBuf,
+
+    /// The platform the ROM was written for [default: chip8]. Only chip8 can be run so far, as the instructions of schip
+    /// and xochip aren't emulated; --quirks picks their behaviour
+    #[arg(long, value_name = "PLATFORM")]
+    pub platform: Option<Platform>,
+
+    /// How the instructions that differ between platforms behave, named after a platform [default: the platform's]
+    #[arg(long, value_name = "PRESET")]
+    pub quirks: Option<Quirks>,
+
+    /// The number of instructions run each second [default: the usual speed of the platform]
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub ips: Option<u32>,
+
+    /// The number of calls the stack holds, or unlimited [default: the platform's]
+    #[arg(long, value_name = "N")]
+    pub stack_depth: Option<StackDepth>,
+
+    /// Name subroutines in call stacks after the labels of the program the ROM was built from (assembly, or Octo for
+    /// .8o files)
+    #[arg(long, value_name = "FILE")]
+    pub symbols: Option<PathBuf>,
+
+    /// Seed the random number generator
+    #[arg(long, value_name = "N")]
+    pub seed: Option<u64>,
+
+    /// Apply an IPS or BPS patch to the ROM (can be repeated, applied in order)
+    #[arg(long = "patch", value_name = "FILE")]
+    pub patches: Vec<PathBuf>,
+}
+
+impl MachineArgs {
+    /// The platform to emulate, from the options or else the ROM's profile.
+    pub fn platform(&self, profile: &Profile) -> Platform {
+        self.platform.or(profile.platform).unwrap_or_default()
+    }
+
+    pub fn quirks(&self, profile: &Profile) -> Quirks {
+        let platform = self.platform(profile);
+        match (self.quirks, &profile.quirks) {
+            (Some(quirks), _) => quirks,
+            (None, Some(quirks)) => quirks.apply(platform),
+            (None, None) => Quirks::for_platform(platform),
+        }
+    }
+
+    pub fn stack_depth(&self, profile: &Profile) -> StackDepth {
+        self.stack_depth
+            .unwrap_or_else(|| self.platform(profile).stack_depth())
+    }
+
+    /// The number of instructions to run in each frame at 60 frames per second.
+    pub fn cycles_per_frame(&self, profile: &Profile) -> usize {
+        let ips = self
+            .ips
+            .or(profile.ips)
+            .unwrap_or_else(|| self.platform(profile).default_ips());
+        ((ips as f64 / 60.0).round() as usize).max(1)
+    }
+}
+
+#[derive(Args)]
+pub struct RunArgs {
+    #[command(flatten)]
+    pub machine: MachineArgs,
+
+    /// Draw the screen in the terminal
+    #[arg(long, help_heading = "Display")]
+    pub tty: bool,
+
+    /// How the terminal draws pixels: half-block or braille (implies --tty)
+    #[arg(long, value_name = "MODE", help_heading = "Display")]
+    pub render: Option<RenderMode>,
+
+    /// Run as fast as possible without any input or output, until --frames, --screenshot-at-frame or the end of
+    /// --replay-input
+    #[arg(long, conflicts_with_all = ["tty", "render"], help_heading = "Display")]
+    pub headless: bool,
+
+    /// The size of each pixel when the window opens [default: 10]
+    #[arg(long, value_name = "N", help_heading = "Display")]
+    #[cfg_attr(not(feature = "sdl"), allow(dead_code))]
+    pub scale: Option<u32>,
+
+    /// Open the window in fullscreen
+    #[arg(long, help_heading = "Display")]
+    #[cfg_attr(not(feature = "sdl"), allow(dead_code))]
+    pub fullscreen: bool,
+
+    /// Fill the window instead of only scaling by whole numbers
+    #[arg(long, help_heading = "Display")]
+    #[cfg_attr(not(feature = "sdl"), allow(dead_code))]
+    pub stretch: bool,
+
+    /// classic, green, amber, octo or lcd
+    #[arg(long, value_name = "NAME", help_heading = "Display")]
+    pub palette: Option<Palette>,
+
+    /// Override the colour of pixels that are on, as a hex code
+    #[arg(long, value_name = "COLOR", help_heading = "Display")]
+    pub fg: Option<Rgb>,
+
+    /// Override the colour of pixels that are off, as a hex code
+    #[arg(long, value_name = "COLOR", help_heading = "Display")]
+    pub bg: Option<Rgb>,
+
+    /// off, blend or phosphor[:DECAY]
+    #[arg(long, value_name = "MODE", help_heading = "Display")]
+    pub persistence: Option<Persistence>,
+
+    /// Effects such as scanlines,grid,bloom,curvature,vignette[:INTENSITY]
+    #[arg(long, value_name = "LIST", help_heading = "Display")]
+    pub filter: Option<Filters>,
+
+    /// Show the frames and instructions run each second
+    #[arg(long, help_heading = "Display")]
+    pub show_counter: bool,
+
+    /// Show which keys of the keypad are pressed
+    #[arg(long, help_heading = "Display")]
+    pub show_keypad: bool,
+
+    /// Keep the buzzer quiet
+    #[arg(long)]
+    pub mute: bool,
+
+    /// Save the palette and overlays chosen with hotkeys into the ROM's profile in the config file
+    #[arg(long)]
+    pub save_settings: bool,
+
+    /// The 16 keyboard keys for the hex keys 0 to F
+    #[arg(long, value_name = "LAYOUT")]
+    pub keymap: Option<KeyMap>,
+
+    /// Quit after running N frames
+    #[arg(long, value_name = "N", help_heading = "Playback")]
+    pub frames: Option<u64>,
+
+    /// Start paused
+    #[arg(long, help_heading = "Playback")]
+    pub paused: bool,
+
+    /// Keep bytes of memory at the values in a cheat file [default: the ROM with a .cht extension, if there is one]
+    #[arg(long, value_name = "FILE", help_heading = "Playback")]
+    pub cheats: Option<PathBuf>,
+
+    /// Load the ROM again whenever its file changes
+    #[arg(long, help_heading = "Playback")]
+    pub watch: bool,
+
+    /// The speed of fast-forward, as a multiplier or 'uncapped'
+    #[arg(long, value_name = "SPEED", help_heading = "Playback")]
+    pub fast_forward: Option<Speed>,
+
+    /// The speed of slow motion, as a multiplier
+    #[arg(long, value_name = "SPEED", help_heading = "Playback")]
+    pub slow_motion: Option<Speed>,
+
+    /// Save a screenshot after running N frames
+    #[arg(long, value_name = "N", help_heading = "Recording")]
+    pub screenshot_at_frame: Option<u64>,
+
+    /// Where screenshots are saved
+    #[arg(long, value_name = "DIR", help_heading = "Recording")]
+    pub screenshot_dir: Option<PathBuf>,
+
+    /// The size of each pixel in screenshots
+    #[arg(long, value_name = "N", help_heading = "Recording")]
+    pub screenshot_scale: Option<u32>,
+
+    /// Record a .gif, .y4m or .rgb video (- streams Y4M to stdout)
+    #[arg(long, value_name = "FILE", help_heading = "Recording")]
+    pub record: Option<PathBuf>,
+
+    /// The size of each pixel in recordings
+    #[arg(long, value_name = "N", help_heading = "Recording")]
+    pub record_scale: Option<u32>,
+
+    /// Record the keys pressed on every frame
+    #[arg(long, value_name = "FILE", help_heading = "Recording")]
+    pub record_input: Option<PathBuf>,
+
+    /// Play back recorded keys instead of reading the keyboard
+    #[arg(long, value_name = "FILE", help_heading = "Recording")]
+    pub replay_input: Option<PathBuf>,
+
+    /// Save every sprite the game draws as a .png sheet or .8o Octo data when it stops
+    #[arg(long, value_name = "FILE", help_heading = "Recording")]
+    pub rip_sprites: Option<PathBuf>,
+
+    /// Save a report of where the game spent its time, and folded stacks for flame graphs, when it stops
+    #[arg(long, value_name = "FILE", help_heading = "Recording")]
+    pub profile: Option<PathBuf>,
+
+    /// Save a disassembly marked with the bytes the game ran, read and wrote, and a .png map of them, when it stops
+    #[arg(long, value_name = "FILE", help_heading = "Recording")]
+    pub coverage: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct DebugArgs {
+    #[command(flatten)]
+    pub machine: MachineArgs,
+
+    /// Stop when the program counter reaches ADDR, given in hex (can be repeated)
+    #[arg(long = "break", value_name = "ADDR", value_parser = parse_address)]
+    pub breakpoints: Vec<u16>,
+}
+
+#[derive(Args)]
+pub struct AsmArgs {
+    /// The program to assemble
+    pub source: PathBuf,
+
+    /// Where to write the ROM [default: the source with a .ch8 extension]
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct TestArgs {
+    #[command(flatten)]
+    pub machine: MachineArgs,
+
+    /// The number of frames to run
+    #[arg(long, value_name = "N", default_value_t = 600)]
+    pub frames: u64,
+
+    /// Fail unless the screen matches this file, which holds the screen as printed by this command
+    #[arg(long, value_name = "FILE")]
+    pub expect: Option<PathBuf>,
+}
@@ -0,0 +1,271 @@
+use crate::chip8::quirks::{Platform, Quirks};
+use crate::frontend::filter::Filters;
+use crate::frontend::playback::Speed;
+use crate::frontend::{KeyMap, Palette, Persistence, Rgb, Settings};
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use toml::Value;
+use toml_edit::{value, DocumentMut, Item, Table};
+
+/// Settings read from the configuration file. Any setting can be left out, in which case the next place settings come
+/// from is used: options on the command line win over the ROM's profile, which wins over the defaults at the top of
+/// the file.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    /// The name of the ROM. Only used to make the file easier to read.
+    pub name: Option<String>,
+    #[serde(deserialize_with = "parse_option")]
+    pub platform: Option<Platform>,
+    pub quirks: Option<QuirksConfig>,
+    pub ips: Option<u32>,
+    #[serde(deserialize_with = "parse_option")]
+    pub fast_forward: Option<Speed>,
+    #[serde(deserialize_with = "parse_option")]
+    pub slow_motion: Option<Speed>,
+    #[serde(deserialize_with = "parse_option")]
+    pub palette: Option<Palette>,
+    #[serde(deserialize_with = "parse_option")]
+    pub fg: Option<Rgb>,
+    #[serde(deserialize_with = "parse_option")]
+    pub bg: Option<Rgb>,
+    #[serde(deserialize_with = "parse_option")]
+    pub keymap: Option<KeyMap>,
+    #[serde(deserialize_with = "parse_option")]
+    pub persistence: Option<Persistence>,
+    #[serde(deserialize_with = "parse_option")]
+    pub filter: Option<Filters>,
+    pub scale: Option<u32>,
+    pub mute: Option<bool>,
+    pub show_counter: Option<bool>,
+    pub show_keypad: Option<bool>,
+}
+
+/// The quirks of a profile, either as a preset named after a platform or as individual quirks that change the
+/// behaviour of the platform being emulated.
+#[derive(Clone, Debug)]
+pub enum QuirksConfig {
+    Preset(Quirks),
+    Flags(QuirkFlags),
+}
+
+impl<'de> Deserialize<'de> for QuirksConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match Value::deserialize(deserializer)? {
+            Value::String(preset) => preset
+                .parse()
+                .map(QuirksConfig::Preset)
+                .map_err(serde::de::Error::custom),
+            flags @ Value::Table(_) => flags
+                .try_into()
+                .map(QuirksConfig::Flags)
+                .map_err(serde::de::Error::custom),
+            _ => Err(serde::de::Error::custom(
+                "quirks must be a preset such as \"schip\" or a table of quirks",
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QuirkFlags {
+    pub shift_uses_vy: Option<bool>,
+    pub load_store_increments_i: Option<bool>,
+    pub jump_uses_vx: Option<bool>,
+    pub logic_resets_vf: Option<bool>,
+    pub clip_sprites: Option<bool>,
+}
+
+impl QuirksConfig {
+    /// Returns the quirks to use on the given platform.
+    pub fn apply(&self, platform: Platform) -> Quirks {
+        match self {
+            QuirksConfig::Preset(quirks) => *quirks,
+            QuirksConfig::Flags(flags) => {
+                let quirks = Quirks::for_platform(platform);
+                Quirks {
+                    shift_uses_vy: flags.shift_uses_vy.unwrap_or(quirks.shift_uses_vy),
+                    load_store_increments_i: flags
+                        .load_store_increments_i
+                        .unwrap_or(quirks.load_store_increments_i),
+                    jump_uses_vx: flags.jump_uses_vx.unwrap_or(quirks.jump_uses_vx),
+                    logic_resets_vf: flags.logic_resets_vf.unwrap_or(quirks.logic_resets_vf),
+                    clip_sprites: flags.clip_sprites.unwrap_or(quirks.clip_sprites),
+                }
+            }
+        }
+    }
+}
+
+impl Profile {
+    /// Returns this profile with any settings it leaves out taken from `defaults`.
+    fn or(self, defaults: &Profile) -> Profile {
+        let defaults = defaults.clone();
+        Profile {
+            name: self.name.or(defaults.name),
+            platform: self.platform.or(defaults.platform),
+            quirks: self.quirks.or(defaults.quirks),
+            ips: self.ips.or(defaults.ips),
+            fast_forward: self.fast_forward.or(defaults.fast_forward),
+            slow_motion: self.slow_motion.or(defaults.slow_motion),
+            palette: self.palette.or(defaults.palette),
+            fg: self.fg.or(defaults.fg),
+            bg: self.bg.or(defaults.bg),
+            keymap: self.keymap.or(defaults.keymap),
+            persistence: self.persistence.or(defaults.persistence),
+            filter: self.filter.or(defaults.filter),
+            scale: self.scale.or(defaults.scale),
+            mute: self.mute.or(defaults.mute),
+            show_counter: self.show_counter.or(defaults.show_counter),
+            show_keypad: self.show_keypad.or(defaults.show_keypad),
+        }
+    }
+}
+
+/// The whole configuration file: defaults at the top level, then a `[rom.SHA1]` section for each ROM with its own
+/// settings, keyed by the SHA-1 hash of the ROM so that it is found whatever the file is called.
+#[derive(Debug, Default)]
+pub struct Config {
+    /// Saves settings changed with hotkeys into the profile of the ROM when the emulator quits.
+    pub save_settings: bool,
+    pub defaults: Profile,
+    pub roms: HashMap<String, Profile>,
+}
+
+impl Config {
+    /// Where the configuration file is kept unless another one is given: `rusty-chip/config.toml` in the user's
+    /// configuration directory (usually `~/.config`).
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("rusty-chip").join("config.toml"))
+    }
+
+    /// Reads the configuration file. A missing file is treated as an empty one, since nobody has to have one.
+    pub fn load(path: &Path) -> Result<Config, String> {
+        match fs::read_to_string(path) {
+            Ok(text) => text
+                .parse()
+                .map_err(|err| format!("Invalid config file {}: {}", path.display(), err)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Config::default()),
+            Err(err) => Err(format!("Could not read {}: {}", path.display(), err)),
+        }
+    }
+
+    /// Returns the settings for the ROM with the given SHA-1 hash. `known` holds what is already known about the ROM,
+    /// e.g. from the ROM database, which the ROM's own profile overrides but which overrides the defaults.
+    pub fn profile(&self, hash: &str, known: &Profile) -> Profile {
+        let known = known.clone().or(&self.defaults);
+        match self.roms.get(hash) {
+            Some(profile) => profile.clone().or(&known),
+            None => known,
+        }
+    }
+}
+
+impl FromStr for Config {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut table: toml::Table = toml::from_str(text).map_err(|err| err.to_string())?;
+
+        let save_settings = match table.remove("save_settings") {
+            None => false,
+            Some(Value::Boolean(save)) => save,
+            Some(_) => return Err(String::from("save_settings must be true or false")),
+        };
+        let mut roms = HashMap::new();
+        match table.remove("rom") {
+            None => {}
+            Some(Value::Table(profiles)) => {
+                for (hash, profile) in profiles {
+                    let profile = profile
+                        .try_into()
+                        .map_err(|err| format!("in [rom.{}]: {}", hash, err))?;
+                    roms.insert(hash.to_lowercase(), profile);
+                }
+            }
+            Some(_) => return Err(String::from("rom must be a table of ROM profiles")),
+        }
+        let defaults = Value::Table(table)
+            .try_into()
+            .map_err(|err: toml::de::Error| err.to_string())?;
+
+        Ok(Config {
+            save_settings,
+            defaults,
+            roms,
+        })
+    }
+}
+
+/// Saves the settings that can be changed with hotkeys into the profile of a ROM, keeping everything else in the file
+/// as it was, comments included.
+pub fn save_settings(
+    path: &Path,
+    hash: &str,
+    name: &str,
+    settings: &Settings,
+) -> Result<(), String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(format!("Could not read {}: {}", path.display(), err)),
+    };
+    let mut document: DocumentMut = text
+        .parse()
+        .map_err(|err| format!("Invalid config file {}: {}", path.display(), err))?;
+
+    let roms = document
+        .entry("rom")
+        .or_insert_with(|| {
+            let mut roms = Table::new();
+            roms.set_implicit(true);
+            Item::Table(roms)
+        })
+        .as_table_mut()
+        .ok_or("'rom' in the config file is not a table")?;
+    let profile = roms
+        .entry(hash)
+        .or_insert_with(|| {
+            let mut profile = Table::new();
+            profile["name"] = value(name);
+            Item::Table(profile)
+        })
+        .as_table_mut()
+        .ok_or_else(|| format!("The profile of {} is not a table", hash))?;
+
+    // Custom colours only come from the command line or the file itself, so only built-in palettes are saved.
+    if Palette::built_in(&settings.palette.name).is_some() {
+        profile["palette"] = value(settings.palette.name.as_str());
+    }
+    profile["show_counter"] = value(settings.show_counter);
+    profile["show_keypad"] = value(settings.show_keypad);
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|err| format!("Could not create {}: {}", dir.display(), err))?;
+    }
+    fs::write(path, document.to_string())
+        .map_err(|err| format!("Could not write {}: {}", path.display(), err))
+}
+
+/// The SHA-1 hash of a ROM in hex, which identifies the ROM in the configuration file.
+pub fn rom_hash(rom: &[u8]) -> String {
+    sha1_smol::Sha1::from(rom).digest().to_string()
+}
+
+/// Deserializes a setting from a string using its `FromStr` implementation, e.g. a palette from its name.
+fn parse_option<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    let text = String::deserialize(deserializer)?;
+    text.parse().map(Some).map_err(serde::de::Error::custom)
+}
@@ -0,0 +1,90 @@
+#[cfg(test)]
+mod config_file_test {
+    use crate::chip8::quirks::{Platform, Quirks};
+    use crate::config::{self, Config, Profile};
+    use crate::frontend::{Palette, Settings};
+    use std::fs;
+
+    const HASH: &str = "0123456789abcdef0123456789abcdef01234567";
+
+    #[test]
+    fn rom_profile_defaults_test() {
+        let config: Config = format!(
+            "ips = 900\npalette = \"amber\"\n\n[rom.{}]\nplatform = \"schip\"\npalette = \"green\"\n",
+            HASH.to_uppercase()
+        )
+        .parse()
+        .unwrap();
+
+        let profile = config.profile(HASH, &Profile::default());
+        assert_eq!(profile.platform, Some(Platform::Schip));
+        assert_eq!(profile.ips, Some(900));
+        assert_eq!(profile.palette.unwrap().name, "green");
+
+        let other = config.profile("unknown", &Profile::default());
+        assert_eq!(other.platform, None);
+        assert_eq!(other.palette.unwrap().name, "amber");
+
+        // What is known about a ROM, e.g. from the ROM database, wins over the defaults but not over the ROM's profile.
+        let known = Profile {
+            platform: Some(Platform::XoChip),
+            ips: Some(1200),
+            ..Profile::default()
+        };
+        let profile = config.profile(HASH, &known);
+        assert_eq!(profile.platform, Some(Platform::Schip));
+        assert_eq!(profile.ips, Some(1200));
+    }
+
+    #[test]
+    fn config_quirks_test() {
+        let config: Config = "quirks = \"schip\"".parse().unwrap();
+        let quirks = config.defaults.quirks.unwrap().apply(Platform::Chip8);
+        assert_eq!(quirks, Quirks::for_platform(Platform::Schip));
+
+        let config: Config = "[quirks]\nclip_sprites = false".parse().unwrap();
+        let quirks = config.defaults.quirks.unwrap().apply(Platform::Chip8);
+        assert_eq!(
+            quirks,
+            Quirks {
+                clip_sprites: false,
+                ..Quirks::for_platform(Platform::Chip8)
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_setting_test() {
+        let err = format!("[rom.{}]\nspeed = 3", HASH)
+            .parse::<Config>()
+            .unwrap_err();
+        assert!(err.contains(&format!("in [rom.{}]", HASH)), "{}", err);
+        assert!(err.contains("speed"), "{}", err);
+        assert!("palette = \"pink\"".parse::<Config>().is_err());
+    }
+
+    #[test]
+    fn save_rom_profile_test() {
+        let path = std::env::temp_dir().join("rusty-chip-config-save-test.toml");
+        fs::write(&path, "# My settings\nips = 700\n").unwrap();
+
+        let settings = Settings {
+            palette: Palette::built_in("octo").unwrap(),
+            show_counter: true,
+            ..Settings::default()
+        };
+        config::save_settings(&path, HASH, "pong", &settings).unwrap();
+
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(text.starts_with("# My settings\n"), "{}", text);
+        let profile = text
+            .parse::<Config>()
+            .unwrap()
+            .profile(HASH, &Profile::default());
+        assert_eq!(profile.name.as_deref(), Some("pong"));
+        assert_eq!(profile.ips, Some(700));
+        assert_eq!(profile.palette.unwrap().name, "octo");
+        assert_eq!(profile.show_counter, Some(true));
+        assert_eq!(profile.show_keypad, Some(false));
+    }
+}
@@ -0,0 +1,377 @@
+use crate::chip8::search::{MemorySearch, SearchFilter};
+use crate::chip8::Chip8;
+use crate::frontend::cheats::{Cheat, Cheats};
+use crate::frontend::sprites::SpriteRipper;
+use std::collections::BTreeSet;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+/// The most instructions `continue` runs before giving up on reaching a breakpoint.
+const CONTINUE_LIMIT: u64 = 10_000_000;
+
+/// The most candidates of a memory search that are listed, or that can be watched at once.
+const SEARCH_LIST_LIMIT: usize = 32;
+
+const HELP: &str = "Commands:
+    s, step [N]          Run N instructions (1 if not given)
+    c, continue          Run until a breakpoint is reached
+    b, break ADDR        Stop when the program counter reaches ADDR
+    d, delete ADDR       Remove the breakpoint at ADDR
+    w, watch ADDR        Stop when the byte at ADDR changes
+    unwatch ADDR         Remove the watchpoint at ADDR
+    r, regs              Show the registers, timers and call stack
+    bt, backtrace        Show the subroutines being run, innermost first, and where each was called from
+    m, mem ADDR [LEN]    Show LEN bytes of memory from ADDR (16 if not given)
+    screen               Show the screen
+    key [KEY...]         Hold down the given hex keys, releasing all others
+    search new           Start a memory search with every address as a candidate
+    search FILTER        Keep the candidates that now equal a value (decimal, or hex with 0x), or
+                         that changed, unchanged, increased or decreased since the last search
+    search               List the candidates of the memory search
+    search watch         Watch every candidate
+    cheat ADDR VALUE     Keep the byte at ADDR at VALUE (in hex) from the start of each frame
+    sprites [FILE]       Show the sprites drawn so far, or save them as a .png sheet or .8o Octo data
+    q, quit              Stop debugging
+Addresses are in hex. An empty line repeats the last command.";
+
+/// An interactive debugger that runs a ROM without a frontend, reading commands from a line based input such as stdin.
+pub struct Debugger {
+    emu: Chip8,
+    breakpoints: BTreeSet<u16>,
+    watchpoints: BTreeSet<u16>,
+    search: Option<MemorySearch>,
+    cheats: Cheats,
+    /// The sprites drawn so far.
+    sprites: SpriteRipper,
+    cycles_per_frame: usize,
+    /// The number of instructions run since the timers last counted down.
+    cycle: usize,
+}
+
+impl Debugger {
+    pub fn new(emu: Chip8, cycles_per_frame: usize) -> Self {
+        Debugger {
+            emu,
+            breakpoints: BTreeSet::new(),
+            watchpoints: BTreeSet::new(),
+            search: None,
+            cheats: Cheats::default(),
+            sprites: SpriteRipper::default(),
+            cycles_per_frame: cycles_per_frame.max(1),
+            cycle: 0,
+        }
+    }
+
+    pub fn add_breakpoint(&mut self, addr: u16) {
+        self.breakpoints.insert(addr);
+    }
+
+    /// Reads and runs commands until the user quits or the input ends.
+    pub fn run(&mut self, input: impl BufRead, output: &mut impl Write) -> io::Result<()> {
+        writeln!(output, "Type 'help' for a list of commands.")?;
+        write!(output, "{}", self.location())?;
+
+        let mut lines = input.lines();
+        let mut last_command = String::new();
+        loop {
+            write!(output, "(chip8) ")?;
+            output.flush()?;
+            let line = match lines.next() {
+                Some(line) => line?,
+                None => break,
+            };
+            let command = match line.trim() {
+                "" => last_command.clone(),
+                command => command.to_string(),
+            };
+            match self.execute(&command, output)? {
+                true => last_command = command,
+                false => break,
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs a single command. Returns false once the user quits.
+    fn execute(&mut self, command: &str, output: &mut impl Write) -> io::Result<bool> {
+        let mut words = command.split_whitespace();
+        let name = words.next().unwrap_or_default();
+        let args: Vec<&str> = words.collect();
+        let address = || {
+            args.first()
+                .ok_or_else(|| String::from("An address is needed"))
+                .and_then(|addr| parse_address(addr))
+        };
+
+        let result = match name {
+            "" => Ok(String::new()),
+            "s" | "step" => {
+                let count = match args.first() {
+                    Some(count) => count
+                        .parse()
+                        .map_err(|_| format!("'{}' is not a number of instructions", count)),
+                    None => Ok(1),
+                };
+                count
+                    .and_then(|count| self.run_instructions(count, false))
+                    .map(|_| self.location())
+            }
+            "c" | "continue" => match self.run_instructions(CONTINUE_LIMIT, true) {
+                Ok(Some(reason)) => Ok(reason + &self.location()),
+                Ok(None) => Err(format!(
+                    "No breakpoint was reached after {} instructions",
+                    CONTINUE_LIMIT
+                )),
+                Err(err) => Err(err),
+            },
+            "b" | "break" => address().map(|addr| {
+                self.breakpoints.insert(addr);
+                format!("Breakpoint at {:03X}\n", addr)
+            }),
+            "d" | "delete" => address().and_then(|addr| match self.breakpoints.remove(&addr) {
+                true => Ok(String::new()),
+                false => Err(format!("There is no breakpoint at {:03X}", addr)),
+            }),
+            "w" | "watch" => address().map(|addr| {
+                self.watchpoints.insert(addr);
+                format!("Watchpoint at {:03X}\n", addr)
+            }),
+            "unwatch" => address().and_then(|addr| match self.watchpoints.remove(&addr) {
+                true => Ok(String::new()),
+                false => Err(format!("There is no watchpoint at {:03X}", addr)),
+            }),
+            "r" | "regs" => Ok(self.registers()),
+            "bt" | "backtrace" => Ok(self.backtrace()),
+            "m" | "mem" => address().and_then(|addr| {
+                let len = match args.get(1) {
+                    Some(len) => len
+                        .parse()
+                        .map_err(|_| format!("'{}' is not a length", len))?,
+                    None => 16,
+                };
+                Ok(self.memory(addr, len))
+            }),
+            "screen" => Ok(self.emu.display.to_text()),
+            "key" => args
+                .iter()
+                .try_fold(0u16, |state, key| match u8::from_str_radix(key, 16) {
+                    Ok(key) if key < 16 => Ok(state | 1 << key),
+                    _ => Err(format!("'{}' is not a key between 0 and F", key)),
+                })
+                .map(|state| {
+                    self.emu.input.set_state(state);
+                    String::new()
+                }),
+            "search" => self.search(args.first().copied()),
+            "cheat" => address().and_then(|addr| {
+                let value = args
+                    .get(1)
+                    .ok_or_else(|| String::from("A value is needed"))
+                    .and_then(|value| {
+                        u8::from_str_radix(value.trim_start_matches("0x"), 16)
+                            .map_err(|_| format!("'{}' is not a byte", value))
+                    })?;
+                let number = self.cheats.cheats.len() + 1;
+                self.cheats.cheats.push(Cheat {
+                    name: format!("Cheat {}", number),
+                    address: addr,
+                    value,
+                    condition: None,
+                    enabled: true,
+                });
+                self.emu.write_memory(addr, value);
+                Ok(format!(
+                    "Cheat {} keeps {:03X} at {:02X}. In a cheat file: Cheat {}: {:#05X} = {:#04X}\n",
+                    number, addr, value, number, addr, value
+                ))
+            }),
+            "sprites" => match args.first() {
+                Some(path) => self
+                    .sprites
+                    .save(Path::new(path))
+                    .map(|()| format!("{} sprites saved to {}\n", self.sprites.len(), path)),
+                None if self.sprites.len() == 0 => Ok(String::from("No sprites have been drawn\n")),
+                None => Ok(self.sprites.gallery()),
+            },
+            "h" | "help" => Ok(format!("{}\n", HELP)),
+            "q" | "quit" => return Ok(false),
+            _ => Err(format!("Unknown command '{}', try 'help'", name)),
+        };
+
+        match result {
+            Ok(text) => write!(output, "{}", text)?,
+            Err(err) => writeln!(output, "{}", err)?,
+        }
+        Ok(true)
+    }
+
+    /// Runs up to `count` instructions, counting down the timers and applying cheats as it would at full speed. When
+    /// `stop_at_breakpoint` is set, stops just before running an instruction at a breakpoint, or just after a watched
+    /// byte changes, and returns why it stopped.
+    fn run_instructions(
+        &mut self,
+        count: u64,
+        stop_at_breakpoint: bool,
+    ) -> Result<Option<String>, String> {
+        for i in 0..count {
+            let pc = self.emu.program_counter();
+            if stop_at_breakpoint && i > 0 && self.breakpoints.contains(&pc) {
+                return Ok(Some(String::new()));
+            }
+            let watched: Vec<(u16, u8)> = self
+                .watchpoints
+                .iter()
+                .map(|&addr| (addr, self.emu.read_memory(addr)))
+                .collect();
+            self.sprites.observe(&self.emu);
+            if let Err(err) = self.emu.emulate_cycle() {
+                return Err(format!("Stopped at {:03X}: {}", pc, err));
+            }
+            self.cycle += 1;
+            if self.cycle == self.cycles_per_frame {
+                self.cycle = 0;
+                self.emu.update_timers();
+                self.cheats.apply(&mut self.emu);
+            }
+
+            if !stop_at_breakpoint {
+                continue;
+            }
+            for (addr, before) in watched {
+                let now = self.emu.read_memory(addr);
+                if now != before {
+                    return Ok(Some(format!(
+                        "{:03X} changed from {:02X} to {:02X} at {:03X}\n",
+                        addr, before, now, pc
+                    )));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Runs a memory search command: starts a search, narrows it down with a filter, watches its candidates or, with no
+    /// argument, lists them.
+    fn search(&mut self, arg: Option<&str>) -> Result<String, String> {
+        if arg == Some("new") {
+            let search = MemorySearch::start(&self.emu);
+            let count = search.candidates().len();
+            self.search = Some(search);
+            return Ok(format!("{} candidates\n", count));
+        }
+
+        let search = self
+            .search
+            .as_mut()
+            .ok_or_else(|| String::from("Start a search with 'search new' first"))?;
+        match arg {
+            None => {
+                let candidates = search.candidates();
+                let mut text: String = candidates
+                    .iter()
+                    .take(SEARCH_LIST_LIMIT)
+                    .map(|&addr| format!("{:03X}: {:02X}\n", addr, search.snapshot_value(addr)))
+                    .collect();
+                if candidates.len() > SEARCH_LIST_LIMIT {
+                    text += &format!("...and {} more\n", candidates.len() - SEARCH_LIST_LIMIT);
+                }
+                Ok(text)
+            }
+            Some("watch") => {
+                let candidates = search.candidates();
+                if candidates.len() > SEARCH_LIST_LIMIT {
+                    return Err(format!(
+                        "There are {} candidates, narrow them down to {} or fewer first",
+                        candidates.len(),
+                        SEARCH_LIST_LIMIT
+                    ));
+                }
+                self.watchpoints.extend(candidates);
+                Ok(format!("Watching {} addresses\n", candidates.len()))
+            }
+            Some(filter) => {
+                let filter: SearchFilter = filter.parse()?;
+                let count = search.filter(&self.emu, filter);
+                Ok(format!("{} candidates\n", count))
+            }
+        }
+    }
+
+    /// The address and disassembly of the next instruction.
+    fn location(&self) -> String {
+        let pc = self.emu.program_counter();
+        let opcode = u16::from_be_bytes([self.emu.read_memory(pc), self.emu.read_memory(pc + 1)]);
+        match self.emu.next_instruction() {
+            Ok(instruction) => format!("{:03X}: {:04X}  {}\n", pc, opcode, instruction),
+            Err(_) => format!("{:03X}: {:04X}  (invalid)\n", pc, opcode),
+        }
+    }
+
+    fn registers(&self) -> String {
+        let mut text = String::new();
+        for (row, registers) in self.emu.registers().chunks(8).enumerate() {
+            let registers: Vec<String> = registers
+                .iter()
+                .enumerate()
+                .map(|(i, value)| format!("V{:X}={:02X}", row * 8 + i, value))
+                .collect();
+            text += &format!("{}\n", registers.join(" "));
+        }
+        let (delay, sound) = self.emu.timers();
+        text += &format!(
+            "PC={:03X} I={:03X} DT={:02X} ST={:02X}\n",
+            self.emu.program_counter(),
+            self.emu.index(),
+            delay,
+            sound
+        );
+        let stack: Vec<String> = self
+            .emu
+            .call_stack()
+            .iter()
+            .map(|call| format!("{:03X}", call.from))
+            .collect();
+        text + &format!("Stack: [{}]\n", stack.join(" "))
+    }
+
+    fn backtrace(&self) -> String {
+        let chain = self.emu.call_chain();
+        if chain.is_empty() {
+            return String::from("Not in a subroutine\n");
+        }
+        chain
+            .iter()
+            .enumerate()
+            .map(|(depth, call)| format!("#{} {}\n", depth, call))
+            .collect()
+    }
+
+    /// A hex dump of `len` bytes of memory from `addr`, 16 bytes to a line.
+    /// Shows `len` bytes from `addr`, wrapping around the end of memory. As memory is 4 KiB, at most that much is
+    /// shown.
+    fn memory(&self, addr: u16, len: usize) -> String {
+        let len = len.min(0x1000);
+        let mut text = String::new();
+        for start in (0..len).step_by(16) {
+            let bytes: Vec<String> = (start..len.min(start + 16))
+                .map(|offset| format!("{:02X}", self.emu.read_memory(wrap(addr, offset))))
+                .collect();
+            text += &format!("{:03X}: {}\n", wrap(addr, start), bytes.join(" "));
+        }
+        text
+    }
+}
+
+/// The address `offset` bytes after `addr`, wrapping around the end of memory.
+fn wrap(addr: u16, offset: usize) -> u16 {
+    ((addr as usize + offset) % 0x1000) as u16
+}
+
+/// Parses an address given in hex, with or without a `0x` prefix.
+pub fn parse_address(addr: &str) -> Result<u16, String> {
+    let digits = addr.trim_start_matches("0x").trim_start_matches("0X");
+    match u16::from_str_radix(digits, 16) {
+        Ok(value) if value < 0x1000 => Ok(value),
+        _ => Err(format!("'{}' is not an address", addr)),
+    }
+}
@@ -0,0 +1,66 @@
+#[cfg(test)]
+mod debugger_commands_test {
+    use crate::chip8::asm::assemble;
+    use crate::chip8::Chip8;
+    use crate::debugger::Debugger;
+
+    /// Runs the debugger on a program with the given commands, returning everything it printed.
+    fn debug(source: &str, commands: &str) -> String {
+        let mut emu = Chip8::new();
+        emu.load_rom_bytes(&assemble(source).unwrap()).unwrap();
+
+        let mut output = Vec::new();
+        Debugger::new(emu, 10)
+            .run(commands.as_bytes(), &mut output)
+            .unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn step_and_break_test() {
+        let output = debug(
+            "LD V0, 1\nloop: ADD V0, 1\nCALL sub\nJP loop\nsub: RET",
+            "step 2\nbreak 206\ncontinue\nregs\nquit\n",
+        );
+        assert!(output.contains("204: 2208  CALL 0x208\n"), "{}", output);
+        assert!(output.contains("Breakpoint at 206\n"), "{}", output);
+        assert!(output.contains("206: 1202  JP 0x202\n"), "{}", output);
+        assert!(output.contains("V0=02 V1=00"), "{}", output);
+        assert!(output.contains("Stack: []\n"), "{}", output);
+    }
+
+    #[test]
+    fn repeat_command_test() {
+        let output = debug("LD V0, 1\nLD V1, 2\nLD V2, 3", "step\n\nmem 200 4\n");
+        assert!(output.contains("204: 6203  LD V2, 0x03\n"), "{}", output);
+        assert!(output.contains("200: 60 01 61 02\n"), "{}", output);
+    }
+
+    #[test]
+    fn mem_wrap_test() {
+        let output = debug("LD V0, 1", "mem FFF 515\nmem FF0 65535\nquit\n");
+        assert!(output.contains("1FF: 00 60 01\n"), "{}", output);
+        assert_eq!(output.matches("FF0: ").count(), 1, "{}", output);
+        assert!(output.contains("FE0: 00"), "{}", output);
+    }
+
+    #[test]
+    fn memory_search_command_test() {
+        // Counts down the byte at 0x300 each time around the loop.
+        let output = debug(
+            "LD I, 0x300\nLD V0, 5\nLD [I], V0\nloop: LD I, 0x300\nLD V0, [I]\nADD V0, 0xFF\nLD I, 0x300\nLD [I], V0\nJP loop",
+            "step 3\nsearch new\nsearch 5\nstep 5\nsearch decreased\nsearch\nsearch watch\ncontinue\n\
+             cheat 300 9\nmem 300 1\n",
+        );
+        assert!(output.contains("4096 candidates\n"), "{}", output);
+        assert!(output.contains("300: 04\n"), "{}", output);
+        assert!(output.contains("Watching 1 addresses\n"), "{}", output);
+        assert!(
+            output.contains("300 changed from 04 to 03 at 20E\n"),
+            "{}",
+            output
+        );
+        assert!(output.contains("Cheat 1: 0x300 = 0x09"), "{}", output);
+        assert!(output.contains("300: 09\n"), "{}", output);
+    }
+}
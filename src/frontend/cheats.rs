@@ -0,0 +1,170 @@
+use crate::chip8::Chip8;
+use std::convert::TryInto;
+use std::fs;
+use std::path::Path;
+
+/// How a condition compares the byte in memory with its value.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+/// The comparisons in the order they are looked for, so that `<=` isn't taken for `<`.
+const COMPARISONS: [(&str, Comparison); 6] = [
+    ("==", Comparison::Equal),
+    ("!=", Comparison::NotEqual),
+    ("<=", Comparison::LessOrEqual),
+    (">=", Comparison::GreaterOrEqual),
+    ("<", Comparison::Less),
+    (">", Comparison::Greater),
+];
+
+/// Only lets a cheat write to memory while a byte of memory compares with a value, e.g. while the game is on a
+/// particular level.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Condition {
+    pub address: u16,
+    pub comparison: Comparison,
+    pub value: u8,
+}
+
+impl Condition {
+    fn holds(&self, emu: &Chip8) -> bool {
+        let byte = emu.read_memory(self.address);
+        match self.comparison {
+            Comparison::Equal => byte == self.value,
+            Comparison::NotEqual => byte != self.value,
+            Comparison::Less => byte < self.value,
+            Comparison::LessOrEqual => byte <= self.value,
+            Comparison::Greater => byte > self.value,
+            Comparison::GreaterOrEqual => byte >= self.value,
+        }
+    }
+}
+
+/// Keeps a byte of memory at a value, such as a counter of lives.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cheat {
+    pub name: String,
+    pub address: u16,
+    pub value: u8,
+    pub condition: Option<Condition>,
+    pub enabled: bool,
+}
+
+/// The cheats for a ROM, which are written into memory at the start of every frame.
+///
+/// Cheats live outside the machine, so the state of the machine only ever holds what the cheats wrote into memory and
+/// never the cheats themselves.
+///
+/// # File format
+/// Cheat files are plain text, with one cheat on each line: its name, a colon, then the address and the value to keep
+/// there. A condition can be added after `if`, which compares a byte of memory with a value using `==`, `!=`, `<`,
+/// `<=`, `>` or `>=`. Numbers are decimal, or hex when they start with `0x`. All cheats start out turned on.
+///
+/// ```text
+/// # Lives never run out
+/// Infinite lives: 0x2F0 = 3
+/// Stay on the first level: 0x2F1 = 1 if 0x2F2 != 0
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Cheats {
+    pub cheats: Vec<Cheat>,
+}
+
+impl Cheats {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("Could not read {}: {}", path.display(), err))?;
+        contents.parse()
+    }
+
+    /// Writes the values of the cheats that are turned on and whose conditions hold.
+    pub fn apply(&self, emu: &mut Chip8) {
+        for cheat in &self.cheats {
+            let applies = cheat.enabled
+                && cheat
+                    .condition
+                    .as_ref()
+                    .is_none_or(|condition| condition.holds(emu));
+            if applies {
+                emu.write_memory(cheat.address, cheat.value);
+            }
+        }
+    }
+
+    /// Turns a cheat on or off by its index, returning the cheat if there is one.
+    pub fn toggle(&mut self, index: usize) -> Option<&Cheat> {
+        let cheat = self.cheats.get_mut(index)?;
+        cheat.enabled = !cheat.enabled;
+        Some(cheat)
+    }
+}
+
+impl std::str::FromStr for Cheats {
+    type Err = String;
+
+    fn from_str(contents: &str) -> Result<Self, Self::Err> {
+        let mut cheats = Vec::new();
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let cheat = parse_cheat(line)
+                .ok_or_else(|| format!("Invalid cheat on line {}: '{}'", number + 1, line))?;
+            cheats.push(cheat);
+        }
+        Ok(Cheats { cheats })
+    }
+}
+
+/// Parses a line of the form `NAME: ADDRESS = VALUE [if ADDRESS OP VALUE]`.
+fn parse_cheat(line: &str) -> Option<Cheat> {
+    let (name, rest) = line.split_once(':')?;
+    let (assignment, condition) = match rest.split_once(" if ") {
+        Some((assignment, condition)) => (assignment, Some(condition)),
+        None => (rest, None),
+    };
+    let (address, value) = assignment.split_once('=')?;
+    let condition = match condition {
+        Some(condition) => Some(parse_condition(condition)?),
+        None => None,
+    };
+    Some(Cheat {
+        name: name.trim().to_string(),
+        address: parse_address(address)?,
+        value: parse_number(value)?.try_into().ok()?,
+        condition,
+        enabled: true,
+    })
+}
+
+fn parse_condition(condition: &str) -> Option<Condition> {
+    let (symbol, comparison) = COMPARISONS
+        .iter()
+        .find(|(symbol, _)| condition.contains(symbol))?;
+    let (address, value) = condition.split_once(symbol)?;
+    Some(Condition {
+        address: parse_address(address)?,
+        comparison: *comparison,
+        value: parse_number(value)?.try_into().ok()?,
+    })
+}
+
+fn parse_address(address: &str) -> Option<u16> {
+    parse_number(address).filter(|&address| address <= 0xFFF)
+}
+
+fn parse_number(number: &str) -> Option<u16> {
+    let number = number.trim();
+    match number.strip_prefix("0x") {
+        Some(hex) => u16::from_str_radix(hex, 16).ok(),
+        None => number.parse().ok(),
+    }
+}
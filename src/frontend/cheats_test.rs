@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod cheat_file_test {
+    use crate::chip8::Chip8;
+    use crate::frontend::cheats::{Cheat, Cheats, Comparison, Condition};
+
+    #[test]
+    fn parse_cheats_test() {
+        let cheats: Cheats =
+            "# Lives\nInfinite lives: 0x2F0 = 3\n\nLevel 1: 0x2F1 = 1 if 0x2F2 <= 0x10\n"
+                .parse()
+                .unwrap();
+        assert_eq!(
+            cheats.cheats,
+            vec![
+                Cheat {
+                    name: String::from("Infinite lives"),
+                    address: 0x2F0,
+                    value: 3,
+                    condition: None,
+                    enabled: true,
+                },
+                Cheat {
+                    name: String::from("Level 1"),
+                    address: 0x2F1,
+                    value: 1,
+                    condition: Some(Condition {
+                        address: 0x2F2,
+                        comparison: Comparison::LessOrEqual,
+                        value: 0x10,
+                    }),
+                    enabled: true,
+                },
+            ]
+        );
+
+        assert_eq!(
+            "Lives: 0x1000 = 3".parse::<Cheats>(),
+            Err(String::from("Invalid cheat on line 1: 'Lives: 0x1000 = 3'"))
+        );
+        assert!("Lives: 0x2F0 = 256".parse::<Cheats>().is_err());
+        assert!("0x2F0 = 3".parse::<Cheats>().is_err());
+    }
+
+    #[test]
+    fn apply_cheats_test() {
+        let mut cheats: Cheats = "A: 0x300 = 9\nB: 0x301 = 7 if 0x302 == 1\nC: 0x303 = 5"
+            .parse()
+            .unwrap();
+        let mut emu = Chip8::new();
+        cheats.toggle(2);
+        cheats.apply(&mut emu);
+        assert_eq!(emu.read_memory(0x300), 9);
+        assert_eq!(emu.read_memory(0x301), 0);
+        assert_eq!(emu.read_memory(0x303), 0);
+
+        emu.write_memory(0x302, 1);
+        cheats.toggle(2);
+        cheats.apply(&mut emu);
+        assert_eq!(emu.read_memory(0x301), 7);
+        assert_eq!(emu.read_memory(0x303), 5);
+        assert!(cheats.toggle(3).is_none());
+    }
+}
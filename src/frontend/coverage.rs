@@ -0,0 +1,200 @@
+use super::screenshot::save_png;
+use super::{Frame, Rgb};
+use crate::chip8::disasm::disassemble_opcode;
+use crate::chip8::instructions::Instruction;
+use crate::chip8::Chip8;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The size of memory, which the coverage map covers all of.
+const MEMORY_SIZE: usize = 0x1000;
+
+/// Where ROMs are loaded.
+const ROM_START: usize = 0x200;
+
+/// The number of bytes on each row of the coverage image.
+const IMAGE_WIDTH: usize = 64;
+
+/// The size of each byte in the coverage image.
+const IMAGE_SCALE: u32 = 8;
+
+/// The colour of ROM bytes that were never used. Bytes outside the ROM that were never used are black.
+const UNUSED_ROM: Rgb = Rgb(0x40, 0x40, 0x40);
+
+/// How a byte of memory was used.
+#[derive(Clone, Copy, Debug, Default)]
+struct Usage {
+    /// Run as part of an instruction.
+    executed: bool,
+    /// Read as data, by DXYN or FX65.
+    read: bool,
+    /// Written by FX33 or FX55.
+    written: bool,
+}
+
+impl Usage {
+    fn is_used(self) -> bool {
+        self.executed || self.read || self.written
+    }
+
+    fn merge(self, other: Usage) -> Usage {
+        Usage {
+            executed: self.executed || other.executed,
+            read: self.read || other.read,
+            written: self.written || other.written,
+        }
+    }
+
+    /// The usage as three letters, `x` for run, `r` for read and `w` for written, with `-` for each that didn't happen.
+    fn flags(self) -> String {
+        [(self.executed, 'x'), (self.read, 'r'), (self.written, 'w')]
+            .iter()
+            .map(|&(used, flag)| if used { flag } else { '-' })
+            .collect()
+    }
+
+    /// The colour of the byte in the coverage image: green for run, blue for read and red for written, mixed when a
+    /// byte was used in several ways.
+    fn color(self) -> Rgb {
+        let channel = |used: bool| if used { 0xFF } else { 0x00 };
+        Rgb(
+            channel(self.written),
+            channel(self.executed),
+            channel(self.read),
+        )
+    }
+}
+
+/// Tracks which bytes of memory a game runs, reads and writes, by looking at each instruction before it runs. Played
+/// through, it shows which parts of a ROM were never used.
+pub struct Coverage {
+    usage: Vec<Usage>,
+}
+
+impl Default for Coverage {
+    fn default() -> Self {
+        Coverage {
+            usage: vec![Usage::default(); MEMORY_SIZE],
+        }
+    }
+}
+
+impl Coverage {
+    /// Records the bytes the instruction about to run uses.
+    pub fn observe(&mut self, emu: &Chip8) {
+        let pc = emu.program_counter() as usize;
+        let instruction = match emu.next_instruction() {
+            Ok(instruction) => instruction,
+            Err(_) => return,
+        };
+        self.mark(pc, 2, |usage| usage.executed = true);
+
+        let i = emu.index() as usize;
+        match instruction {
+            Instruction::DrawSprite(_, _, height) => {
+                self.mark(i, height, |usage| usage.read = true)
+            }
+            Instruction::MemRegisterLoad(last) => self.mark(i, last + 1, |usage| usage.read = true),
+            Instruction::MemRegisterDump(last) => {
+                self.mark(i, last + 1, |usage| usage.written = true)
+            }
+            Instruction::BCDSave(_) => self.mark(i, 3, |usage| usage.written = true),
+            _ => {}
+        }
+    }
+
+    fn mark(&mut self, address: usize, length: usize, set: impl Fn(&mut Usage)) {
+        for offset in 0..length {
+            set(&mut self.usage[(address + offset) % MEMORY_SIZE]);
+        }
+    }
+
+    /// Disassembles a ROM, marking each line with how its bytes were used. Bytes that were run are disassembled as
+    /// instructions and everything else as single bytes of data, so that code and data are told apart the way the game
+    /// used them.
+    pub fn annotated_disassembly(&self, rom: &[u8]) -> String {
+        let rom_usage = &self.usage[ROM_START..ROM_START + rom.len()];
+        let count = |used: fn(&Usage) -> bool| rom_usage.iter().filter(|usage| used(usage)).count();
+        let percent = |count: usize| count as f64 * 100.0 / rom.len().max(1) as f64;
+        let (executed, read, written) = (
+            count(|usage| usage.executed),
+            count(|usage| usage.read),
+            count(|usage| usage.written),
+        );
+        let unused = count(|usage| !usage.is_used());
+
+        let mut listing = format!(
+            "; Coverage of the {} bytes of the ROM\n\
+             ; x: run as an instruction, r: read as data, w: written, -: not used that way\n\
+             ; {} run ({:.1}%), {} read ({:.1}%), {} written ({:.1}%), {} never used ({:.1}%)\n",
+            rom.len(),
+            executed,
+            percent(executed),
+            read,
+            percent(read),
+            written,
+            percent(written),
+            unused,
+            percent(unused)
+        );
+
+        let mut offset = 0;
+        while offset < rom.len() {
+            let address = ROM_START + offset;
+            let usage = self.usage[address];
+            let (text, encoded, length) = match rom.get(offset..offset + 2) {
+                Some(&[hi, lo]) if usage.executed => {
+                    let opcode = u16::from_be_bytes([hi, lo]);
+                    let text = disassemble_opcode(opcode);
+                    (text, format!("{:04X}", opcode), 2)
+                }
+                _ => (
+                    format!("DB 0x{:02X}", rom[offset]),
+                    format!("{:02X}", rom[offset]),
+                    1,
+                ),
+            };
+            let usage = (0..length).fold(Usage::default(), |merged, byte| {
+                merged.merge(self.usage[address + byte])
+            });
+            let _ = writeln!(
+                listing,
+                "    {:<20} ; {:03X}: {:<4}  {}",
+                text,
+                address,
+                encoded,
+                usage.flags()
+            );
+            offset += length;
+        }
+        listing
+    }
+
+    /// Draws all of memory with one square per byte, in rows of 64 bytes, coloured by how each byte was used.
+    pub fn image(&self, rom_length: usize) -> Frame {
+        let rom = ROM_START..ROM_START + rom_length;
+        let mut frame = Frame::new(IMAGE_WIDTH, MEMORY_SIZE / IMAGE_WIDTH, Rgb(0, 0, 0));
+        for (address, usage) in self.usage.iter().enumerate() {
+            let color = if usage.is_used() {
+                usage.color()
+            } else if rom.contains(&address) {
+                UNUSED_ROM
+            } else {
+                continue;
+            };
+            frame.set(address % IMAGE_WIDTH, address / IMAGE_WIDTH, color);
+        }
+        frame
+    }
+
+    /// Saves the annotated disassembly of the ROM at `path`, and the image next to it with the extension `.png`.
+    /// Returns the path of the image.
+    pub fn save(&self, path: &Path, rom: &[u8]) -> Result<PathBuf, String> {
+        let image = path.with_extension("png");
+        fs::write(path, self.annotated_disassembly(rom))
+            .and_then(|()| save_png(&self.image(rom.len()), &image, IMAGE_SCALE))
+            .map_err(|err| format!("Could not save the coverage: {}", err))?;
+        Ok(image)
+    }
+}
@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod coverage_map_test {
+    use crate::chip8::Chip8;
+    use crate::frontend::coverage::Coverage;
+    use crate::frontend::Rgb;
+
+    /// Draws a sprite from its data, writes over part of it with FX33, then loops forever.
+    const ROM: &[u8] = &[
+        0xA2, 0x08, // I = 0x208
+        0xD0, 0x02, // Draw 2 bytes
+        0xF0, 0x33, // Write V0 in decimal to 0x208-0x20A
+        0x12, 0x06, // Loop forever
+        0x81, 0xFF, 0x00, // Data
+        0x42, // Never used
+    ];
+
+    fn run() -> Coverage {
+        let mut emu = Chip8::new();
+        emu.load_rom_bytes(ROM).unwrap();
+        let mut coverage = Coverage::default();
+        for _ in 0..6 {
+            coverage.observe(&emu);
+            emu.emulate_cycle().unwrap();
+        }
+        coverage
+    }
+
+    #[test]
+    fn coverage_marks_test() {
+        let listing = run().annotated_disassembly(ROM);
+        let lines: Vec<&str> = listing.lines().collect();
+        assert_eq!(
+            lines[2],
+            "; 8 run (66.7%), 2 read (16.7%), 3 written (25.0%), 1 never used (8.3%)"
+        );
+        assert_eq!(
+            &lines[3..],
+            [
+                "    LD I, 0x208          ; 200: A208  x--",
+                "    DRW V0, V0, 2        ; 202: D002  x--",
+                "    LD B, V0             ; 204: F033  x--",
+                "    JP 0x206             ; 206: 1206  x--",
+                "    DB 0x81              ; 208: 81    -rw",
+                "    DB 0xFF              ; 209: FF    -rw",
+                "    DB 0x00              ; 20A: 00    --w",
+                "    DB 0x42              ; 20B: 42    ---",
+            ]
+        );
+    }
+
+    #[test]
+    fn coverage_image_test() {
+        let image = run().image(ROM.len());
+        assert_eq!((image.width, image.height), (64, 64));
+        // 0x200 is the start of row 8.
+        assert_eq!(image.get(0, 8), Rgb(0x00, 0xFF, 0x00));
+        assert_eq!(image.get(8, 8), Rgb(0xFF, 0x00, 0xFF));
+        assert_eq!(image.get(11, 8), Rgb(0x40, 0x40, 0x40));
+        assert_eq!(image.get(12, 8), Rgb(0x00, 0x00, 0x00));
+    }
+}
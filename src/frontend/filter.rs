@@ -0,0 +1,265 @@
+use super::{Frame, Rgb};
+use std::str::FromStr;
+
+/// Post-processing effects that give the scaled up screen the look of an old CRT or LCD. They run on the CPU, so they
+/// look the same in the window, in screenshots and in recordings.
+///
+/// Each effect has an intensity between 0 and 1, and is turned off when it is `None`. Effects are always applied in
+/// the order of the fields, whatever order they were given in.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Filters {
+    /// Darkens the bottom of every row of pixels, like the gaps between the lines of a CRT.
+    pub scanlines: Option<f32>,
+    /// Darkens the edges of every pixel, like the gaps between the cells of an LCD.
+    pub grid: Option<f32>,
+    /// Makes bright pixels glow onto their neighbours.
+    pub bloom: Option<f32>,
+    /// Bulges the screen outwards like the glass of a CRT.
+    pub curvature: Option<f32>,
+    /// Darkens the corners of the screen.
+    pub vignette: Option<f32>,
+}
+
+/// The names of the effects and the intensities they have when none is given.
+const EFFECTS: [(&str, f32); 5] = [
+    ("scanlines", 0.5),
+    ("grid", 0.3),
+    ("bloom", 0.4),
+    ("curvature", 0.3),
+    ("vignette", 0.4),
+];
+
+impl Filters {
+    /// Scales up a frame so that each pixel is a `scale` sized square, then applies the effects.
+    pub fn apply(&self, frame: &Frame, scale: usize) -> Frame {
+        let scale = scale.max(1);
+        let mut image = Frame::new(frame.width * scale, frame.height * scale, frame.background);
+        for y in 0..image.height {
+            for x in 0..image.width {
+                image.set(x, y, frame.get(x / scale, y / scale));
+            }
+        }
+
+        if let Some(intensity) = self.scanlines {
+            scanlines(&mut image, scale, intensity);
+        }
+        if let Some(intensity) = self.grid {
+            grid(&mut image, scale, intensity);
+        }
+        if let Some(intensity) = self.bloom {
+            bloom(&mut image, scale, intensity);
+        }
+        if let Some(intensity) = self.curvature {
+            image = curvature(&image, intensity);
+        }
+        if let Some(intensity) = self.vignette {
+            vignette(&mut image, intensity);
+        }
+        image
+    }
+}
+
+impl FromStr for Filters {
+    type Err = String;
+
+    /// Parses a comma separated list of effects, each optionally followed by its intensity, e.g.
+    /// `scanlines,bloom:0.6`. `none` turns every effect off.
+    fn from_str(list: &str) -> Result<Self, Self::Err> {
+        let mut filters = Filters::default();
+        if list == "none" {
+            return Ok(filters);
+        }
+
+        for effect in list.split(',') {
+            let mut parts = effect.splitn(2, ':');
+            let name = parts.next().unwrap_or_default();
+            let default = EFFECTS
+                .iter()
+                .find(|(effect, _)| *effect == name)
+                .map(|(_, intensity)| *intensity)
+                .ok_or_else(|| {
+                    let names: Vec<_> = EFFECTS.iter().map(|(name, _)| *name).collect();
+                    format!(
+                        "Unknown filter '{}', expected one of: {}",
+                        name,
+                        names.join(", ")
+                    )
+                })?;
+            let intensity = match parts.next() {
+                None => default,
+                Some(intensity) => match intensity.parse::<f32>() {
+                    Ok(intensity) if (0.0..=1.0).contains(&intensity) => intensity,
+                    _ => {
+                        return Err(format!(
+                            "The intensity of {} must be between 0 and 1, got '{}'",
+                            name, intensity
+                        ))
+                    }
+                },
+            };
+
+            let field = match name {
+                "scanlines" => &mut filters.scanlines,
+                "grid" => &mut filters.grid,
+                "bloom" => &mut filters.bloom,
+                "curvature" => &mut filters.curvature,
+                _ => &mut filters.vignette,
+            };
+            *field = Some(intensity);
+        }
+        Ok(filters)
+    }
+}
+
+/// Multiplies the brightness of a colour by `factor`.
+fn darken(Rgb(r, g, b): Rgb, factor: f32) -> Rgb {
+    let scale = |channel: u8| (channel as f32 * factor).round().clamp(0.0, 255.0) as u8;
+    Rgb(scale(r), scale(g), scale(b))
+}
+
+fn scanlines(image: &mut Frame, scale: usize, intensity: f32) {
+    // The bottom third of each row of pixels is darkened. Without any scaling, every other row is darkened instead.
+    let is_gap = |y: usize| {
+        if scale == 1 {
+            y % 2 == 1
+        } else {
+            y % scale >= scale - (scale / 3).max(1)
+        }
+    };
+
+    for y in (0..image.height).filter(|&y| is_gap(y)) {
+        for x in 0..image.width {
+            image.set(x, y, darken(image.get(x, y), 1.0 - intensity));
+        }
+    }
+}
+
+fn grid(image: &mut Frame, scale: usize, intensity: f32) {
+    // There's no room for gaps between pixels that aren't scaled up.
+    if scale == 1 {
+        return;
+    }
+
+    for y in 0..image.height {
+        for x in 0..image.width {
+            if x % scale == scale - 1 || y % scale == scale - 1 {
+                image.set(x, y, darken(image.get(x, y), 1.0 - intensity));
+            }
+        }
+    }
+}
+
+fn bloom(image: &mut Frame, scale: usize, intensity: f32) {
+    // The glow is the image blurred over about a pixel in each direction, added on top of the image.
+    let radius = scale;
+    let channels: Vec<[f32; 3]> = (0..image.width * image.height)
+        .map(|i| {
+            let Rgb(r, g, b) = image.get(i % image.width, i / image.width);
+            [r as f32, g as f32, b as f32]
+        })
+        .collect();
+    let horizontal = box_blur(&channels, image.width, image.height, radius, true);
+    let blurred = box_blur(&horizontal, image.width, image.height, radius, false);
+
+    for y in 0..image.height {
+        for x in 0..image.width {
+            let Rgb(r, g, b) = image.get(x, y);
+            let glow = blurred[y * image.width + x];
+            let add = |channel: u8, glow: f32| (channel as f32 + glow * intensity).min(255.0) as u8;
+            image.set(x, y, Rgb(add(r, glow[0]), add(g, glow[1]), add(b, glow[2])));
+        }
+    }
+}
+
+/// Averages each pixel with the `radius` pixels on either side of it, either along rows or along columns.
+fn box_blur(
+    channels: &[[f32; 3]],
+    width: usize,
+    height: usize,
+    radius: usize,
+    horizontal: bool,
+) -> Vec<[f32; 3]> {
+    let (lines, length) = if horizontal {
+        (height, width)
+    } else {
+        (width, height)
+    };
+    let index = |line: usize, position: usize| {
+        if horizontal {
+            line * width + position
+        } else {
+            position * width + line
+        }
+    };
+
+    let mut blurred = vec![[0.0; 3]; channels.len()];
+    let window = (2 * radius + 1) as f32;
+    for line in 0..lines {
+        // Keep a running sum of the pixels under the window as it slides along the line. Pixels past the ends of the
+        // line count as black.
+        let mut sum = [0.0; 3];
+        for position in 0..radius.min(length) {
+            for c in 0..3 {
+                sum[c] += channels[index(line, position)][c];
+            }
+        }
+        for position in 0..length {
+            if position + radius < length {
+                for c in 0..3 {
+                    sum[c] += channels[index(line, position + radius)][c];
+                }
+            }
+            if position > radius {
+                for c in 0..3 {
+                    sum[c] -= channels[index(line, position - radius - 1)][c];
+                }
+            }
+            for c in 0..3 {
+                blurred[index(line, position)][c] = sum[c] / window;
+            }
+        }
+    }
+    blurred
+}
+
+/// Returns the position of a pixel relative to the centre of the image, between -1 and 1 on both axes.
+fn centred(image: &Frame, x: usize, y: usize) -> (f32, f32) {
+    (
+        (x as f32 + 0.5) / image.width as f32 * 2.0 - 1.0,
+        (y as f32 + 0.5) / image.height as f32 * 2.0 - 1.0,
+    )
+}
+
+fn curvature(image: &Frame, intensity: f32) -> Frame {
+    let strength = intensity * 0.25;
+    let mut curved = Frame::new(image.width, image.height, Rgb(0, 0, 0));
+
+    for y in 0..image.height {
+        for x in 0..image.width {
+            // Pixels further from the centre are taken from further out in the image, which bulges it outwards and
+            // leaves the corners black.
+            let (u, v) = centred(image, x, y);
+            let (u, v) = (u * (1.0 + strength * v * v), v * (1.0 + strength * u * u));
+            let source_x = (u + 1.0) / 2.0 * image.width as f32;
+            let source_y = (v + 1.0) / 2.0 * image.height as f32;
+            if source_x >= 0.0
+                && source_y >= 0.0
+                && (source_x as usize) < image.width
+                && (source_y as usize) < image.height
+            {
+                curved.set(x, y, image.get(source_x as usize, source_y as usize));
+            }
+        }
+    }
+    curved
+}
+
+fn vignette(image: &mut Frame, intensity: f32) {
+    for y in 0..image.height {
+        for x in 0..image.width {
+            let (u, v) = centred(image, x, y);
+            let factor = 1.0 - intensity * (u * u + v * v) / 2.0;
+            image.set(x, y, darken(image.get(x, y), factor.max(0.0)));
+        }
+    }
+}
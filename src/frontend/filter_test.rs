@@ -0,0 +1,74 @@
+#[cfg(test)]
+mod filter_effects_test {
+    use crate::frontend::filter::Filters;
+    use crate::frontend::{Frame, Rgb};
+
+    const WHITE: Rgb = Rgb(0xFF, 0xFF, 0xFF);
+
+    fn white_frame() -> Frame {
+        let mut frame = Frame::new(4, 4, Rgb(0, 0, 0));
+        for y in 0..4 {
+            for x in 0..4 {
+                frame.set(x, y, WHITE);
+            }
+        }
+        frame
+    }
+
+    #[test]
+    fn parse_filters_test() {
+        let filters: Filters = "scanlines,bloom:0.6".parse().unwrap();
+        assert_eq!(filters.scanlines, Some(0.5));
+        assert_eq!(filters.bloom, Some(0.6));
+        assert_eq!(filters.grid, None);
+        assert_eq!("none".parse::<Filters>(), Ok(Filters::default()));
+        assert!("blur".parse::<Filters>().is_err());
+        assert!("vignette:2".parse::<Filters>().is_err());
+    }
+
+    #[test]
+    fn no_filters_test() {
+        let mut frame = Frame::new(2, 1, Rgb(0, 0, 0));
+        frame.set(1, 0, WHITE);
+        let image = Filters::default().apply(&frame, 3);
+        assert_eq!((image.width, image.height), (6, 3));
+        assert_eq!(image.get(2, 2), Rgb(0, 0, 0));
+        assert_eq!(image.get(3, 2), WHITE);
+    }
+
+    #[test]
+    fn scanlines_and_grid_test() {
+        let filters: Filters = "scanlines:0.5".parse().unwrap();
+        let image = filters.apply(&white_frame(), 3);
+        assert_eq!(image.get(0, 0), WHITE);
+        assert_eq!(image.get(0, 1), WHITE);
+        assert_eq!(image.get(0, 2), Rgb(0x80, 0x80, 0x80));
+
+        let filters: Filters = "grid:1".parse().unwrap();
+        let image = filters.apply(&white_frame(), 3);
+        assert_eq!(image.get(1, 1), WHITE);
+        assert_eq!(image.get(2, 1), Rgb(0, 0, 0));
+        assert_eq!(image.get(1, 2), Rgb(0, 0, 0));
+    }
+
+    #[test]
+    fn bloom_test() {
+        let mut frame = Frame::new(5, 1, Rgb(0, 0, 0));
+        frame.set(2, 0, WHITE);
+        let filters: Filters = "bloom:1".parse().unwrap();
+        let image = filters.apply(&frame, 1);
+        assert_eq!(image.get(0, 0), Rgb(0, 0, 0));
+        assert!(image.get(1, 0).0 > 0);
+        assert_eq!(image.get(2, 0), WHITE);
+    }
+
+    #[test]
+    fn curvature_and_vignette_test() {
+        let filters: Filters = "curvature:1,vignette:1".parse().unwrap();
+        let image = filters.apply(&white_frame(), 8);
+        let centre = image.get(16, 16);
+        assert!(centre.0 > 0xF0);
+        assert_eq!(image.get(0, 0), Rgb(0, 0, 0));
+        assert!(image.get(16, 1).0 < centre.0);
+    }
+}
@@ -0,0 +1,14 @@
+use super::{Frame, Frontend, Hotkey};
+use crate::chip8::input::Input;
+
+/// A frontend without any output or input, for running ROMs in scripts and CI. The emulator keeps running until it
+/// reaches its frame limit.
+pub struct HeadlessFrontend;
+
+impl Frontend for HeadlessFrontend {
+    fn poll_input(&mut self, _input: &mut Input) -> Vec<Hotkey> {
+        Vec::new()
+    }
+
+    fn draw(&mut self, _frame: &Frame) {}
+}
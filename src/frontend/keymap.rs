@@ -0,0 +1,77 @@
+use crate::chip8::input::CHIP8_NUM_KEYS;
+use std::str::FromStr;
+
+/// The default layout, which maps the left side of a QWERTY keyboard onto the hex keypad:
+///
+/// | | | | |
+/// |--|--|--|--|
+/// | 1 | 2 | 3 | 4 |
+/// | Q | W | E | R |
+/// | A | S | D | F |
+/// | Z | X | C | V |
+const DEFAULT_LAYOUT: &str = "x123qweasdzc4rfv";
+
+/// The hex keys in the order they are laid out on the original COSMAC VIP keypad.
+pub const KEYPAD_LAYOUT: [[usize; 4]; 4] = [
+    [0x1, 0x2, 0x3, 0xC],
+    [0x4, 0x5, 0x6, 0xD],
+    [0x7, 0x8, 0x9, 0xE],
+    [0xA, 0x0, 0xB, 0xF],
+];
+
+/// Maps keyboard characters to the 16 keys of the CHIP-8 hex keypad. The character at index N of the layout is the
+/// keyboard key that presses hex key N.
+#[derive(Clone, Debug)]
+pub struct KeyMap {
+    keys: [char; CHIP8_NUM_KEYS],
+}
+
+impl KeyMap {
+    /// Returns the hex key bound to the given character. Letters are matched regardless of case.
+    pub fn key_for(&self, c: char) -> Option<usize> {
+        let c = c.to_ascii_lowercase();
+        self.keys.iter().position(|&key| key == c)
+    }
+
+    /// Binds a character to a hex key. Whichever hex key the character pressed before gets the character the hex key
+    /// had, so that every hex key can still be pressed.
+    pub fn bind(&mut self, c: char, key: usize) {
+        let c = c.to_ascii_lowercase();
+        if let Some(old) = self.key_for(c) {
+            self.keys[old] = self.keys[key];
+        }
+        self.keys[key] = c;
+    }
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        DEFAULT_LAYOUT.parse().unwrap()
+    }
+}
+
+impl FromStr for KeyMap {
+    type Err = String;
+
+    /// Parses a layout of exactly 16 distinct characters, ordered from hex key 0 to hex key F.
+    fn from_str(layout: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = layout.chars().map(|c| c.to_ascii_lowercase()).collect();
+        if chars.len() != CHIP8_NUM_KEYS {
+            return Err(format!(
+                "Key map must contain {} characters, got {}",
+                CHIP8_NUM_KEYS,
+                chars.len()
+            ));
+        }
+
+        let mut keys = [' '; CHIP8_NUM_KEYS];
+        for (i, c) in chars.into_iter().enumerate() {
+            if keys[..i].contains(&c) {
+                return Err(format!("Key '{}' is mapped more than once", c));
+            }
+            keys[i] = c;
+        }
+
+        Ok(KeyMap { keys })
+    }
+}
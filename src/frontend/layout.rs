@@ -0,0 +1,42 @@
+/// The area of a window the screen is drawn in, in window pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Viewport {
+    /// Fits a screen of `screen` pixels into a window of `window` pixels, keeping its aspect ratio and centring it with
+    /// black bars around it. Unless `stretch` is set, every pixel of the screen is drawn as the same whole number of
+    /// window pixels, which keeps pixels sharp but may leave wider bars.
+    pub fn fit(window: (u32, u32), screen: (u32, u32), stretch: bool) -> Self {
+        let (window_width, window_height) = window;
+        let (screen_width, screen_height) = (screen.0.max(1), screen.1.max(1));
+
+        let (width, height) = if stretch {
+            let scale = f64::min(
+                window_width as f64 / screen_width as f64,
+                window_height as f64 / screen_height as f64,
+            );
+            (
+                (screen_width as f64 * scale).round() as u32,
+                (screen_height as f64 * scale).round() as u32,
+            )
+        } else {
+            // Windows smaller than the screen still get one window pixel per pixel, cropping the screen.
+            let scale = (window_width / screen_width)
+                .min(window_height / screen_height)
+                .max(1);
+            (screen_width * scale, screen_height * scale)
+        };
+
+        Viewport {
+            x: window_width.saturating_sub(width) / 2,
+            y: window_height.saturating_sub(height) / 2,
+            width,
+            height,
+        }
+    }
+}
@@ -0,0 +1,54 @@
+#[cfg(test)]
+mod layout_viewport_test {
+    use crate::frontend::layout::Viewport;
+
+    #[test]
+    fn integer_scale_test() {
+        let viewport = Viewport::fit((640, 320), (64, 32), false);
+        assert_eq!(
+            viewport,
+            Viewport {
+                x: 0,
+                y: 0,
+                width: 640,
+                height: 320
+            }
+        );
+
+        // The largest whole scale that fits is 12, leaving bars on every side.
+        let viewport = Viewport::fit((800, 600), (64, 32), false);
+        assert_eq!(
+            viewport,
+            Viewport {
+                x: 16,
+                y: 108,
+                width: 768,
+                height: 384
+            }
+        );
+
+        // A high resolution screen in the same window gets half the scale.
+        let viewport = Viewport::fit((800, 600), (128, 64), false);
+        assert_eq!((viewport.width, viewport.height), (768, 384));
+    }
+
+    #[test]
+    fn stretch_test() {
+        let viewport = Viewport::fit((800, 600), (64, 32), true);
+        assert_eq!(
+            viewport,
+            Viewport {
+                x: 0,
+                y: 100,
+                width: 800,
+                height: 400
+            }
+        );
+    }
+
+    #[test]
+    fn small_window_test() {
+        let viewport = Viewport::fit((50, 20), (64, 32), false);
+        assert_eq!((viewport.width, viewport.height), (64, 32));
+    }
+}
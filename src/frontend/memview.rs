@@ -0,0 +1,257 @@
+use super::Rgb;
+use crate::chip8::Chip8;
+
+/// The number of bytes shown on each row.
+pub const BYTES_PER_ROW: usize = 8;
+
+/// The number of rows shown at once.
+pub const VISIBLE_ROWS: usize = 16;
+
+/// Where the font is kept in memory.
+const FONT: std::ops::Range<u16> = 0x50..0xA0;
+
+/// Where ROMs are loaded.
+const ROM_START: u16 = 0x200;
+
+const ADDRESS_COLOR: Rgb = Rgb(0x80, 0x80, 0x80);
+const BACKGROUND: Rgb = Rgb(0x00, 0x00, 0x00);
+
+/// Why a byte of memory stands out, from the most important to the least. A byte that is several of these is shown as
+/// the first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Highlight {
+    /// The byte being edited.
+    Cursor,
+    /// One of the two bytes of the next instruction.
+    ProgramCounter,
+    /// The byte I points at.
+    Index,
+    /// A return address on the stack.
+    StackTarget,
+    /// A byte that changed during the last frame.
+    Changed,
+    /// Part of the built-in font.
+    Font,
+    /// Part of the loaded ROM.
+    Rom,
+    Plain,
+}
+
+impl Highlight {
+    /// The foreground and background colours of a byte with this highlight.
+    pub fn colors(self) -> (Rgb, Rgb) {
+        match self {
+            Highlight::Cursor => (BACKGROUND, Rgb(0xFF, 0xFF, 0xFF)),
+            Highlight::ProgramCounter => (Rgb(0xFF, 0xFF, 0xFF), Rgb(0xC0, 0x30, 0x30)),
+            Highlight::Index => (Rgb(0xFF, 0xFF, 0xFF), Rgb(0x20, 0x80, 0x20)),
+            Highlight::StackTarget => (Rgb(0xFF, 0xFF, 0xFF), Rgb(0x80, 0x40, 0xC0)),
+            Highlight::Changed => (BACKGROUND, Rgb(0xFF, 0xCC, 0x00)),
+            Highlight::Font => (Rgb(0x80, 0xC0, 0xFF), BACKGROUND),
+            Highlight::Rom => (Rgb(0xFF, 0xFF, 0xFF), BACKGROUND),
+            Highlight::Plain => (Rgb(0xA0, 0xA0, 0xA0), BACKGROUND),
+        }
+    }
+}
+
+/// A piece of a row of the memory viewer.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Segment {
+    /// Text in the given colours.
+    Text(String, Rgb, Rgb),
+    /// A byte drawn as eight pixels, the way DXYN would draw it, in the given colours.
+    Bitmap(u8, Rgb, Rgb),
+}
+
+/// A row of memory: its address, then each byte in hex, as a character and as pixels.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MemoryRow {
+    pub address: u16,
+    pub bytes: Vec<(u8, Highlight)>,
+}
+
+impl MemoryRow {
+    /// The columns of the row, in the order they are drawn.
+    pub fn segments(&self) -> Vec<Segment> {
+        let mut segments = vec![Segment::Text(
+            format!("{:03X} ", self.address),
+            ADDRESS_COLOR,
+            BACKGROUND,
+        )];
+        for &(byte, highlight) in &self.bytes {
+            let (foreground, background) = highlight.colors();
+            segments.push(Segment::Text(String::from(" "), foreground, BACKGROUND));
+            segments.push(Segment::Text(
+                format!("{:02X}", byte),
+                foreground,
+                background,
+            ));
+        }
+        segments.push(Segment::Text(String::from("  "), ADDRESS_COLOR, BACKGROUND));
+        for &(byte, highlight) in &self.bytes {
+            let (foreground, background) = highlight.colors();
+            let c = if byte.is_ascii_graphic() || byte == b' ' {
+                byte as char
+            } else {
+                '.'
+            };
+            segments.push(Segment::Text(c.to_string(), foreground, background));
+        }
+        segments.push(Segment::Text(String::from("  "), ADDRESS_COLOR, BACKGROUND));
+        for &(byte, highlight) in &self.bytes {
+            let (foreground, background) = highlight.colors();
+            segments.push(Segment::Bitmap(byte, foreground, background));
+        }
+        segments
+    }
+}
+
+/// What the memory viewer shows, handed to the frontend whenever it is open.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MemoryView {
+    pub rows: Vec<MemoryRow>,
+    /// Whether typing hex digits edits memory, which is only allowed while the emulator is paused.
+    pub editable: bool,
+}
+
+impl MemoryView {
+    /// The line shown above the rows.
+    pub fn title(&self) -> &'static str {
+        if self.editable {
+            "Memory: arrows move, hex digits edit"
+        } else {
+            "Memory: pause to edit"
+        }
+    }
+}
+
+/// A hex viewer of memory that follows the game as it runs, and lets bytes be changed while the emulator is paused.
+/// It covers all 4 KiB of memory.
+pub struct MemoryViewer {
+    open: bool,
+    cursor: u16,
+    /// The address of the first row shown.
+    top: u16,
+    /// Memory at the end of the previous frame.
+    previous: Vec<u8>,
+    /// The bytes that changed during the last frame.
+    changed: Vec<bool>,
+    /// The high nibble typed for the byte at the cursor, waiting for the low nibble.
+    pending: Option<u8>,
+}
+
+impl MemoryViewer {
+    /// A closed viewer with the cursor at the start of the ROM.
+    pub fn new() -> Self {
+        MemoryViewer {
+            open: false,
+            cursor: ROM_START,
+            top: ROM_START,
+            previous: Vec::new(),
+            changed: Vec::new(),
+            pending: None,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Opens or closes the viewer. Changes are only tracked while it is open, so a viewer that was just opened shows
+    /// none.
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.previous.clear();
+        self.changed.clear();
+        self.pending = None;
+    }
+
+    /// Notes which bytes changed during the frame that just ended.
+    pub fn end_frame(&mut self, emu: &Chip8) {
+        let memory = emu.memory();
+        self.changed = memory
+            .iter()
+            .enumerate()
+            .map(|(addr, &byte)| {
+                self.previous
+                    .get(addr)
+                    .is_some_and(|&before| before != byte)
+            })
+            .collect();
+        self.previous = memory.to_vec();
+    }
+
+    /// Moves the cursor by a number of bytes, stopping at either end of memory, and scrolls to keep it in view.
+    pub fn move_cursor(&mut self, emu: &Chip8, bytes: isize) {
+        let last = emu.memory().len() - 1;
+        self.cursor = (self.cursor as isize + bytes).clamp(0, last as isize) as u16;
+        self.pending = None;
+
+        let row = |addr: u16| addr - addr % BYTES_PER_ROW as u16;
+        let visible = (VISIBLE_ROWS * BYTES_PER_ROW) as u16;
+        if self.cursor < self.top {
+            self.top = row(self.cursor);
+        } else if self.cursor >= self.top + visible {
+            self.top = row(self.cursor) + BYTES_PER_ROW as u16 - visible;
+        }
+    }
+
+    /// Types a hex digit into the byte at the cursor. The first digit of a byte is held until the second is typed,
+    /// then the byte is written and the cursor moves on. Returns the address and value of a byte that was written.
+    pub fn edit(&mut self, emu: &mut Chip8, digit: u8) -> Option<(u16, u8)> {
+        match self.pending.take() {
+            None => {
+                self.pending = Some(digit & 0xF);
+                None
+            }
+            Some(high) => {
+                let (addr, value) = (self.cursor, high << 4 | digit & 0xF);
+                emu.write_memory(addr, value);
+                // The edit isn't a change made by the game.
+                if let Some(byte) = self.previous.get_mut(addr as usize) {
+                    *byte = value;
+                }
+                self.move_cursor(emu, 1);
+                Some((addr, value))
+            }
+        }
+    }
+
+    /// What the viewer shows of the machine.
+    pub fn view(&self, emu: &Chip8, editable: bool) -> MemoryView {
+        let memory = emu.memory();
+        let pc = emu.program_counter();
+        let rom_end = ROM_START as usize + emu.rom().len();
+        let highlight = |addr: u16| {
+            let changed = self.changed.get(addr as usize) == Some(&true);
+            if editable && addr == self.cursor {
+                Highlight::Cursor
+            } else if addr == pc || addr == pc.wrapping_add(1) {
+                Highlight::ProgramCounter
+            } else if addr == emu.index() {
+                Highlight::Index
+            } else if emu.call_stack().iter().any(|call| call.from == addr) {
+                Highlight::StackTarget
+            } else if changed {
+                Highlight::Changed
+            } else if FONT.contains(&addr) {
+                Highlight::Font
+            } else if (ROM_START as usize..rom_end).contains(&(addr as usize)) {
+                Highlight::Rom
+            } else {
+                Highlight::Plain
+            }
+        };
+
+        let rows = (0..VISIBLE_ROWS)
+            .map(|row| self.top as usize + row * BYTES_PER_ROW)
+            .filter(|&address| address < memory.len())
+            .map(|address| MemoryRow {
+                address: address as u16,
+                bytes: (address..(address + BYTES_PER_ROW).min(memory.len()))
+                    .map(|addr| (memory[addr], highlight(addr as u16)))
+                    .collect(),
+            })
+            .collect();
+        MemoryView { rows, editable }
+    }
+}
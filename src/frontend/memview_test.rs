@@ -0,0 +1,52 @@
+#[cfg(test)]
+mod memory_viewer_test {
+    use crate::chip8::Chip8;
+    use crate::frontend::memview::{Highlight, MemoryViewer, BYTES_PER_ROW, VISIBLE_ROWS};
+
+    #[test]
+    fn memview_highlight_test() {
+        let mut emu = Chip8::new();
+        emu.load_rom_bytes(&[0xA2, 0x0C, 0x00, 0xE0]).unwrap();
+        emu.emulate_cycle().unwrap();
+        let mut viewer = MemoryViewer::new();
+        viewer.toggle();
+        viewer.end_frame(&emu);
+        emu.write_memory(0x208, 0x42);
+        viewer.end_frame(&emu);
+
+        let view = viewer.view(&emu, false);
+        assert_eq!(view.rows.len(), VISIBLE_ROWS);
+        assert_eq!(view.rows[0].address, 0x200);
+        let highlights: Vec<Highlight> = view.rows[0].bytes.iter().map(|&(_, h)| h).collect();
+        assert_eq!(
+            highlights[..4],
+            [
+                Highlight::Rom,
+                Highlight::Rom,
+                Highlight::ProgramCounter,
+                Highlight::ProgramCounter
+            ]
+        );
+        assert_eq!(view.rows[1].bytes[0], (0x42, Highlight::Changed));
+        assert_eq!(view.rows[1].bytes[4].1, Highlight::Index);
+    }
+
+    #[test]
+    fn memview_edit_test() {
+        let mut emu = Chip8::new();
+        let mut viewer = MemoryViewer::new();
+        viewer.toggle();
+        viewer.move_cursor(&emu, 0x100);
+
+        assert_eq!(viewer.edit(&mut emu, 0xA), None);
+        assert_eq!(viewer.edit(&mut emu, 0x5), Some((0x300, 0xA5)));
+        assert_eq!(emu.read_memory(0x300), 0xA5);
+
+        // The cursor moved on, and the view scrolled to keep it in sight.
+        let view = viewer.view(&emu, true);
+        let top = view.rows[0].address as usize;
+        assert!((top..top + VISIBLE_ROWS * BYTES_PER_ROW).contains(&0x301));
+        let row = &view.rows[(0x301 - top) / BYTES_PER_ROW];
+        assert_eq!(row.bytes[1].1, Highlight::Cursor);
+    }
+}
@@ -0,0 +1,710 @@
+pub mod cheats;
+mod cheats_test;
+pub mod coverage;
+mod coverage_test;
+pub mod filter;
+mod filter_test;
+#[cfg(any(feature = "sdl", test))]
+mod font;
+pub mod headless;
+mod keymap;
+#[cfg(any(feature = "sdl", test))]
+mod layout;
+mod layout_test;
+pub mod memview;
+mod memview_test;
+#[cfg(any(feature = "sdl", test))]
+mod osd;
+mod osd_test;
+mod palette;
+mod palette_test;
+pub mod playback;
+mod playback_test;
+pub mod profiler;
+mod profiler_test;
+pub mod recording;
+mod recording_test;
+mod renderer;
+mod renderer_test;
+pub mod replay;
+mod replay_test;
+pub mod screenshot;
+mod screenshot_test;
+#[cfg(feature = "sdl")]
+pub mod sdl;
+mod session_test;
+pub mod sprites;
+mod sprites_test;
+pub mod terminal;
+mod terminal_test;
+pub mod watch;
+mod watch_test;
+
+pub use keymap::KeyMap;
+pub use palette::{Palette, Rgb};
+pub use renderer::{Frame, Persistence, Renderer};
+
+use crate::chip8::input::Input;
+use crate::chip8::Chip8;
+use cheats::Cheats;
+use coverage::Coverage;
+use filter::Filters;
+use memview::{MemoryView, MemoryViewer};
+use playback::{Playback, Step};
+use profiler::Profiler;
+use recording::VideoRecorder;
+use replay::{InputRecorder, InputReplay};
+use sprites::SpriteRipper;
+use std::path::PathBuf;
+use std::thread;
+use std::time::SystemTime;
+use std::time::{Duration, Instant};
+use watch::RomWatcher;
+
+const FRAME_DURATION: Duration = Duration::from_micros(1_000_000 / 60);
+
+/// Actions that the user can trigger with hotkeys while the emulator is running.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Hotkey {
+    /// Switches to the next built-in palette.
+    CyclePalette,
+    /// Saves a screenshot of the current frame.
+    Screenshot,
+    /// Starts recording a GIF, or stops the recording in progress.
+    ToggleRecording,
+    /// Pauses or resumes the emulator.
+    TogglePause,
+    /// Runs a single frame, pausing the emulator first if needed.
+    AdvanceFrame,
+    /// Runs a single instruction, pausing the emulator first if needed.
+    AdvanceInstruction,
+    /// Turns fast-forward on or off.
+    ToggleFastForward,
+    /// Turns slow motion on or off.
+    ToggleSlowMotion,
+    /// Shows or hides the frame and instruction counter.
+    ToggleCounter,
+    /// Shows or hides the keypad.
+    ToggleKeypad,
+    /// Starts the ROM again from the beginning.
+    Reset,
+    /// Turns the cheat with the given index on or off.
+    ToggleCheat(usize),
+    /// Shows or hides the memory viewer.
+    ToggleMemoryViewer,
+    /// Moves the cursor of the memory viewer by a number of bytes.
+    MoveMemoryCursor(isize),
+    /// Types a hex digit into the byte under the cursor of the memory viewer.
+    EditMemory(u8),
+}
+
+/// Settings shared by all frontends that can be changed while the emulator is running.
+#[derive(Clone, Debug, Default)]
+pub struct Settings {
+    pub palette: Palette,
+    pub persistence: Persistence,
+    /// Shows how many frames and instructions are emulated each second.
+    pub show_counter: bool,
+    /// Shows the keypad with the keys that are pressed highlighted.
+    pub show_keypad: bool,
+    /// Keeps the buzzer quiet.
+    pub mute: bool,
+}
+
+/// What the overlay shows on top of the game, besides messages. Updated every frame.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OverlayInfo {
+    /// The number of frames and instructions emulated in the last second, when the counter is shown.
+    pub counter: Option<(u32, u32)>,
+    /// The state of the keypad, when the keypad is shown.
+    pub keypad: Option<u16>,
+}
+
+/// Options for a run of the emulator that stay the same while it is running.
+pub struct RunOptions {
+    /// The name of the ROM being run, used to name screenshots.
+    pub rom_name: String,
+    /// The number of instructions run in each frame.
+    pub cycles_per_frame: usize,
+    /// Runs frames as fast as possible rather than at 60 frames per second.
+    pub uncapped: bool,
+    /// Stops the emulator after this many frames.
+    pub frame_limit: Option<u64>,
+    /// The directory screenshots are saved in.
+    pub screenshot_dir: PathBuf,
+    /// The size of each CHIP-8 pixel in saved screenshots.
+    pub screenshot_scale: u32,
+    /// Saves a screenshot once this many frames have been run.
+    pub screenshot_at_frame: Option<u64>,
+    /// Records a video from the first frame.
+    pub video_recorder: Option<VideoRecorder>,
+    /// The size of each CHIP-8 pixel in video recordings.
+    pub recording_scale: u32,
+    /// The effects applied to screenshots and recordings.
+    pub filters: Filters,
+    /// Records the keypad state on every frame.
+    pub input_recorder: Option<InputRecorder>,
+    /// Plays back a recorded keypad state in place of the user's input.
+    pub input_replay: Option<InputReplay>,
+    /// Whether the emulator starts paused and the speeds used for fast-forward and slow motion.
+    pub playback: Playback,
+    /// Loads the ROM again whenever its file changes.
+    pub rom_watcher: Option<RomWatcher>,
+    /// The cheats written into memory on every frame.
+    pub cheats: Cheats,
+    /// Saves the sprites the game draws into this file when the emulator stops.
+    pub rip_sprites: Option<PathBuf>,
+    /// Saves a profile of where the game spent its time into this file when the emulator stops.
+    pub profile: Option<PathBuf>,
+    /// Saves a map of the bytes the game ran, read and wrote into this file when the emulator stops.
+    pub coverage: Option<PathBuf>,
+    /// Ends the run with an error when the machine stops on an instruction it can't run, rather than pausing so that
+    /// the user can look at what went wrong.
+    pub stop_on_error: bool,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        RunOptions {
+            rom_name: String::from("rom"),
+            cycles_per_frame: 10,
+            uncapped: false,
+            frame_limit: None,
+            screenshot_dir: PathBuf::from("."),
+            screenshot_scale: 1,
+            screenshot_at_frame: None,
+            video_recorder: None,
+            recording_scale: 4,
+            filters: Filters::default(),
+            input_recorder: None,
+            input_replay: None,
+            playback: Playback::default(),
+            rom_watcher: None,
+            cheats: Cheats::default(),
+            rip_sprites: None,
+            profile: None,
+            coverage: None,
+            stop_on_error: false,
+        }
+    }
+}
+
+/// A frontend presents the CHIP-8 framebuffer to the user and feeds their key presses back into the emulator.
+pub trait Frontend {
+    /// Handles any pending events, updating the keypad state in `input`. Returns the hotkeys that were pressed.
+    fn poll_input(&mut self, input: &mut Input) -> Vec<Hotkey>;
+
+    /// Presents a rendered frame.
+    fn draw(&mut self, frame: &Frame);
+
+    /// Whether the frontend has to be redrawn even though the framebuffer hasn't changed (e.g. after a resize).
+    fn needs_redraw(&self) -> bool {
+        false
+    }
+
+    /// Tells the user about something that happened, such as a screenshot being saved.
+    fn show_message(&mut self, message: &str) {
+        eprintln!("{}", message);
+    }
+
+    /// Shows a short status, such as "Paused", until it is replaced. An empty status hides the indicator.
+    fn show_status(&mut self, _status: &str) {}
+
+    /// Updates the counter and keypad drawn over the game, for frontends that have an overlay.
+    fn update_overlay(&mut self, _info: &OverlayInfo) {}
+
+    /// Starts or stops the buzzer, for frontends that can make a sound.
+    fn set_tone(&mut self, _on: bool) {}
+
+    /// Shows the memory viewer, or hides it when there is no view, for frontends that can show it. Called on every
+    /// iteration of the run loop, so frontends should only redraw it when the view changed.
+    fn show_memory(&mut self, _view: Option<&MemoryView>) {}
+}
+
+/// Saves a screenshot of the frame, telling the user where it went.
+fn take_screenshot(frame: &Frame, frontend: &mut dyn Frontend, options: &RunOptions) {
+    let image = options
+        .filters
+        .apply(frame, options.screenshot_scale as usize);
+    let result = screenshot::save(&image, &options.screenshot_dir, &options.rom_name, 1);
+    match result {
+        Ok(path) => frontend.show_message(&format!("Screenshot saved to {}", path.display())),
+        Err(err) => frontend.show_message(&format!("Could not save screenshot: {}", err)),
+    }
+}
+
+/// Starts a GIF recording with an automatically numbered filename, or stops the recording in progress.
+fn toggle_recording(
+    recorder: &mut Option<VideoRecorder>,
+    frontend: &mut dyn Frontend,
+    options: &RunOptions,
+) {
+    match recorder.take() {
+        Some(recording) => finish_recording(recording, frontend),
+        None => {
+            let path = screenshot::next_path(
+                &options.screenshot_dir,
+                &options.rom_name,
+                "gif",
+                SystemTime::now(),
+            );
+            match VideoRecorder::create(&path, options.recording_scale, options.filters.clone()) {
+                Ok(recording) => {
+                    frontend.show_message(&format!("Recording to {}", path.display()));
+                    *recorder = Some(recording);
+                }
+                Err(err) => frontend.show_message(&err),
+            }
+        }
+    }
+}
+
+fn finish_recording(recorder: VideoRecorder, frontend: &mut dyn Frontend) {
+    let path = recorder.path().to_path_buf();
+    match recorder.finish() {
+        Ok(()) => frontend.show_message(&format!("Recording saved to {}", path.display())),
+        Err(err) => frontend.show_message(&format!("Could not save recording: {}", err)),
+    }
+}
+
+/// Measures how many frames and instructions are emulated each second.
+struct RateCounter {
+    since: Instant,
+    frames: u64,
+    instructions: u64,
+    rates: (u32, u32),
+}
+
+impl RateCounter {
+    fn new(now: Instant) -> Self {
+        RateCounter {
+            since: now,
+            frames: 0,
+            instructions: 0,
+            rates: (0, 0),
+        }
+    }
+
+    /// Takes the total number of frames and instructions emulated so far, and returns the rates measured over the last
+    /// full second.
+    fn update(&mut self, now: Instant, frames: u64, instructions: u64) -> (u32, u32) {
+        let elapsed = now - self.since;
+        if elapsed >= Duration::from_secs(1) {
+            let per_second = |count: u64| (count as f64 / elapsed.as_secs_f64()).round() as u32;
+            self.rates = (
+                per_second(frames - self.frames),
+                per_second(instructions - self.instructions),
+            );
+            self.since = now;
+            self.frames = frames;
+            self.instructions = instructions;
+        }
+        self.rates
+    }
+}
+
+/// The state of a run that changes as frames are emulated.
+struct Session {
+    options: RunOptions,
+    renderer: Renderer,
+    video_recorder: Option<VideoRecorder>,
+    input_recorder: Option<InputRecorder>,
+    input_replay: Option<InputReplay>,
+    rom_watcher: Option<RomWatcher>,
+    cheats: Cheats,
+    memory_viewer: MemoryViewer,
+    /// Collects the sprites the game draws, and the file they are saved in.
+    sprite_ripper: Option<(SpriteRipper, PathBuf)>,
+    /// Counts where the game spends its time, and the file the profile is saved in.
+    profiler: Option<(Profiler, PathBuf)>,
+    /// Tracks how the game uses memory, and the file the coverage is saved in.
+    coverage: Option<(Coverage, PathBuf)>,
+    /// The number of frames emulated so far.
+    frame_count: u64,
+    /// The number of instructions run so far.
+    instructions: u64,
+    /// The number of instructions of the current frame that have already been run.
+    cycle: usize,
+    /// Whether the rendered frame changed since it was last drawn.
+    changed: bool,
+    /// Why the machine stopped, if it came to an instruction it couldn't run that hasn't been reported yet.
+    error: Option<String>,
+}
+
+impl Session {
+    fn new(mut options: RunOptions) -> Self {
+        Session {
+            renderer: Renderer::new(),
+            video_recorder: options.video_recorder.take(),
+            input_recorder: options.input_recorder.take(),
+            input_replay: options.input_replay.take(),
+            rom_watcher: options.rom_watcher.take(),
+            cheats: std::mem::take(&mut options.cheats),
+            memory_viewer: MemoryViewer::new(),
+            sprite_ripper: options
+                .rip_sprites
+                .take()
+                .map(|path| (SpriteRipper::default(), path)),
+            profiler: options
+                .profile
+                .take()
+                .map(|path| (Profiler::default(), path)),
+            coverage: options
+                .coverage
+                .take()
+                .map(|path| (Coverage::default(), path)),
+            options,
+            frame_count: 0,
+            instructions: 0,
+            cycle: 0,
+            changed: false,
+            error: None,
+        }
+    }
+
+    /// Whether the frame limit has been reached, or the machine has stopped on an error that hasn't been reported.
+    fn is_finished(&self) -> bool {
+        self.options.frame_limit == Some(self.frame_count) || self.error.is_some()
+    }
+
+    /// Runs the rest of the current frame.
+    fn run_frame(&mut self, emu: &mut Chip8, frontend: &mut dyn Frontend, settings: &Settings) {
+        while !self.run_instruction(emu, frontend, settings, false) {}
+    }
+
+    /// Runs the next instruction. Returns whether it was the last instruction of the frame, or the machine stopped
+    /// because the instruction couldn't be run. `stepping` is set when the user is stepping through a frame one
+    /// instruction at a time, so that the effect of each is shown.
+    fn run_instruction(
+        &mut self,
+        emu: &mut Chip8,
+        frontend: &mut dyn Frontend,
+        settings: &Settings,
+        stepping: bool,
+    ) -> bool {
+        if self.cycle == 0 {
+            self.start_frame(emu, frontend);
+        }
+
+        if let Some((ripper, _)) = &mut self.sprite_ripper {
+            ripper.observe(emu);
+        }
+        if let Some((profiler, _)) = &mut self.profiler {
+            profiler.observe(emu);
+        }
+        if let Some((coverage, _)) = &mut self.coverage {
+            coverage.observe(emu);
+        }
+        if let Err(err) = emu.emulate_cycle() {
+            self.error = Some(format!("Stopped at {:03X}: {}", emu.program_counter(), err));
+            return true;
+        }
+        self.instructions += 1;
+        self.cycle += 1;
+        if self.cycle < self.options.cycles_per_frame {
+            // Show the effect of single instructions while stepping through a frame. Otherwise the frame is only
+            // rendered once it ends, as persistence blends and fades whole frames rather than single draws.
+            if stepping && emu.display.should_draw() {
+                self.render(emu, settings);
+            }
+            return false;
+        }
+
+        self.cycle = 0;
+        emu.update_timers();
+        self.frame_count += 1;
+        self.end_frame(emu, frontend, settings);
+        true
+    }
+
+    /// Sets the keypad and applies the cheats for the frame that is about to start.
+    fn start_frame(&mut self, emu: &mut Chip8, frontend: &mut dyn Frontend) {
+        self.cheats.apply(emu);
+        if let Some(replay) = &mut self.input_replay {
+            emu.input.set_state(replay.state_at(self.frame_count));
+        }
+        if let Some(recorder) = &mut self.input_recorder {
+            if let Err(err) = recorder.record(self.frame_count, emu.input.get_state()) {
+                frontend.show_message(&format!("Could not record input: {}", err));
+                self.input_recorder = None;
+            }
+        }
+    }
+
+    /// Whether the keypad is being recorded or replayed. Resets and reloads aren't part of a recording, so they are
+    /// refused while it is, as the replay would no longer match the game.
+    fn records_or_replays_input(&self) -> bool {
+        self.input_recorder.is_some() || self.input_replay.is_some()
+    }
+
+    /// Loads the ROM again if its file has changed.
+    fn reload_if_changed(&mut self, emu: &mut Chip8, frontend: &mut dyn Frontend) {
+        let refused = self.records_or_replays_input();
+        let watcher = match &mut self.rom_watcher {
+            Some(watcher) => watcher,
+            None => return,
+        };
+        let path = watcher.path().display().to_string();
+        let result = match watcher.poll(Instant::now()) {
+            Some(_) if refused => {
+                frontend.show_message(&format!(
+                    "Not reloading {} while input is recorded or replayed",
+                    path
+                ));
+                return;
+            }
+            Some(result) => result.and_then(|rom| emu.load_rom_bytes(&rom)),
+            None => return,
+        };
+        match result {
+            Ok(()) => {
+                // The frame that was in progress is abandoned.
+                self.cycle = 0;
+                frontend.show_message(&format!("Reloaded {}", path));
+            }
+            Err(err) => frontend.show_message(&format!("Could not reload {}: {}", path, err)),
+        }
+    }
+
+    /// Renders the frame that was just emulated, saving it in any screenshot or recording that was asked for.
+    fn end_frame(&mut self, emu: &mut Chip8, frontend: &mut dyn Frontend, settings: &Settings) {
+        if emu.display.should_draw() || !self.renderer.is_settled() {
+            self.render(emu, settings);
+        }
+        if self.options.screenshot_at_frame == Some(self.frame_count) {
+            take_screenshot(self.renderer.frame(), frontend, &self.options);
+        }
+        if let Some(recorder) = &mut self.video_recorder {
+            if let Err(err) = recorder.add_frame(self.renderer.frame()) {
+                frontend.show_message(&format!("Could not record video: {}", err));
+                self.video_recorder = None;
+            }
+        }
+        if self.memory_viewer.is_open() {
+            self.memory_viewer.end_frame(emu);
+        }
+        if let Some((profiler, _)) = &mut self.profiler {
+            profiler.end_frame();
+        }
+    }
+
+    fn render(&mut self, emu: &mut Chip8, settings: &Settings) {
+        let dirty = emu.display.take_dirty();
+        self.changed |= self.renderer.update(&emu.display, dirty, settings);
+    }
+
+    /// Finishes any recordings that are still in progress, and saves the sprites that were ripped, the profile and the
+    /// coverage.
+    fn finish(self, emu: &Chip8, frontend: &mut dyn Frontend) {
+        if let Some((ripper, path)) = self.sprite_ripper {
+            match ripper.save(&path) {
+                Ok(()) => frontend.show_message(&format!(
+                    "{} sprites saved to {}",
+                    ripper.len(),
+                    path.display()
+                )),
+                Err(err) => frontend.show_message(&err),
+            }
+        }
+        if let Some((profiler, path)) = self.profiler {
+            match profiler.save(&path) {
+                Ok(folded) => frontend.show_message(&format!(
+                    "Profile saved to {} and {}",
+                    path.display(),
+                    folded.display()
+                )),
+                Err(err) => frontend.show_message(&err),
+            }
+        }
+        if let Some((coverage, path)) = self.coverage {
+            match coverage.save(&path, emu.rom()) {
+                Ok(image) => frontend.show_message(&format!(
+                    "Coverage saved to {} and {}",
+                    path.display(),
+                    image.display()
+                )),
+                Err(err) => frontend.show_message(&err),
+            }
+        }
+        if let Some(recorder) = self.video_recorder {
+            finish_recording(recorder, frontend);
+        }
+        if let Some(recorder) = self.input_recorder {
+            if let Err(err) = recorder.finish(self.frame_count) {
+                frontend.show_message(&format!("Could not record input: {}", err));
+            }
+        }
+    }
+}
+
+/// Runs the emulator until the user quits or the frame limit is reached, presenting 60 frames per second. Any settings
+/// changed with hotkeys are left in `settings`.
+///
+/// When the machine comes to an instruction it can't run, such as a call that overflows the stack, the error is shown
+/// and the emulator pauses, or the run fails with the error if `stop_on_error` is set.
+pub fn run(
+    emu: &mut Chip8,
+    frontend: &mut dyn Frontend,
+    settings: &mut Settings,
+    mut options: RunOptions,
+) -> Result<(), String> {
+    let mut playback = std::mem::take(&mut options.playback);
+    let mut session = Session::new(options);
+    let mut status = String::new();
+    let mut tone = false;
+    let mut rate_counter = RateCounter::new(Instant::now());
+    let mut next_frame = Instant::now();
+    let mut result = Ok(());
+
+    loop {
+        let mut redraw = false;
+        let mut screenshot = false;
+        // While input is replayed, the keyboard only controls the emulator, not the keypad.
+        let mut live_input = Input::new();
+        let input = match session.input_replay {
+            Some(_) => &mut live_input,
+            None => &mut emu.input,
+        };
+        for hotkey in frontend.poll_input(input) {
+            match hotkey {
+                Hotkey::CyclePalette => {
+                    settings.palette = settings.palette.next();
+                    redraw = true;
+                }
+                Hotkey::Screenshot => screenshot = true,
+                Hotkey::ToggleRecording => {
+                    toggle_recording(&mut session.video_recorder, frontend, &session.options)
+                }
+                Hotkey::TogglePause => playback.toggle_pause(),
+                Hotkey::AdvanceFrame => playback.advance_frame(),
+                Hotkey::AdvanceInstruction => playback.advance_instruction(),
+                Hotkey::ToggleFastForward => playback.toggle_fast_forward(),
+                Hotkey::ToggleSlowMotion => playback.toggle_slow_motion(),
+                Hotkey::ToggleCounter => settings.show_counter = !settings.show_counter,
+                Hotkey::ToggleKeypad => settings.show_keypad = !settings.show_keypad,
+                Hotkey::ToggleCheat(index) => match session.cheats.toggle(index) {
+                    Some(cheat) => frontend.show_message(&format!(
+                        "Cheat {} ({}) {}",
+                        index + 1,
+                        cheat.name,
+                        if cheat.enabled { "on" } else { "off" }
+                    )),
+                    None => frontend.show_message(&format!("There is no cheat {}", index + 1)),
+                },
+                Hotkey::Reset if session.records_or_replays_input() => {
+                    frontend.show_message("Can't reset while input is recorded or replayed")
+                }
+                Hotkey::Reset => {
+                    emu.reset();
+                    session.cycle = 0;
+                    frontend.show_message("Reset");
+                }
+                Hotkey::ToggleMemoryViewer => session.memory_viewer.toggle(),
+                Hotkey::MoveMemoryCursor(bytes) => session.memory_viewer.move_cursor(emu, bytes),
+                // Memory can only be changed while paused, so the game doesn't overwrite an edit before it is seen.
+                Hotkey::EditMemory(digit) if playback.is_paused() => {
+                    if let Some((addr, value)) = session.memory_viewer.edit(emu, digit) {
+                        frontend.show_message(&format!("Wrote {:02X} to {:03X}", value, addr));
+                    }
+                }
+                Hotkey::EditMemory(_) => {}
+            }
+        }
+        if live_input.should_quit() {
+            emu.input.quit();
+        }
+        session.reload_if_changed(emu, frontend);
+        if emu.input.should_quit() || session.is_finished() {
+            break;
+        }
+        if playback.status() != status {
+            status = playback.status();
+            frontend.show_status(&status);
+        }
+
+        next_frame += FRAME_DURATION;
+        let frames_before = session.frame_count;
+        match playback.take_step() {
+            Some(Step::Frame) => session.run_frame(emu, frontend, settings),
+            Some(Step::Instruction) => {
+                session.run_instruction(emu, frontend, settings, true);
+            }
+            None => match playback.frames_due() {
+                Some(frames) => {
+                    for _ in 0..frames {
+                        if session.is_finished() {
+                            break;
+                        }
+                        session.run_frame(emu, frontend, settings);
+                    }
+                }
+                // Keep emulating until the next frame has to be presented, so that hotkeys still work.
+                None => loop {
+                    session.run_frame(emu, frontend, settings);
+                    if session.is_finished() || Instant::now() >= next_frame {
+                        break;
+                    }
+                },
+            },
+        }
+        if let Some(err) = session.error.take() {
+            if session.options.stop_on_error {
+                result = Err(err);
+                break;
+            }
+            // Messages are a single line, so the calls that led up to a stack error are left out.
+            frontend.show_message(err.lines().next().unwrap_or_default());
+            playback.pause();
+        }
+
+        // The buzzer only sounds while the game is running, so it goes quiet while paused.
+        let sounding =
+            !settings.mute && emu.is_sound_playing() && session.frame_count != frames_before;
+        if sounding != tone {
+            tone = sounding;
+            frontend.set_tone(tone);
+        }
+
+        // Frames emulated since the palette changed were already rendered with it.
+        if redraw && session.frame_count == frames_before {
+            session.render(emu, settings);
+        }
+        let counter =
+            rate_counter.update(Instant::now(), session.frame_count, session.instructions);
+        frontend.update_overlay(&OverlayInfo {
+            counter: settings.show_counter.then_some(counter),
+            keypad: settings.show_keypad.then(|| emu.input.get_state()),
+        });
+        if session.changed || frontend.needs_redraw() {
+            frontend.draw(session.renderer.frame());
+            session.changed = false;
+        }
+        if screenshot {
+            take_screenshot(session.renderer.frame(), frontend, &session.options);
+        }
+        let memory_view = session
+            .memory_viewer
+            .is_open()
+            .then(|| session.memory_viewer.view(emu, playback.is_paused()));
+        frontend.show_memory(memory_view.as_ref());
+
+        if session.options.uncapped {
+            continue;
+        }
+
+        // Sleep until the next frame is due. If we've fallen behind, start counting again from now rather than
+        // rushing through the missed frames.
+        let now = Instant::now();
+        if next_frame > now {
+            thread::sleep(next_frame - now);
+        } else {
+            next_frame = now;
+        }
+    }
+
+    if tone {
+        frontend.set_tone(false);
+    }
+    session.finish(emu, frontend);
+    result
+}
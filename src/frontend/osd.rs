@@ -0,0 +1,190 @@
+use super::font::{self, GLYPH_HEIGHT, GLYPH_WIDTH};
+use super::keymap::KEYPAD_LAYOUT;
+use super::{Frame, OverlayInfo, Rgb};
+use std::time::{Duration, Instant};
+
+/// How long messages stay on screen.
+const MESSAGE_DURATION: Duration = Duration::from_secs(3);
+/// The most messages shown at once. Older messages are dropped to make room for new ones.
+const MAX_MESSAGES: usize = 3;
+
+const TEXT_COLOR: Rgb = Rgb(0xFF, 0xFF, 0xFF);
+const BOX_COLOR: Rgb = Rgb(0x20, 0x20, 0x20);
+const KEY_COLOR: Rgb = Rgb(0x40, 0x40, 0x40);
+const PRESSED_KEY_COLOR: Rgb = Rgb(0xFF, 0xCC, 0x00);
+
+/// A surface the overlay can be drawn on.
+pub trait Canvas {
+    /// The size of the canvas in pixels, as (width, height).
+    fn size(&self) -> (u32, u32);
+
+    fn fill_rect(&mut self, x: u32, y: u32, width: u32, height: u32, color: Rgb);
+}
+
+impl Canvas for Frame {
+    fn size(&self) -> (u32, u32) {
+        (self.width as u32, self.height as u32)
+    }
+
+    fn fill_rect(&mut self, x: u32, y: u32, width: u32, height: u32, color: Rgb) {
+        let right = (x + width).min(self.width as u32);
+        let bottom = (y + height).min(self.height as u32);
+        for y in y..bottom {
+            for x in x..right {
+                self.set(x as usize, y as usize, color);
+            }
+        }
+    }
+}
+
+/// An on-screen display drawn on top of the game: messages that disappear after a few seconds, a status in the top
+/// right corner, and optionally a frame counter and the state of the keypad. It is drawn at the resolution of the
+/// window, so text stays sharp at any scale.
+pub struct Osd {
+    /// The messages being shown and when they disappear, oldest first.
+    messages: Vec<(String, Instant)>,
+    status: String,
+    info: OverlayInfo,
+    /// Whether anything changed since the overlay was last drawn.
+    dirty: bool,
+}
+
+impl Osd {
+    pub fn new() -> Self {
+        Osd {
+            messages: Vec::new(),
+            status: String::new(),
+            info: OverlayInfo::default(),
+            dirty: false,
+        }
+    }
+
+    pub fn show_message(&mut self, message: &str, now: Instant) {
+        if self.messages.len() == MAX_MESSAGES {
+            self.messages.remove(0);
+        }
+        self.messages
+            .push((message.to_string(), now + MESSAGE_DURATION));
+        self.dirty = true;
+    }
+
+    pub fn set_status(&mut self, status: &str) {
+        if self.status != status {
+            self.status = status.to_string();
+            self.dirty = true;
+        }
+    }
+
+    pub fn set_info(&mut self, info: &OverlayInfo) {
+        if self.info != *info {
+            self.info = info.clone();
+            self.dirty = true;
+        }
+    }
+
+    /// Whether the overlay looks different to when it was last drawn.
+    pub fn needs_redraw(&self, now: Instant) -> bool {
+        self.dirty || self.messages.iter().any(|(_, expiry)| *expiry <= now)
+    }
+
+    /// Draws the overlay, sizing everything to suit the size of the canvas.
+    pub fn draw(&mut self, canvas: &mut dyn Canvas, now: Instant) {
+        self.messages.retain(|(_, expiry)| *expiry > now);
+        self.dirty = false;
+
+        let (width, height) = canvas.size();
+        let scale = (height / 160).max(1);
+        let margin = 2 * scale;
+
+        if let Some((frames, instructions)) = self.info.counter {
+            let counter = format!("{} FPS {} IPS", frames, instructions);
+            draw_label(canvas, &counter, margin, margin, scale);
+        }
+        if !self.status.is_empty() {
+            let x = width.saturating_sub(label_size(&self.status, scale).0 + margin);
+            draw_label(canvas, &self.status, x, margin, scale);
+        }
+
+        let mut y = height;
+        for (message, _) in self.messages.iter().rev() {
+            y = y.saturating_sub(label_size(message, scale).1 + margin);
+            draw_label(canvas, message, margin, y, scale);
+        }
+
+        if let Some(state) = self.info.keypad {
+            draw_keypad(canvas, state, scale);
+        }
+    }
+}
+
+/// The size of a label, including the padding around its text.
+fn label_size(text: &str, scale: u32) -> (u32, u32) {
+    let padding = 2 * scale;
+    (
+        text_width(text, scale) + 2 * padding,
+        GLYPH_HEIGHT * scale + 2 * padding,
+    )
+}
+
+/// The width of a line of text with a pixel between each glyph.
+fn text_width(text: &str, scale: u32) -> u32 {
+    let glyphs = text.chars().count() as u32;
+    (glyphs * (GLYPH_WIDTH + 1)).saturating_sub(1) * scale
+}
+
+/// Draws text on a box, so that it can be read on top of any game.
+fn draw_label(canvas: &mut dyn Canvas, text: &str, x: u32, y: u32, scale: u32) {
+    let (width, height) = label_size(text, scale);
+    canvas.fill_rect(x, y, width, height, BOX_COLOR);
+    draw_text(
+        canvas,
+        text,
+        x + 2 * scale,
+        y + 2 * scale,
+        scale,
+        TEXT_COLOR,
+    );
+}
+
+/// Draws text with its top left corner at (x, y), with each pixel of the font drawn as a `scale` sized square.
+pub fn draw_text(canvas: &mut dyn Canvas, text: &str, x: u32, y: u32, scale: u32, color: Rgb) {
+    for (i, c) in text.chars().enumerate() {
+        let left = x + i as u32 * (GLYPH_WIDTH + 1) * scale;
+        for (row, bits) in font::glyph(c).iter().enumerate() {
+            for column in 0..GLYPH_WIDTH {
+                if bits & (0x10 >> column) != 0 {
+                    let (px, py) = (left + column * scale, y + row as u32 * scale);
+                    canvas.fill_rect(px, py, scale, scale, color);
+                }
+            }
+        }
+    }
+}
+
+/// Draws the keypad in the bottom right corner, highlighting the keys that are pressed.
+fn draw_keypad(canvas: &mut dyn Canvas, state: u16, scale: u32) {
+    let (width, height) = canvas.size();
+    let key_size = (GLYPH_HEIGHT + 4) * scale;
+    let gap = scale;
+    let keypad_size = 4 * key_size + 3 * gap;
+    let left = width.saturating_sub(keypad_size + 2 * scale);
+    let top = height.saturating_sub(keypad_size + 2 * scale);
+
+    for (row, keys) in KEYPAD_LAYOUT.iter().enumerate() {
+        for (column, &key) in keys.iter().enumerate() {
+            let x = left + column as u32 * (key_size + gap);
+            let y = top + row as u32 * (key_size + gap);
+            let pressed = state & (1 << key) != 0;
+            let (background, foreground) = if pressed {
+                (PRESSED_KEY_COLOR, BOX_COLOR)
+            } else {
+                (KEY_COLOR, TEXT_COLOR)
+            };
+
+            canvas.fill_rect(x, y, key_size, key_size, background);
+            let label = format!("{:X}", key);
+            let text_x = x + (key_size - GLYPH_WIDTH * scale) / 2;
+            draw_text(canvas, &label, text_x, y + 2 * scale, scale, foreground);
+        }
+    }
+}
@@ -0,0 +1,74 @@
+#[cfg(test)]
+mod osd_overlay_test {
+    use crate::frontend::osd::Osd;
+    use crate::frontend::{Frame, OverlayInfo, Rgb};
+    use std::time::{Duration, Instant};
+
+    const BLACK: Rgb = Rgb(0, 0, 0);
+
+    fn lit_pixels(frame: &Frame, x: usize, y: usize, width: usize, height: usize) -> usize {
+        (y..y + height)
+            .flat_map(|y| (x..x + width).map(move |x| (x, y)))
+            .filter(|&(x, y)| frame.get(x, y) != BLACK)
+            .count()
+    }
+
+    #[test]
+    fn message_expires_test() {
+        let now = Instant::now();
+        let mut osd = Osd::new();
+        assert!(!osd.needs_redraw(now));
+
+        osd.show_message("State saved to slot 2", now);
+        assert!(osd.needs_redraw(now));
+
+        let mut frame = Frame::new(320, 160, BLACK);
+        osd.draw(&mut frame, now);
+        assert!(!osd.needs_redraw(now));
+        assert!(lit_pixels(&frame, 0, 140, 160, 20) > 0);
+
+        let later = now + Duration::from_secs(5);
+        assert!(osd.needs_redraw(later));
+        let mut frame = Frame::new(320, 160, BLACK);
+        osd.draw(&mut frame, later);
+        assert_eq!(lit_pixels(&frame, 0, 0, 320, 160), 0);
+    }
+
+    #[test]
+    fn counter_and_keypad_test() {
+        let now = Instant::now();
+        let mut osd = Osd::new();
+        let info = OverlayInfo {
+            counter: Some((60, 600)),
+            keypad: Some(0),
+        };
+        osd.set_info(&info);
+        osd.set_status("Paused");
+        let mut frame = Frame::new(320, 160, BLACK);
+        osd.draw(&mut frame, now);
+        assert!(lit_pixels(&frame, 0, 0, 100, 15) > 0);
+        assert!(lit_pixels(&frame, 280, 0, 40, 15) > 0);
+
+        // Setting the same info again doesn't need a redraw.
+        osd.set_info(&info);
+        assert!(!osd.needs_redraw(now));
+
+        // Pressing key 1 highlights the top left key of the keypad.
+        let keypad = |frame: &Frame| {
+            (0..frame.height)
+                .flat_map(|y| (0..frame.width).map(move |x| (x, y)))
+                .filter(|&(x, y)| frame.get(x, y) == Rgb(0xFF, 0xCC, 0x00))
+                .count()
+        };
+        assert_eq!(keypad(&frame), 0);
+        osd.set_info(&OverlayInfo {
+            counter: None,
+            keypad: Some(1 << 0x1),
+        });
+        assert!(osd.needs_redraw(now));
+        let mut frame = Frame::new(320, 160, BLACK);
+        osd.draw(&mut frame, now);
+        assert!(keypad(&frame) > 0);
+        assert_eq!(frame.get(272, 112), Rgb(0xFF, 0xCC, 0x00));
+    }
+}
@@ -0,0 +1,158 @@
+use std::str::FromStr;
+
+/// A colour made of red, green and blue components.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl FromStr for Rgb {
+    type Err = String;
+
+    /// Parses a colour written as a hex code, with or without a leading `#` (e.g. `#FFCC00`).
+    fn from_str(hex: &str) -> Result<Self, Self::Err> {
+        let digits = hex.trim_start_matches('#');
+        let value = if digits.len() == 6 {
+            u32::from_str_radix(digits, 16).ok()
+        } else {
+            None
+        };
+
+        match value {
+            Some(value) => Ok(Rgb((value >> 16) as u8, (value >> 8) as u8, value as u8)),
+            None => Err(format!(
+                "'{}' is not a colour, expected a hex code like #FFCC00",
+                hex
+            )),
+        }
+    }
+}
+
+/// The colours used to draw the screen. Colours are indexed by the value of a pixel: index 0 is used for pixels that
+/// are off and index 1 for pixels that are on. On displays with two bit planes, index 2 is used for pixels only set in
+/// the second plane and index 3 for pixels set in both.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Palette {
+    pub name: String,
+    pub colors: [Rgb; 4],
+}
+
+/// The palettes that come with the emulator, in the order they are cycled through.
+const BUILT_IN_PALETTES: [(&str, [Rgb; 4]); 5] = [
+    (
+        "classic",
+        [
+            Rgb(0x00, 0x00, 0x00),
+            Rgb(0xFF, 0xFF, 0xFF),
+            Rgb(0xAA, 0xAA, 0xAA),
+            Rgb(0x55, 0x55, 0x55),
+        ],
+    ),
+    (
+        "green",
+        [
+            Rgb(0x0A, 0x1A, 0x0A),
+            Rgb(0x33, 0xFF, 0x66),
+            Rgb(0x1A, 0x80, 0x33),
+            Rgb(0x99, 0xFF, 0xB3),
+        ],
+    ),
+    (
+        "amber",
+        [
+            Rgb(0x1A, 0x0F, 0x00),
+            Rgb(0xFF, 0xB0, 0x00),
+            Rgb(0x80, 0x58, 0x00),
+            Rgb(0xFF, 0xD7, 0x80),
+        ],
+    ),
+    (
+        "octo",
+        [
+            Rgb(0x99, 0x66, 0x00),
+            Rgb(0xFF, 0xCC, 0x00),
+            Rgb(0xFF, 0x66, 0x00),
+            Rgb(0x66, 0x22, 0x00),
+        ],
+    ),
+    (
+        "lcd",
+        [
+            Rgb(0x87, 0x9C, 0x7A),
+            Rgb(0x1C, 0x2A, 0x1C),
+            Rgb(0x4F, 0x5F, 0x4A),
+            Rgb(0x00, 0x00, 0x00),
+        ],
+    ),
+];
+
+impl Palette {
+    /// Returns the built-in palette with the given name.
+    pub fn built_in(name: &str) -> Option<Palette> {
+        BUILT_IN_PALETTES
+            .iter()
+            .find(|(built_in, _)| *built_in == name)
+            .map(|(name, colors)| Palette {
+                name: name.to_string(),
+                colors: *colors,
+            })
+    }
+
+    /// The names of all the built-in palettes.
+    pub fn built_in_names() -> Vec<&'static str> {
+        BUILT_IN_PALETTES.iter().map(|(name, _)| *name).collect()
+    }
+
+    /// Returns the built-in palette that comes after this one, wrapping around at the end. Custom palettes are followed
+    /// by the first built-in palette.
+    pub fn next(&self) -> Palette {
+        let index = BUILT_IN_PALETTES
+            .iter()
+            .position(|(name, _)| *name == self.name)
+            .map_or(0, |index| (index + 1) % BUILT_IN_PALETTES.len());
+        let (name, colors) = BUILT_IN_PALETTES[index];
+        Palette {
+            name: name.to_string(),
+            colors,
+        }
+    }
+
+    /// Returns a copy of this palette with the off and on colours replaced.
+    pub fn with_colors(&self, background: Option<Rgb>, foreground: Option<Rgb>) -> Palette {
+        let mut colors = self.colors;
+        colors[0] = background.unwrap_or(colors[0]);
+        colors[1] = foreground.unwrap_or(colors[1]);
+
+        Palette {
+            name: if colors == self.colors {
+                self.name.clone()
+            } else {
+                String::from("custom")
+            },
+            colors,
+        }
+    }
+
+    /// Returns the colour used to draw a pixel with the given value.
+    pub fn color(&self, pixel: u8) -> Rgb {
+        self.colors[pixel as usize & 0b11]
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::built_in("classic").unwrap()
+    }
+}
+
+impl FromStr for Palette {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Palette::built_in(name).ok_or_else(|| {
+            format!(
+                "Unknown palette '{}', expected one of: {}",
+                name,
+                Palette::built_in_names().join(", ")
+            )
+        })
+    }
+}
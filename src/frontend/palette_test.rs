@@ -0,0 +1,36 @@
+#[cfg(test)]
+mod palette_parse_test {
+    use crate::frontend::{Palette, Rgb};
+
+    #[test]
+    fn parse_rgb_test() {
+        assert_eq!("#FFCC00".parse(), Ok(Rgb(0xFF, 0xCC, 0x00)));
+        assert_eq!("0a1b2c".parse(), Ok(Rgb(0x0A, 0x1B, 0x2C)));
+        assert!("#FFF".parse::<Rgb>().is_err());
+        assert!("#GGGGGG".parse::<Rgb>().is_err());
+    }
+
+    #[test]
+    fn cycle_palettes_test() {
+        let mut palette = Palette::default();
+        let mut names = vec![palette.name.clone()];
+        for _ in 0..Palette::built_in_names().len() {
+            palette = palette.next();
+            names.push(palette.name.clone());
+        }
+
+        assert_eq!(
+            names,
+            vec!["classic", "green", "amber", "octo", "lcd", "classic"]
+        );
+    }
+
+    #[test]
+    fn custom_colors_test() {
+        let palette = Palette::default().with_colors(Some(Rgb(1, 2, 3)), None);
+        assert_eq!(palette.name, "custom");
+        assert_eq!(palette.color(0), Rgb(1, 2, 3));
+        assert_eq!(palette.color(1), Rgb(0xFF, 0xFF, 0xFF));
+        assert_eq!(palette.next().name, "classic");
+    }
+}
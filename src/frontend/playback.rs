@@ -0,0 +1,179 @@
+use std::str::FromStr;
+
+/// How fast frames are emulated compared to a real CHIP-8 running at 60 frames per second.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Speed {
+    /// Emulates this many frames for every frame that is presented (e.g. 0.25 for quarter speed).
+    Multiplier(f64),
+    /// Emulates as many frames as possible in the time of each presented frame.
+    Uncapped,
+}
+
+impl FromStr for Speed {
+    type Err = String;
+
+    /// Parses a multiplier such as `4` or `0.5`, or `uncapped`.
+    fn from_str(speed: &str) -> Result<Self, Self::Err> {
+        match speed {
+            "uncapped" => Ok(Speed::Uncapped),
+            _ => match speed.parse::<f64>() {
+                Ok(multiplier) if multiplier > 0.0 && multiplier.is_finite() => {
+                    Ok(Speed::Multiplier(multiplier))
+                }
+                _ => Err(format!(
+                    "Invalid speed '{}', expected a multiplier above 0 or 'uncapped'",
+                    speed
+                )),
+            },
+        }
+    }
+}
+
+/// A single step taken while the emulator is paused.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Step {
+    /// Runs the rest of the current frame, including the timer update at its end.
+    Frame,
+    /// Runs a single instruction.
+    Instruction,
+}
+
+/// Controls whether the emulator is paused and how fast it runs. Speed only changes how many frames are emulated for
+/// each frame that is presented, so the timers always tick once per emulated frame and stay in step with the game.
+#[derive(Clone, Debug)]
+pub struct Playback {
+    paused: bool,
+    fast_forward: bool,
+    slow_motion: bool,
+    /// The speed used while fast-forwarding.
+    pub fast_forward_speed: Speed,
+    /// The speed used in slow motion.
+    pub slow_motion_speed: Speed,
+    step: Option<Step>,
+    /// The fraction of a frame that is owed from previous calls to `frames_due`, when running at speeds that aren't
+    /// whole numbers.
+    owed: f64,
+}
+
+impl Playback {
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.step = None;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn toggle_pause(&mut self) {
+        if self.paused {
+            self.resume();
+        } else {
+            self.pause();
+        }
+    }
+
+    /// Runs the rest of the current frame, pausing the emulator first if it is running.
+    pub fn advance_frame(&mut self) {
+        self.pause();
+        self.step = Some(Step::Frame);
+    }
+
+    /// Runs the next instruction, pausing the emulator first if it is running.
+    pub fn advance_instruction(&mut self) {
+        self.pause();
+        self.step = Some(Step::Instruction);
+    }
+
+    /// Turns fast-forward on or off. Turning it on ends slow motion.
+    pub fn set_fast_forward(&mut self, fast_forward: bool) {
+        self.fast_forward = fast_forward;
+        self.slow_motion &= !fast_forward;
+    }
+
+    pub fn toggle_fast_forward(&mut self) {
+        self.set_fast_forward(!self.fast_forward);
+    }
+
+    /// Turns slow motion on or off. Turning it on ends fast-forward.
+    pub fn set_slow_motion(&mut self, slow_motion: bool) {
+        self.slow_motion = slow_motion;
+        self.fast_forward &= !slow_motion;
+    }
+
+    pub fn toggle_slow_motion(&mut self) {
+        self.set_slow_motion(!self.slow_motion);
+    }
+
+    /// The speed the emulator currently runs at when it isn't paused.
+    pub fn speed(&self) -> Speed {
+        if self.fast_forward {
+            self.fast_forward_speed
+        } else if self.slow_motion {
+            self.slow_motion_speed
+        } else {
+            Speed::Multiplier(1.0)
+        }
+    }
+
+    /// Returns the step the user asked for while paused, if there is one.
+    pub fn take_step(&mut self) -> Option<Step> {
+        self.step.take()
+    }
+
+    /// Returns the number of frames to emulate before presenting the next frame, or `None` if as many frames as
+    /// possible should be emulated. Should be called once for every frame that is presented.
+    pub fn frames_due(&mut self) -> Option<u32> {
+        if self.paused {
+            return Some(0);
+        }
+
+        match self.speed() {
+            Speed::Multiplier(multiplier) => {
+                self.owed += multiplier;
+                let frames = self.owed.floor();
+                self.owed -= frames;
+                Some(frames as u32)
+            }
+            Speed::Uncapped => None,
+        }
+    }
+
+    /// A short description of the playback state for the on-screen indicator, or an empty string when the emulator is
+    /// running at normal speed.
+    pub fn status(&self) -> String {
+        if self.paused {
+            return String::from("Paused");
+        }
+
+        let label = if self.fast_forward {
+            "Fast forward"
+        } else if self.slow_motion {
+            "Slow motion"
+        } else {
+            return String::new();
+        };
+        match self.speed() {
+            Speed::Multiplier(multiplier) => format!("{} x{}", label, multiplier),
+            Speed::Uncapped => format!("{} (uncapped)", label),
+        }
+    }
+}
+
+impl Default for Playback {
+    fn default() -> Self {
+        Playback {
+            paused: false,
+            fast_forward: false,
+            slow_motion: false,
+            fast_forward_speed: Speed::Multiplier(4.0),
+            slow_motion_speed: Speed::Multiplier(0.25),
+            step: None,
+            owed: 0.0,
+        }
+    }
+}
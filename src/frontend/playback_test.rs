@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod playback_speed_test {
+    use crate::frontend::playback::{Playback, Speed, Step};
+
+    #[test]
+    fn parse_speed_test() {
+        assert_eq!("4".parse::<Speed>(), Ok(Speed::Multiplier(4.0)));
+        assert_eq!("0.5".parse::<Speed>(), Ok(Speed::Multiplier(0.5)));
+        assert_eq!("uncapped".parse::<Speed>(), Ok(Speed::Uncapped));
+        assert!("0".parse::<Speed>().is_err());
+        assert!("-2".parse::<Speed>().is_err());
+        assert!("fast".parse::<Speed>().is_err());
+    }
+
+    #[test]
+    fn frames_due_test() {
+        let mut playback = Playback::default();
+        assert_eq!(playback.frames_due(), Some(1));
+        assert_eq!(playback.status(), "");
+
+        playback.toggle_fast_forward();
+        assert_eq!(playback.frames_due(), Some(4));
+        assert_eq!(playback.status(), "Fast forward x4");
+
+        playback.toggle_slow_motion();
+        let frames: Vec<_> = (0..8).map(|_| playback.frames_due()).collect();
+        assert_eq!(frames.iter().flatten().sum::<u32>(), 2);
+        assert_eq!(playback.status(), "Slow motion x0.25");
+
+        playback.slow_motion_speed = Speed::Uncapped;
+        assert_eq!(playback.frames_due(), None);
+    }
+
+    #[test]
+    fn pause_and_step_test() {
+        let mut playback = Playback::default();
+        playback.toggle_fast_forward();
+        playback.pause();
+        assert_eq!(playback.frames_due(), Some(0));
+        assert_eq!(playback.status(), "Paused");
+        assert_eq!(playback.take_step(), None);
+
+        playback.advance_instruction();
+        assert_eq!(playback.take_step(), Some(Step::Instruction));
+        assert_eq!(playback.take_step(), None);
+
+        playback.resume();
+        playback.advance_frame();
+        assert_eq!(playback.status(), "Paused");
+        assert_eq!(playback.take_step(), Some(Step::Frame));
+
+        playback.toggle_pause();
+        assert_eq!(playback.frames_due(), Some(4));
+    }
+}
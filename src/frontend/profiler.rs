@@ -0,0 +1,226 @@
+use crate::chip8::instructions::Instruction;
+use crate::chip8::Chip8;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The number of addresses listed in the hot spots of a report.
+const HOT_SPOT_LIMIT: usize = 20;
+
+/// How often a subroutine was called and how many instructions were run in it.
+#[derive(Clone, Copy, Debug, Default)]
+struct SubroutineProfile {
+    calls: u64,
+    /// The instructions run in the subroutine and everything it called.
+    total: u64,
+    /// The instructions run in the subroutine itself.
+    own: u64,
+}
+
+/// Counts where a game spends its time, by looking at each instruction before it runs: how often each address and
+/// each kind of instruction runs, how many instructions each subroutine takes, and how many frames are spent waiting
+/// for a key or for the delay timer.
+#[derive(Default)]
+pub struct Profiler {
+    instructions: u64,
+    frames: u64,
+    addresses: BTreeMap<u16, (u64, Instruction)>,
+    kinds: BTreeMap<String, u64>,
+    subroutines: BTreeMap<u16, SubroutineProfile>,
+    /// The names of the subroutines that were called, from the ROM's symbols or else their addresses.
+    names: BTreeMap<u16, String>,
+    /// The subroutines that are running, outermost first, mirroring the return addresses on the machine's stack.
+    calls: Vec<u16>,
+    /// The number of instructions run in each chain of calls, keyed by the chain in the folded format flame graph
+    /// tools read.
+    stacks: BTreeMap<String, u64>,
+    /// The frames in which the game waited for a key with FX0A.
+    key_wait_frames: u64,
+    /// The frames in which the game read the delay timer over and over from the same place, waiting for it to run out.
+    timer_wait_frames: u64,
+    /// Whether FX0A was left waiting during the current frame.
+    waiting_for_key: bool,
+    /// The addresses the delay timer was read from during the current frame.
+    timer_reads: Vec<u16>,
+    waiting_for_timer: bool,
+}
+
+impl Profiler {
+    /// Counts the instruction about to run.
+    pub fn observe(&mut self, emu: &Chip8) {
+        let pc = emu.program_counter();
+        let instruction = match emu.next_instruction() {
+            Ok(instruction) => instruction,
+            Err(_) => return,
+        };
+
+        // A reset or a reload empties the machine's stack without any returns.
+        self.calls.truncate(emu.call_stack().len());
+
+        self.instructions += 1;
+        self.addresses.entry(pc).or_insert((0, instruction)).0 += 1;
+        *self.kinds.entry(kind(instruction)).or_default() += 1;
+        *self.stacks.entry(self.stack_name()).or_default() += 1;
+        for (depth, &addr) in self.calls.iter().enumerate() {
+            // Recursive subroutines are only counted once.
+            if !self.calls[..depth].contains(&addr) {
+                self.subroutines.entry(addr).or_default().total += 1;
+            }
+        }
+        if let Some(&addr) = self.calls.last() {
+            self.subroutines.entry(addr).or_default().own += 1;
+        }
+
+        match instruction {
+            Instruction::FlowCall(addr) => {
+                self.subroutines.entry(addr).or_default().calls += 1;
+                self.names.entry(addr).or_insert_with(|| {
+                    emu.symbols()
+                        .name(addr)
+                        .map_or_else(|| format!("sub_{:03X}", addr), String::from)
+                });
+                self.calls.push(addr);
+            }
+            Instruction::FlowReturn => {
+                self.calls.pop();
+            }
+            Instruction::KeyOpGetKey(_) if emu.input.get_pressed().is_none() => {
+                self.waiting_for_key = true;
+            }
+            // The delay timer only changes between frames, so reading it twice from the same place in one frame means
+            // the game is going round a loop until it runs out.
+            Instruction::DelayTimerSaveVx(_) if emu.timers().0 > 0 => {
+                if self.timer_reads.contains(&pc) {
+                    self.waiting_for_timer = true;
+                } else {
+                    self.timer_reads.push(pc);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Notes what the game was waiting for during the frame that just ended.
+    pub fn end_frame(&mut self) {
+        self.frames += 1;
+        if self.waiting_for_key {
+            self.key_wait_frames += 1;
+        } else if self.waiting_for_timer {
+            self.timer_wait_frames += 1;
+        }
+        self.waiting_for_key = false;
+        self.waiting_for_timer = false;
+        self.timer_reads.clear();
+    }
+
+    /// The chain of calls that is running, as `main;sub_2A0;sub_31C`.
+    fn stack_name(&self) -> String {
+        let mut name = String::from("main");
+        for &addr in &self.calls {
+            let _ = write!(name, ";{}", self.names[&addr]);
+        }
+        name
+    }
+
+    /// A report of the hot spots, the kinds of instructions run, the subroutines and the frames spent waiting.
+    pub fn report(&self) -> String {
+        let percent = |count: u64, total: u64| count as f64 * 100.0 / total.max(1) as f64;
+        let mut report = format!(
+            "{} instructions over {} frames\n",
+            self.instructions, self.frames
+        );
+
+        report.push_str("\nHot spots\n");
+        let mut addresses: Vec<_> = self.addresses.iter().collect();
+        addresses.sort_by_key(|&(_, &(count, _))| Reverse(count));
+        for (addr, (count, instruction)) in addresses.into_iter().take(HOT_SPOT_LIMIT) {
+            let _ = writeln!(
+                report,
+                "  {:03X}  {:>10}  {:5.1}%  {}",
+                addr,
+                count,
+                percent(*count, self.instructions),
+                instruction
+            );
+        }
+
+        report.push_str("\nInstructions\n");
+        let mut kinds: Vec<_> = self.kinds.iter().collect();
+        kinds.sort_by_key(|&(_, &count)| Reverse(count));
+        for (kind, count) in kinds {
+            let _ = writeln!(
+                report,
+                "  {:<20}  {:>10}  {:5.1}%",
+                kind,
+                count,
+                percent(*count, self.instructions)
+            );
+        }
+
+        report.push_str("\nSubroutines\n");
+        let mut subroutines: Vec<_> = self.subroutines.iter().collect();
+        subroutines.sort_by_key(|(_, profile)| Reverse(profile.total));
+        let width = self.names.values().map(String::len).max().unwrap_or(0);
+        let _ = writeln!(
+            report,
+            "  {:<width$}  {:>8}  {:>12}  {:>12}  {:>11}",
+            "", "calls", "instructions", "in itself", "per call"
+        );
+        for (&addr, profile) in subroutines {
+            let _ = writeln!(
+                report,
+                "  {:<width$}  {:>8}  {:>12}  {:>12}  {:>11.1}",
+                self.names[&addr],
+                profile.calls,
+                profile.total,
+                profile.own,
+                profile.total as f64 / profile.calls.max(1) as f64
+            );
+        }
+
+        report.push_str("\nWaiting\n");
+        let _ = writeln!(
+            report,
+            "  for a key (FX0A)        {:>8} frames  {:5.1}%",
+            self.key_wait_frames,
+            percent(self.key_wait_frames, self.frames)
+        );
+        let _ = writeln!(
+            report,
+            "  for the delay timer     {:>8} frames  {:5.1}%",
+            self.timer_wait_frames,
+            percent(self.timer_wait_frames, self.frames)
+        );
+        report
+    }
+
+    /// The instructions run in each chain of calls, one chain per line, as flame graph tools such as inferno and
+    /// flamegraph.pl read them.
+    pub fn folded_stacks(&self) -> String {
+        self.stacks
+            .iter()
+            .map(|(stack, count)| format!("{} {}\n", stack, count))
+            .collect()
+    }
+
+    /// Saves the report at `path`, and the folded stacks next to it with the extension `.folded`. Returns the path of
+    /// the folded stacks.
+    pub fn save(&self, path: &Path) -> Result<PathBuf, String> {
+        let folded = path.with_extension("folded");
+        fs::write(path, self.report())
+            .and_then(|()| fs::write(&folded, self.folded_stacks()))
+            .map_err(|err| format!("Could not save the profile: {}", err))?;
+        Ok(folded)
+    }
+}
+
+/// The kind of an instruction, which is the name of its variant without the operands.
+fn kind(instruction: Instruction) -> String {
+    let name = format!("{:?}", instruction);
+    match name.find('(') {
+        Some(end) => name[..end].to_string(),
+        None => name,
+    }
+}
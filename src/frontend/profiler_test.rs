@@ -0,0 +1,44 @@
+#[cfg(test)]
+mod profile_report_test {
+    use crate::chip8::Chip8;
+    use crate::frontend::profiler::Profiler;
+
+    /// Calls a subroutine that waits for the delay timer to run out, then loops forever.
+    const ROM: &[u8] = &[
+        0x60, 0x05, // V0 = 5
+        0xF0, 0x15, // Delay timer = V0
+        0x22, 0x08, // Call 0x208
+        0x12, 0x06, // Loop forever
+        0xF1, 0x07, // 0x208: V1 = delay timer
+        0x31, 0x00, // Skip if V1 == 0
+        0x12, 0x08, // Jump back to 0x208
+        0x00, 0xEE, // Return
+    ];
+
+    #[test]
+    fn profiler_report_test() {
+        let mut emu = Chip8::new();
+        emu.load_rom_bytes(ROM).unwrap();
+        let mut profiler = Profiler::default();
+        for _ in 0..10 {
+            for _ in 0..10 {
+                profiler.observe(&emu);
+                emu.emulate_cycle().unwrap();
+            }
+            emu.update_timers();
+            profiler.end_frame();
+        }
+
+        let report = profiler.report();
+        assert!(report.starts_with("100 instructions over 10 frames\n"));
+        assert!(report.contains("\n  206          "));
+        assert!(report.contains("  sub_208         1"));
+        assert!(report.contains("for the delay timer            5 frames"));
+
+        let folded = profiler.folded_stacks();
+        let lines: Vec<&str> = folded.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("main "));
+        assert!(lines[1].starts_with("main;sub_208 "));
+    }
+}
@@ -0,0 +1,250 @@
+use super::filter::Filters;
+use super::{Frame, Rgb};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// GIF delays are in centiseconds, and most viewers slow down frames shorter than 2 centiseconds. Frames that would be
+/// shown for less than this are dropped instead.
+const GIF_MIN_DELAY: u64 = 2;
+
+/// The formats gameplay can be recorded in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VideoFormat {
+    /// An animated GIF. Identical frames are merged to keep files small.
+    Gif,
+    /// An uncompressed YUV4MPEG2 stream with 4:4:4 chroma, which most video encoders can read directly.
+    Y4m,
+    /// Uncompressed 24-bit RGB frames with no header, e.g. for `ffmpeg -f rawvideo -pix_fmt rgb24 -r 60`.
+    RawRgb,
+}
+
+impl VideoFormat {
+    /// Picks the format from the extension of a path. `-` means a Y4M stream written to stdout.
+    pub fn from_path(path: &Path) -> Result<Self, String> {
+        if path == Path::new("-") {
+            return Ok(VideoFormat::Y4m);
+        }
+
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("gif") => Ok(VideoFormat::Gif),
+            Some("y4m") => Ok(VideoFormat::Y4m),
+            Some("rgb") | Some("raw") => Ok(VideoFormat::RawRgb),
+            _ => Err(format!(
+                "Can't tell the video format of {}, expected a .gif, .y4m, .rgb or .raw file",
+                path.display()
+            )),
+        }
+    }
+}
+
+/// Writes frames into a GIF file. The GIF is only started when the first frame is written, since its size isn't known
+/// before then.
+struct GifWriter {
+    file: Option<BufWriter<File>>,
+    encoder: Option<gif::Encoder<BufWriter<File>>>,
+}
+
+impl GifWriter {
+    fn write_frame(&mut self, frame: &gif::Frame) -> io::Result<()> {
+        if let Some(file) = self.file.take() {
+            let mut encoder = gif::Encoder::new(file, frame.width, frame.height, &[])
+                .map_err(io::Error::other)?;
+            encoder
+                .set_repeat(gif::Repeat::Infinite)
+                .map_err(io::Error::other)?;
+            self.encoder = Some(encoder);
+        }
+
+        match &mut self.encoder {
+            Some(encoder) => encoder.write_frame(frame).map_err(io::Error::other),
+            None => Ok(()),
+        }
+    }
+
+    fn finish(self) -> io::Result<()> {
+        match (self.encoder, self.file) {
+            (Some(encoder), _) => encoder.into_inner()?.flush(),
+            (None, Some(mut file)) => file.flush(),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+enum Encoder {
+    Gif {
+        writer: GifWriter,
+        /// The last frame that was added but not yet written, and the frame number it was first shown on. Frames are
+        /// held back until a different frame arrives, since that decides how long they are shown for.
+        pending: Option<(Frame, u64)>,
+    },
+    Y4m(Box<dyn Write>),
+    RawRgb(Box<dyn Write>),
+}
+
+/// Records every frame shown while the emulator runs into a video file.
+pub struct VideoRecorder {
+    encoder: Encoder,
+    path: PathBuf,
+    scale: usize,
+    filters: Filters,
+    frame_count: u64,
+}
+
+impl VideoRecorder {
+    /// Starts a recording at `path`, picking the format from its extension. Each pixel is recorded as a `scale` sized
+    /// square, with `filters` applied on top.
+    pub fn create(path: &Path, scale: u32, filters: Filters) -> Result<Self, String> {
+        let format = VideoFormat::from_path(path)?;
+        let output = || -> io::Result<Box<dyn Write>> {
+            if path == Path::new("-") {
+                Ok(Box::new(BufWriter::new(io::stdout())))
+            } else {
+                Ok(Box::new(BufWriter::new(File::create(path)?)))
+            }
+        };
+        let error = |err: io::Error| format!("Could not create {}: {}", path.display(), err);
+
+        let encoder = match format {
+            VideoFormat::Gif => {
+                let file = BufWriter::new(File::create(path).map_err(error)?);
+                Encoder::Gif {
+                    writer: GifWriter {
+                        file: Some(file),
+                        encoder: None,
+                    },
+                    pending: None,
+                }
+            }
+            VideoFormat::Y4m => Encoder::Y4m(output().map_err(error)?),
+            VideoFormat::RawRgb => Encoder::RawRgb(output().map_err(error)?),
+        };
+
+        Ok(VideoRecorder {
+            encoder,
+            path: path.to_path_buf(),
+            scale: scale.max(1) as usize,
+            filters,
+            frame_count: 0,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Adds the frame shown for the next 1/60th of a second to the recording.
+    pub fn add_frame(&mut self, frame: &Frame) -> io::Result<()> {
+        let (width, height) = (frame.width * self.scale, frame.height * self.scale);
+
+        match &mut self.encoder {
+            Encoder::Gif { writer, pending } => match pending {
+                Some((pending_frame, _)) if pending_frame == frame => {}
+                Some((pending_frame, start))
+                    if centiseconds(self.frame_count) - centiseconds(*start) < GIF_MIN_DELAY =>
+                {
+                    *pending_frame = frame.clone();
+                }
+                _ => {
+                    if let Some((pending_frame, start)) = pending.take() {
+                        let image = self.filters.apply(&pending_frame, self.scale);
+                        write_gif_frame(writer, &image, start, self.frame_count)?;
+                    }
+                    *pending = Some((frame.clone(), self.frame_count));
+                }
+            },
+            Encoder::Y4m(output) => {
+                if self.frame_count == 0 {
+                    writeln!(
+                        output,
+                        "YUV4MPEG2 W{} H{} F60:1 Ip A1:1 C444",
+                        width, height
+                    )?;
+                }
+                output.write_all(b"FRAME\n")?;
+                let image = self.filters.apply(frame, self.scale);
+                output.write_all(&to_yuv444(&image.to_rgb_bytes(1)))?;
+            }
+            Encoder::RawRgb(output) => {
+                let image = self.filters.apply(frame, self.scale);
+                output.write_all(&image.to_rgb_bytes(1))?
+            }
+        }
+
+        self.frame_count += 1;
+        Ok(())
+    }
+
+    /// Writes out anything that is still buffered and closes the recording.
+    pub fn finish(self) -> io::Result<()> {
+        match self.encoder {
+            Encoder::Gif {
+                mut writer,
+                pending,
+            } => {
+                if let Some((frame, start)) = pending {
+                    let end = self.frame_count.max(start + 1);
+                    let image = self.filters.apply(&frame, self.scale);
+                    write_gif_frame(&mut writer, &image, start, end)?;
+                }
+                writer.finish()
+            }
+            Encoder::Y4m(mut output) | Encoder::RawRgb(mut output) => output.flush(),
+        }
+    }
+}
+
+/// The time at which a frame starts, in centiseconds, rounded to the nearest centisecond.
+fn centiseconds(frame: u64) -> u64 {
+    (frame * 100 + 30) / 60
+}
+
+/// Writes an image that is shown from frame number `start` until `end`.
+fn write_gif_frame(writer: &mut GifWriter, image: &Frame, start: u64, end: u64) -> io::Result<()> {
+    let (width, height) = (image.width as u16, image.height as u16);
+    let rgb = image.to_rgb_bytes(1);
+
+    // Frames almost always use only a handful of colours, so they can be written with an exact palette. Quantizing is
+    // only needed when persistence effects produce more colours than a GIF palette can hold.
+    let mut palette: HashMap<Rgb, u8> = HashMap::new();
+    let mut indices = Vec::with_capacity(rgb.len() / 3);
+    for pixel in rgb.chunks(3) {
+        let color = Rgb(pixel[0], pixel[1], pixel[2]);
+        let next_index = palette.len();
+        if next_index > 255 && !palette.contains_key(&color) {
+            break;
+        }
+        indices.push(*palette.entry(color).or_insert(next_index as u8));
+    }
+
+    let mut gif_frame = if indices.len() == rgb.len() / 3 {
+        let mut colors = vec![0; palette.len() * 3];
+        for (Rgb(r, g, b), index) in palette {
+            colors[index as usize * 3..index as usize * 3 + 3].copy_from_slice(&[r, g, b]);
+        }
+        gif::Frame::from_palette_pixels(width, height, indices, colors, None)
+    } else {
+        gif::Frame::from_rgb_speed(width, height, &rgb, 10)
+    };
+
+    gif_frame.delay = (centiseconds(end) - centiseconds(start)).max(1) as u16;
+    writer.write_frame(&gif_frame)
+}
+
+/// Converts packed RGB bytes into the three planes of a 4:4:4 Y'CbCr image, using the BT.601 studio range that Y4M
+/// readers expect.
+fn to_yuv444(rgb: &[u8]) -> Vec<u8> {
+    let pixels = rgb.len() / 3;
+    let mut planes = vec![0; pixels * 3];
+
+    for (i, pixel) in rgb.chunks(3).enumerate() {
+        let (r, g, b) = (pixel[0] as f32, pixel[1] as f32, pixel[2] as f32);
+        planes[i] = (16.0 + (65.738 * r + 129.057 * g + 25.064 * b) / 256.0).round() as u8;
+        planes[pixels + i] =
+            (128.0 + (-37.945 * r - 74.494 * g + 112.439 * b) / 256.0).round() as u8;
+        planes[pixels * 2 + i] =
+            (128.0 + (112.439 * r - 94.154 * g - 18.285 * b) / 256.0).round() as u8;
+    }
+    planes
+}
@@ -0,0 +1,77 @@
+#[cfg(test)]
+mod recording_video_test {
+    use crate::frontend::filter::Filters;
+    use crate::frontend::recording::{VideoFormat, VideoRecorder};
+    use crate::frontend::{Frame, Rgb};
+    use std::fs::{self, File};
+    use std::path::Path;
+
+    fn frame(lit: usize) -> Frame {
+        let mut frame = Frame::new(64, 32, Rgb(0, 0, 0));
+        frame.set(lit, 0, Rgb(0xFF, 0xFF, 0xFF));
+        frame
+    }
+
+    #[test]
+    fn video_format_test() {
+        assert_eq!(
+            VideoFormat::from_path(Path::new("a.gif")),
+            Ok(VideoFormat::Gif)
+        );
+        assert_eq!(
+            VideoFormat::from_path(Path::new("a.y4m")),
+            Ok(VideoFormat::Y4m)
+        );
+        assert_eq!(
+            VideoFormat::from_path(Path::new("a.rgb")),
+            Ok(VideoFormat::RawRgb)
+        );
+        assert_eq!(VideoFormat::from_path(Path::new("-")), Ok(VideoFormat::Y4m));
+        assert!(VideoFormat::from_path(Path::new("a.mp4")).is_err());
+    }
+
+    #[test]
+    fn gif_merges_identical_frames_test() {
+        let path = std::env::temp_dir().join("rusty-chip-merge-frames-test.gif");
+        let mut recorder = VideoRecorder::create(&path, 2, Filters::default()).unwrap();
+        for _ in 0..30 {
+            recorder.add_frame(&frame(0)).unwrap();
+        }
+        for _ in 0..90 {
+            recorder.add_frame(&frame(2)).unwrap();
+        }
+        recorder.finish().unwrap();
+
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::RGBA);
+        let mut decoder = options.read_info(File::open(&path).unwrap()).unwrap();
+        let mut frames = Vec::new();
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            frames.push((frame.delay, frame.buffer[4 * 4..4 * 5].to_vec()));
+        }
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            frames,
+            vec![
+                (50, vec![0, 0, 0, 0xFF]),
+                (150, vec![0xFF, 0xFF, 0xFF, 0xFF])
+            ]
+        );
+    }
+
+    #[test]
+    fn raw_rgb_test() {
+        let path = std::env::temp_dir().join("rusty-chip-raw-rgb-test.rgb");
+        let mut recorder = VideoRecorder::create(&path, 1, Filters::default()).unwrap();
+        recorder.add_frame(&frame(0)).unwrap();
+        recorder.add_frame(&frame(0)).unwrap();
+        recorder.finish().unwrap();
+
+        let bytes = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(bytes.len(), 2 * 64 * 32 * 3);
+        assert_eq!(&bytes[0..6], &[0xFF, 0xFF, 0xFF, 0, 0, 0]);
+    }
+}
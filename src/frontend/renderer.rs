@@ -0,0 +1,206 @@
+use super::{Palette, Rgb, Settings};
+use crate::chip8::display::{Display, Region, CHIP8_SCREEN_HEIGHT, CHIP8_SCREEN_WIDTH};
+use std::str::FromStr;
+
+/// Intensities below this are treated as fully faded, so that pixels don't fade forever.
+const MIN_INTENSITY: f32 = 1.0 / 255.0;
+
+/// How pixels that were just turned off are shown, to hide the flicker caused by games erasing and redrawing sprites.
+/// These only change what gets presented; the framebuffer the emulator uses for collision detection is untouched.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Persistence {
+    /// Pixels are shown exactly as they are in the framebuffer.
+    #[default]
+    Off,
+    /// Pixels fade out like the phosphor of a CRT. `decay` is the fraction of a pixel's brightness left after each
+    /// frame, between 0 (no persistence) and 1 (pixels never fade).
+    Phosphor { decay: f32 },
+    /// Pixels that were on at the end of the previous frame stay on for the current frame.
+    Blend,
+}
+
+impl FromStr for Persistence {
+    type Err = String;
+
+    /// Parses `off`, `blend`, `phosphor` or `phosphor:DECAY` (e.g. `phosphor:0.7`).
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        let mut parts = mode.splitn(2, ':');
+        match (parts.next(), parts.next()) {
+            (Some("off"), None) => Ok(Persistence::Off),
+            (Some("blend"), None) => Ok(Persistence::Blend),
+            (Some("phosphor"), None) => Ok(Persistence::Phosphor { decay: 0.6 }),
+            (Some("phosphor"), Some(decay)) => match decay.parse::<f32>() {
+                Ok(decay) if (0.0..=1.0).contains(&decay) => Ok(Persistence::Phosphor { decay }),
+                _ => Err(format!("Decay must be between 0 and 1, got '{}'", decay)),
+            },
+            _ => Err(format!(
+                "Unknown persistence mode '{}', expected 'off', 'blend' or 'phosphor[:DECAY]'",
+                mode
+            )),
+        }
+    }
+}
+
+/// An image of the screen as it should be presented, with every pixel turned into a colour.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    pub width: usize,
+    pub height: usize,
+    /// The colour of pixels that are turned off.
+    pub background: Rgb,
+    pixels: Vec<Rgb>,
+}
+
+impl Frame {
+    pub fn new(width: usize, height: usize, background: Rgb) -> Self {
+        Frame {
+            width,
+            height,
+            background,
+            pixels: vec![background; width * height],
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Rgb {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, color: Rgb) {
+        self.pixels[y * self.width + x] = color;
+    }
+
+    /// Returns the frame as packed RGB bytes, one row after another, drawing each pixel as a `scale` sized square.
+    pub fn to_rgb_bytes(&self, scale: usize) -> Vec<u8> {
+        let scale = scale.max(1);
+        let (width, height) = (self.width * scale, self.height * scale);
+
+        let mut bytes = Vec::with_capacity(width * height * 3);
+        for y in 0..height {
+            for x in 0..width {
+                let Rgb(r, g, b) = self.get(x / scale, y / scale);
+                bytes.extend_from_slice(&[r, g, b]);
+            }
+        }
+        bytes
+    }
+
+    /// Returns whether the pixel is drawn in a different colour to the background.
+    pub fn is_lit(&self, x: usize, y: usize) -> bool {
+        self.get(x, y) != self.background
+    }
+}
+
+/// Turns the framebuffer into the frame that gets presented, applying the palette and persistence settings. Until the
+/// renderer is settled, it has to be updated once every frame even when the framebuffer hasn't changed, so that pixels
+/// fade out at the right speed.
+pub struct Renderer {
+    /// The brightness of each pixel between 0 and 1, and the value the pixel had when it was last turned on.
+    intensities: Vec<f32>,
+    values: Vec<u8>,
+    /// The framebuffer as it was at the end of the previous frame.
+    previous: Vec<u8>,
+    /// Whether rendering the same framebuffer again would give the same frame, i.e. nothing is fading out.
+    settled: bool,
+    /// The settings the frame was last rendered with. When they change, the whole frame has to be rendered again.
+    palette: Palette,
+    persistence: Persistence,
+    frame: Frame,
+}
+
+impl Renderer {
+    pub fn new() -> Self {
+        let size = CHIP8_SCREEN_WIDTH * CHIP8_SCREEN_HEIGHT;
+        Renderer {
+            intensities: vec![0.0; size],
+            values: vec![0; size],
+            previous: vec![0; size],
+            settled: true,
+            palette: Palette::default(),
+            persistence: Persistence::Off,
+            frame: Frame::new(CHIP8_SCREEN_WIDTH, CHIP8_SCREEN_HEIGHT, Rgb(0, 0, 0)),
+        }
+    }
+
+    /// Renders the framebuffer at the end of a frame, given the part of it that was drawn to since the last update.
+    /// Without persistence only that part is rendered again, so frames where nothing was drawn cost next to nothing.
+    /// Returns whether the rendered frame changed since the last update.
+    pub fn update(
+        &mut self,
+        display: &Display,
+        dirty: Option<Region>,
+        settings: &Settings,
+    ) -> bool {
+        let palette = &settings.palette;
+        let full = settings.persistence != Persistence::Off
+            || !self.settled
+            || self.persistence != settings.persistence
+            || self.palette != *palette;
+        let region = match dirty {
+            _ if full => Region::screen(),
+            Some(region) => region,
+            None => return false,
+        };
+
+        self.frame.background = palette.color(0);
+        let mut changed = false;
+        let mut settled = true;
+        for y in region.y..region.y + region.height {
+            for x in region.x..region.x + region.width {
+                let index = y * CHIP8_SCREEN_WIDTH + x;
+                let pixel = display.get_pixel(x, y);
+
+                let color = match settings.persistence {
+                    Persistence::Off => palette.color(pixel),
+                    Persistence::Blend => {
+                        settled &= pixel == self.previous[index];
+                        palette.color(pixel | self.previous[index])
+                    }
+                    Persistence::Phosphor { decay } => {
+                        if pixel != 0 {
+                            self.intensities[index] = 1.0;
+                            self.values[index] = pixel;
+                        } else if self.intensities[index] > MIN_INTENSITY {
+                            self.intensities[index] *= decay;
+                        } else {
+                            self.intensities[index] = 0.0;
+                        }
+                        settled &= self.intensities[index] == 0.0 || self.intensities[index] == 1.0;
+                        fade(palette, self.values[index], self.intensities[index])
+                    }
+                };
+
+                if self.frame.get(x, y) != color {
+                    self.frame.set(x, y, color);
+                    changed = true;
+                }
+                self.previous[index] = pixel;
+            }
+        }
+
+        self.settled = settled;
+        if self.palette != *palette {
+            self.palette = palette.clone();
+        }
+        self.persistence = settings.persistence;
+        changed
+    }
+
+    /// Whether the frame would stay the same if the framebuffer doesn't change. When this is false, the renderer has to
+    /// keep being updated every frame.
+    pub fn is_settled(&self) -> bool {
+        self.settled
+    }
+
+    /// The most recently rendered frame.
+    pub fn frame(&self) -> &Frame {
+        &self.frame
+    }
+}
+
+/// Blends the colour of a pixel value towards the background colour.
+fn fade(palette: &Palette, pixel: u8, intensity: f32) -> Rgb {
+    let (Rgb(r0, g0, b0), Rgb(r1, g1, b1)) = (palette.color(0), palette.color(pixel));
+    let mix =
+        |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * intensity).round() as u8;
+    Rgb(mix(r0, r1), mix(g0, g1), mix(b0, b1))
+}
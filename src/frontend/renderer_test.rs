@@ -0,0 +1,127 @@
+#[cfg(test)]
+mod renderer_persistence_test {
+    use crate::chip8::display::{Display, Region};
+    use crate::frontend::{Persistence, Renderer, Rgb, Settings};
+
+    const OFF: Rgb = Rgb(0, 0, 0);
+    const ON: Rgb = Rgb(0xFF, 0xFF, 0xFF);
+
+    fn settings(persistence: Persistence) -> Settings {
+        Settings {
+            persistence,
+            ..Settings::default()
+        }
+    }
+
+    #[test]
+    fn persistence_off_test() {
+        let settings = settings(Persistence::Off);
+        let mut renderer = Renderer::new();
+        let mut display = Display::new();
+
+        display.screen[3][4] = 1;
+        assert!(renderer.update(&display, Some(Region::screen()), &settings));
+        assert_eq!(renderer.frame().get(3, 4), ON);
+
+        display.screen[3][4] = 0;
+        assert!(renderer.update(&display, Some(Region::screen()), &settings));
+        assert_eq!(renderer.frame().get(3, 4), OFF);
+        assert!(renderer.is_settled());
+    }
+
+    #[test]
+    fn persistence_blend_test() {
+        let settings = settings(Persistence::Blend);
+        let mut renderer = Renderer::new();
+        let mut display = Display::new();
+
+        display.screen[3][4] = 1;
+        renderer.update(&display, Some(Region::screen()), &settings);
+        display.screen[3][4] = 0;
+        renderer.update(&display, Some(Region::screen()), &settings);
+        assert_eq!(renderer.frame().get(3, 4), ON);
+        assert!(!renderer.is_settled());
+
+        renderer.update(&display, Some(Region::screen()), &settings);
+        assert_eq!(renderer.frame().get(3, 4), OFF);
+        assert!(renderer.is_settled());
+    }
+
+    #[test]
+    fn persistence_phosphor_test() {
+        let settings = settings(Persistence::Phosphor { decay: 0.5 });
+        let mut renderer = Renderer::new();
+        let mut display = Display::new();
+
+        display.screen[3][4] = 1;
+        renderer.update(&display, Some(Region::screen()), &settings);
+        assert_eq!(renderer.frame().get(3, 4), ON);
+
+        display.screen[3][4] = 0;
+        renderer.update(&display, Some(Region::screen()), &settings);
+        assert_eq!(renderer.frame().get(3, 4), Rgb(0x80, 0x80, 0x80));
+        renderer.update(&display, Some(Region::screen()), &settings);
+        assert_eq!(renderer.frame().get(3, 4), Rgb(0x40, 0x40, 0x40));
+
+        while !renderer.is_settled() {
+            renderer.update(&display, Some(Region::screen()), &settings);
+        }
+        assert_eq!(renderer.frame().get(3, 4), OFF);
+        assert_eq!(display.screen[3][4], 0);
+    }
+
+    #[test]
+    fn dirty_region_test() {
+        let mut settings = settings(Persistence::Off);
+        let mut renderer = Renderer::new();
+        let mut display = Display::new();
+
+        // Pixels outside the dirty region aren't looked at.
+        display.screen[3][4] = 1;
+        assert!(!renderer.update(&display, None, &settings));
+        display.mark_dirty(Region {
+            x: 60,
+            y: 0,
+            width: 8,
+            height: 2,
+        });
+        let dirty = display.take_dirty();
+        assert_eq!(
+            dirty,
+            Some(Region {
+                x: 0,
+                y: 0,
+                width: 64,
+                height: 2
+            })
+        );
+        assert!(!renderer.update(&display, dirty, &settings));
+
+        display.mark_dirty(Region {
+            x: 3,
+            y: 4,
+            width: 1,
+            height: 1,
+        });
+        let dirty = display.take_dirty();
+        assert!(renderer.update(&display, dirty, &settings));
+        assert_eq!(renderer.frame().get(3, 4), ON);
+
+        // Changing the palette renders the whole frame again.
+        settings.palette = settings.palette.next();
+        assert!(renderer.update(&display, None, &settings));
+        assert_eq!(renderer.frame().get(3, 4), settings.palette.color(1));
+    }
+
+    #[test]
+    fn parse_persistence_test() {
+        assert_eq!("off".parse(), Ok(Persistence::Off));
+        assert_eq!("blend".parse(), Ok(Persistence::Blend));
+        assert_eq!(
+            "phosphor:0.25".parse(),
+            Ok(Persistence::Phosphor { decay: 0.25 })
+        );
+        assert!("phosphor:2".parse::<Persistence>().is_err());
+        assert!("ghosting".parse::<Persistence>().is_err());
+    }
+}
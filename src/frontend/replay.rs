@@ -0,0 +1,134 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Records the state of the keypad to a file whenever it changes, so that a session can be replayed later.
+///
+/// # File format
+/// Recordings are plain text. The first line holds the seed of the random number generator, so that games using CXNN
+/// play out the same way. Each line after that holds the frame a change happened on and the new state of the keypad as
+/// a hex bit mask (bit N is set when key N is pressed). The last line marks the frame the recording ended on:
+///
+/// ```text
+/// seed 1234
+/// 0 0000
+/// 35 0010
+/// 41 0000
+/// 600 end
+/// ```
+pub struct InputRecorder {
+    file: BufWriter<File>,
+    last_state: Option<u16>,
+}
+
+impl InputRecorder {
+    pub fn create(path: &Path, seed: u64) -> io::Result<Self> {
+        let mut file = BufWriter::new(File::create(path)?);
+        writeln!(file, "seed {}", seed)?;
+
+        Ok(InputRecorder {
+            file,
+            last_state: None,
+        })
+    }
+
+    /// Records the state of the keypad at the start of a frame.
+    pub fn record(&mut self, frame: u64, state: u16) -> io::Result<()> {
+        if self.last_state != Some(state) {
+            writeln!(self.file, "{} {:04X}", frame, state)?;
+            self.last_state = Some(state);
+        }
+        Ok(())
+    }
+
+    /// Marks the end of the recording.
+    pub fn finish(mut self, frame: u64) -> io::Result<()> {
+        writeln!(self.file, "{} end", frame)?;
+        self.file.flush()
+    }
+}
+
+/// Plays back the keypad states from a recording made by `InputRecorder`.
+pub struct InputReplay {
+    /// The recorded changes as (frame, state) pairs, in order.
+    changes: Vec<(u64, u16)>,
+    next_change: usize,
+    /// The state of the keypad after the changes played back so far.
+    state: u16,
+    /// The seed the random number generator had when the recording was made.
+    pub seed: Option<u64>,
+    /// The frame the recording ended on.
+    pub length: u64,
+}
+
+impl InputReplay {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("Could not read {}: {}", path.display(), err))?;
+        contents.parse()
+    }
+
+    /// Returns the state of the keypad at the start of a frame: the last change on or before it, or no keys pressed
+    /// before the first change. Frames must be asked for in order.
+    pub fn state_at(&mut self, frame: u64) -> u16 {
+        while let Some(&(change_frame, change_state)) = self.changes.get(self.next_change) {
+            if change_frame > frame {
+                break;
+            }
+            self.state = change_state;
+            self.next_change += 1;
+        }
+        self.state
+    }
+}
+
+impl std::str::FromStr for InputReplay {
+    type Err = String;
+
+    fn from_str(contents: &str) -> Result<Self, Self::Err> {
+        let mut changes = Vec::new();
+        let mut seed = None;
+        let mut length = None;
+
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let error = || format!("Invalid input recording on line {}: '{}'", number + 1, line);
+            let mut parts = line.split_whitespace();
+            if line.starts_with("seed") {
+                seed = Some(
+                    parts
+                        .nth(1)
+                        .and_then(|seed| seed.parse().ok())
+                        .ok_or_else(error)?,
+                );
+                continue;
+            }
+
+            let frame: u64 = parts
+                .next()
+                .and_then(|frame| frame.parse().ok())
+                .ok_or_else(error)?;
+            match parts.next() {
+                Some("end") => length = Some(frame),
+                Some(state) => {
+                    let state = u16::from_str_radix(state, 16).map_err(|_| error())?;
+                    changes.push((frame, state));
+                }
+                None => return Err(error()),
+            }
+        }
+
+        let last_change = changes.last().map_or(0, |&(frame, _)| frame);
+        Ok(InputReplay {
+            changes,
+            next_change: 0,
+            state: 0,
+            seed,
+            length: length.unwrap_or(last_change),
+        })
+    }
+}
@@ -0,0 +1,47 @@
+#[cfg(test)]
+mod replay_file_test {
+    use crate::frontend::replay::{InputRecorder, InputReplay};
+    use std::fs;
+
+    #[test]
+    fn parse_replay_test() {
+        let mut replay: InputReplay = "seed 42\n0 0000\n# comment\n35 0010\n41 0000\n600 end\n"
+            .parse()
+            .unwrap();
+        assert_eq!(replay.seed, Some(42));
+        assert_eq!(replay.length, 600);
+
+        assert_eq!(replay.state_at(0), 0x0000);
+        assert_eq!(replay.state_at(34), 0x0000);
+        assert_eq!(replay.state_at(35), 0x0010);
+        assert_eq!(replay.state_at(36), 0x0010);
+        assert_eq!(replay.state_at(50), 0x0000);
+        assert_eq!(replay.state_at(700), 0x0000);
+    }
+
+    #[test]
+    fn parse_invalid_replay_test() {
+        assert!("35 00G0".parse::<InputReplay>().is_err());
+        assert!("x 0010".parse::<InputReplay>().is_err());
+        assert!("35".parse::<InputReplay>().is_err());
+    }
+
+    #[test]
+    fn record_and_replay_test() {
+        let path = std::env::temp_dir().join("rusty-chip-record-replay-test.txt");
+        let mut recorder = InputRecorder::create(&path, 7).unwrap();
+        recorder.record(0, 0).unwrap();
+        recorder.record(1, 0).unwrap();
+        recorder.record(2, 0x8001).unwrap();
+        recorder.finish(10).unwrap();
+
+        let mut replay = InputReplay::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(replay.seed, Some(7));
+        assert_eq!(replay.length, 10);
+        assert_eq!(replay.state_at(1), 0);
+        assert_eq!(replay.state_at(2), 0x8001);
+        assert_eq!(replay.state_at(3), 0x8001);
+    }
+}
@@ -0,0 +1,80 @@
+use super::Frame;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Saves a frame as a PNG image, drawing each pixel as a `scale` sized square.
+pub fn save_png(frame: &Frame, path: &Path, scale: u32) -> io::Result<()> {
+    let scale = scale.max(1) as usize;
+    let (width, height) = (frame.width * scale, frame.height * scale);
+    let data = frame.to_rgb_bytes(scale);
+
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(file, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&data))
+        .map_err(io::Error::other)
+}
+
+/// Saves a screenshot of a frame into `dir`, under an automatically numbered filename. Returns the path of the saved
+/// screenshot.
+pub fn save(frame: &Frame, dir: &Path, rom_name: &str, scale: u32) -> io::Result<PathBuf> {
+    let path = next_path(dir, rom_name, "png", SystemTime::now());
+    save_png(frame, &path, scale)?;
+    Ok(path)
+}
+
+/// Returns the first unused path for a screenshot or recording of a ROM, e.g. `PONG-20201018-153012-1.png`. The
+/// timestamp is in UTC.
+pub fn next_path(dir: &Path, rom_name: &str, extension: &str, time: SystemTime) -> PathBuf {
+    let timestamp = format_timestamp(time);
+    (1..)
+        .map(|number| {
+            dir.join(format!(
+                "{}-{}-{}.{}",
+                rom_name, timestamp, number, extension
+            ))
+        })
+        .find(|path| !path.exists())
+        .unwrap()
+}
+
+/// Formats a time as `YYYYMMDD-HHMMSS` in UTC.
+fn format_timestamp(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let (days, seconds) = (seconds / 86400, seconds % 86400);
+
+    // Converts the number of days since 1970-01-01 into a date in the proleptic Gregorian calendar. The calendar is
+    // shifted to start in March so that leap days fall at the end of each 400 year era.
+    // See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
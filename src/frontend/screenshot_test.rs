@@ -0,0 +1,53 @@
+#[cfg(test)]
+mod screenshot_save_test {
+    use crate::frontend::screenshot::{next_path, save_png};
+    use crate::frontend::{Frame, Rgb};
+    use std::fs::{self, File};
+    use std::path::Path;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn next_path_test() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_603_035_012);
+        let path = next_path(Path::new("shots"), "PONG", "png", time);
+        assert_eq!(path, Path::new("shots").join("PONG-20201018-153012-1.png"));
+
+        let leap_day = UNIX_EPOCH + Duration::from_secs(951_782_400);
+        let path = next_path(Path::new("shots"), "PONG", "gif", leap_day);
+        assert_eq!(path, Path::new("shots").join("PONG-20000229-000000-1.gif"));
+    }
+
+    #[test]
+    fn next_path_skips_existing_test() {
+        let dir = std::env::temp_dir().join("rusty-chip-next-path-test");
+        fs::create_dir_all(&dir).unwrap();
+        let time = UNIX_EPOCH;
+
+        let first = next_path(&dir, "MAZE", "png", time);
+        File::create(&first).unwrap();
+        let second = next_path(&dir, "MAZE", "png", time);
+        fs::remove_file(&first).unwrap();
+
+        assert_eq!(second, dir.join("MAZE-19700101-000000-2.png"));
+    }
+
+    #[test]
+    fn save_png_test() {
+        let mut frame = Frame::new(64, 32, Rgb(0, 0, 0));
+        frame.set(1, 0, Rgb(0x11, 0x22, 0x33));
+
+        let path = std::env::temp_dir().join("rusty-chip-save-png-test.png");
+        save_png(&frame, &path, 2).unwrap();
+
+        let decoder = png::Decoder::new(File::open(&path).unwrap());
+        let mut reader = decoder.read_info().unwrap();
+        let mut data = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut data).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!((info.width, info.height), (128, 64));
+        assert_eq!(&data[0..6], &[0, 0, 0, 0, 0, 0]);
+        assert_eq!(&data[6..12], &[0x11, 0x22, 0x33, 0x11, 0x22, 0x33]);
+        assert_eq!(&data[128 * 3 + 6..128 * 3 + 9], &[0x11, 0x22, 0x33]);
+    }
+}
@@ -0,0 +1,465 @@
+use super::filter::Filters;
+use super::font::{GLYPH_HEIGHT, GLYPH_WIDTH};
+use super::layout::Viewport;
+use super::memview::{Highlight, MemoryView, Segment, BYTES_PER_ROW, VISIBLE_ROWS};
+use super::osd::{self, Canvas, Osd};
+use super::{Frame, Frontend, Hotkey, KeyMap, OverlayInfo, Rgb};
+use crate::chip8::display::{CHIP8_SCREEN_HEIGHT, CHIP8_SCREEN_WIDTH};
+use crate::chip8::input::Input;
+use sdl2::audio::{AudioCallback, AudioDevice, AudioSpecDesired};
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::{Keycode, Mod};
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect;
+use sdl2::render::{Texture, TextureCreator, WindowCanvas};
+use sdl2::video::{FullscreenType, WindowContext};
+use sdl2::{EventPump, VideoSubsystem};
+use std::time::Instant;
+
+/// How the SDL window is set up.
+pub struct WindowOptions {
+    /// The size of each CHIP-8 pixel when the window is opened.
+    pub scale: u32,
+    /// Opens the window in fullscreen.
+    pub fullscreen: bool,
+    /// Stretches the screen to fill as much of the window as possible, rather than only scaling it by whole numbers.
+    pub stretch: bool,
+    /// The effects applied to the screen after it is scaled up.
+    pub filters: Filters,
+}
+
+/// The size of each pixel of the font in the memory window.
+const MEMORY_TEXT_SCALE: u32 = 2;
+
+/// The width and height of a character in the memory window, including the space around it.
+const MEMORY_CELL: (u32, u32) = (
+    (GLYPH_WIDTH + 1) * MEMORY_TEXT_SCALE,
+    (GLYPH_HEIGHT + 3) * MEMORY_TEXT_SCALE,
+);
+
+/// The number of characters across the memory window: the address, each byte in hex and as a character, the gaps
+/// between them, and room for the pixels of each byte.
+const MEMORY_COLUMNS: u32 =
+    4 + 3 * BYTES_PER_ROW as u32 + 2 + BYTES_PER_ROW as u32 + 2 + 2 * BYTES_PER_ROW as u32;
+
+/// Presents the emulator in a resizable SDL window. The screen is scaled to fit the window and centred in it.
+pub struct SdlFrontend {
+    canvas: WindowCanvas,
+    video: VideoSubsystem,
+    /// The window showing the memory viewer, while it is open.
+    memory_window: Option<WindowCanvas>,
+    /// The view shown in the memory window, so that it is only redrawn when it changes.
+    memory: Option<MemoryView>,
+    texture_creator: TextureCreator<WindowContext>,
+    /// The texture each frame is uploaded into, and its size. SDL scales it up to the size of the window.
+    texture: Option<(Texture, (u32, u32))>,
+    event_pump: EventPump,
+    keymap: KeyMap,
+    stretch: bool,
+    filters: Filters,
+    /// Whether the window changed size since it was last drawn.
+    resized: bool,
+    osd: Osd,
+    /// Plays the buzzer, unless no audio device could be opened.
+    buzzer: Option<AudioDevice<SquareWave>>,
+}
+
+/// The tone of the buzzer, a square wave at a fixed pitch.
+struct SquareWave {
+    phase_step: f32,
+    phase: f32,
+    volume: f32,
+}
+
+impl AudioCallback for SquareWave {
+    type Channel = f32;
+
+    fn callback(&mut self, out: &mut [f32]) {
+        for sample in out.iter_mut() {
+            *sample = if self.phase < 0.5 {
+                self.volume
+            } else {
+                -self.volume
+            };
+            self.phase = (self.phase + self.phase_step) % 1.0;
+        }
+    }
+}
+
+impl SdlFrontend {
+    pub fn new(options: WindowOptions, keymap: KeyMap) -> Self {
+        let ctx = sdl2::init().unwrap();
+        let video = ctx.video().unwrap();
+        let mut builder = video.window(
+            "CHIP-8 Emulator",
+            CHIP8_SCREEN_WIDTH as u32 * options.scale,
+            CHIP8_SCREEN_HEIGHT as u32 * options.scale,
+        );
+        builder.position_centered().resizable().opengl();
+        if options.fullscreen {
+            builder.fullscreen_desktop();
+        }
+        let mut window = builder.build().unwrap();
+        let _ = window.set_minimum_size(CHIP8_SCREEN_WIDTH as u32, CHIP8_SCREEN_HEIGHT as u32);
+
+        // Pixels are scaled up without any smoothing, so that they stay sharp when stretched.
+        sdl2::hint::set("SDL_RENDER_SCALE_QUALITY", "nearest");
+        let mut canvas = window.into_canvas().build().unwrap();
+        canvas.clear();
+        canvas.present();
+
+        // The emulator still runs without sound if there is no audio device.
+        let buzzer = ctx.audio().ok().and_then(|audio| {
+            let spec = AudioSpecDesired {
+                freq: Some(44100),
+                channels: Some(1),
+                samples: None,
+            };
+            audio
+                .open_playback(None, &spec, |spec| SquareWave {
+                    phase_step: 440.0 / spec.freq as f32,
+                    phase: 0.0,
+                    volume: 0.1,
+                })
+                .ok()
+        });
+
+        SdlFrontend {
+            texture_creator: canvas.texture_creator(),
+            texture: None,
+            canvas,
+            video,
+            memory_window: None,
+            memory: None,
+            event_pump: ctx.event_pump().unwrap(),
+            keymap,
+            stretch: options.stretch,
+            filters: options.filters,
+            resized: false,
+            osd: Osd::new(),
+            buzzer,
+        }
+    }
+
+    /// Switches between fullscreen and a window. Fullscreen uses the desktop's resolution rather than changing it.
+    fn toggle_fullscreen(&mut self) {
+        let window = self.canvas.window_mut();
+        let fullscreen = match window.fullscreen_state() {
+            FullscreenType::Off => FullscreenType::Desktop,
+            _ => FullscreenType::Off,
+        };
+        let _ = window.set_fullscreen(fullscreen);
+        self.resized = true;
+    }
+
+    /// Opens the memory window next to the main window. The emulator keeps running without it if it can't be opened.
+    fn open_memory_window(&mut self) -> Option<WindowCanvas> {
+        let height = (VISIBLE_ROWS as u32 + 1) * MEMORY_CELL.1;
+        let window = self
+            .video
+            .window("Memory", MEMORY_COLUMNS * MEMORY_CELL.0, height)
+            .build()
+            .ok()?;
+        window.into_canvas().build().ok()
+    }
+
+    /// Whether an event happened in the memory window.
+    fn is_memory_window(&self, window_id: u32) -> bool {
+        self.memory_window
+            .as_ref()
+            .is_some_and(|canvas| canvas.window().id() == window_id)
+    }
+}
+
+/// Draws the memory viewer. Each byte's pixels are drawn as squares the size of a font pixel.
+fn draw_memory(canvas: &mut WindowCanvas, view: &MemoryView) {
+    let (cell_width, cell_height) = MEMORY_CELL;
+    let scale = MEMORY_TEXT_SCALE;
+    canvas.set_draw_color(Color::RGB(0, 0, 0));
+    canvas.clear();
+    let (title, _) = Highlight::Plain.colors();
+    osd::draw_text(canvas, view.title(), 0, scale, scale, title);
+
+    for (row, memory_row) in view.rows.iter().enumerate() {
+        let y = (row as u32 + 1) * cell_height;
+        let mut x = 0;
+        for segment in memory_row.segments() {
+            match segment {
+                Segment::Text(text, foreground, background) => {
+                    let width = text.chars().count() as u32 * cell_width;
+                    Canvas::fill_rect(canvas, x, y, width, cell_height, background);
+                    osd::draw_text(canvas, &text, x, y + scale, scale, foreground);
+                    x += width;
+                }
+                Segment::Bitmap(byte, foreground, background) => {
+                    let pixel = cell_width / 4;
+                    for bit in 0..8 {
+                        let color = if byte & (0x80 >> bit) != 0 {
+                            foreground
+                        } else {
+                            background
+                        };
+                        Canvas::fill_rect(
+                            canvas,
+                            x + bit * pixel,
+                            y,
+                            pixel,
+                            cell_height - scale,
+                            color,
+                        );
+                    }
+                    x += 2 * cell_width;
+                }
+            }
+        }
+    }
+    canvas.present();
+}
+
+/// Returns the hex key bound to an SDL keycode. Only keys whose name is a single character can be bound.
+fn key_for(keymap: &KeyMap, keycode: Keycode) -> Option<usize> {
+    let name = keycode.name();
+    let mut name = name.chars();
+    match (name.next(), name.next()) {
+        (Some(c), None) => keymap.key_for(c),
+        _ => None,
+    }
+}
+
+impl Canvas for WindowCanvas {
+    fn size(&self) -> (u32, u32) {
+        self.output_size().unwrap_or((0, 0))
+    }
+
+    fn fill_rect(&mut self, x: u32, y: u32, width: u32, height: u32, Rgb(r, g, b): Rgb) {
+        self.set_draw_color(Color::RGB(r, g, b));
+        let _ = WindowCanvas::fill_rect(self, Rect::new(x as i32, y as i32, width, height));
+    }
+}
+
+/// Returns the hotkey bound to an SDL keycode.
+fn hotkey_for(keycode: Keycode) -> Option<Hotkey> {
+    match keycode {
+        Keycode::F1 => Some(Hotkey::Reset),
+        Keycode::F2 => Some(Hotkey::CyclePalette),
+        Keycode::F3 => Some(Hotkey::ToggleCounter),
+        Keycode::F4 => Some(Hotkey::ToggleKeypad),
+        Keycode::F5 | Keycode::Pause => Some(Hotkey::TogglePause),
+        Keycode::F6 => Some(Hotkey::AdvanceFrame),
+        Keycode::F7 => Some(Hotkey::AdvanceInstruction),
+        Keycode::F8 => Some(Hotkey::ToggleFastForward),
+        Keycode::F9 => Some(Hotkey::ToggleSlowMotion),
+        Keycode::F10 => Some(Hotkey::ToggleRecording),
+        Keycode::F12 => Some(Hotkey::Screenshot),
+        _ => None,
+    }
+}
+
+/// Returns the hotkey bound to a number key pressed with Ctrl, which turns one of the first nine cheats on or off.
+fn cheat_hotkey_for(keycode: Keycode) -> Option<Hotkey> {
+    let index = match keycode {
+        Keycode::Num1 => 0,
+        Keycode::Num2 => 1,
+        Keycode::Num3 => 2,
+        Keycode::Num4 => 3,
+        Keycode::Num5 => 4,
+        Keycode::Num6 => 5,
+        Keycode::Num7 => 6,
+        Keycode::Num8 => 7,
+        Keycode::Num9 => 8,
+        _ => return None,
+    };
+    Some(Hotkey::ToggleCheat(index))
+}
+
+/// Returns the hotkey bound to a key while the memory viewer is open. Hex digits edit memory only while the viewer
+/// allows it, and otherwise still press keys on the keypad.
+fn memory_hotkey_for(keycode: Keycode, view: &MemoryView) -> Option<Hotkey> {
+    let page = (BYTES_PER_ROW * VISIBLE_ROWS) as isize;
+    match keycode {
+        Keycode::Left => Some(Hotkey::MoveMemoryCursor(-1)),
+        Keycode::Right => Some(Hotkey::MoveMemoryCursor(1)),
+        Keycode::Up => Some(Hotkey::MoveMemoryCursor(-(BYTES_PER_ROW as isize))),
+        Keycode::Down => Some(Hotkey::MoveMemoryCursor(BYTES_PER_ROW as isize)),
+        Keycode::PageUp => Some(Hotkey::MoveMemoryCursor(-page)),
+        Keycode::PageDown => Some(Hotkey::MoveMemoryCursor(page)),
+        _ if view.editable => {
+            let name = keycode.name();
+            let mut name = name.chars();
+            match (name.next(), name.next()) {
+                (Some(c), None) => c.to_digit(16).map(|digit| Hotkey::EditMemory(digit as u8)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+impl Frontend for SdlFrontend {
+    fn poll_input(&mut self, input: &mut Input) -> Vec<Hotkey> {
+        let mut hotkeys = Vec::new();
+        let events: Vec<Event> = self.event_pump.poll_iter().collect();
+        for event in events {
+            match event {
+                Event::KeyDown {
+                    keycode: Some(Keycode::Escape),
+                    ..
+                }
+                | Event::Quit { .. } => input.quit(),
+                Event::KeyDown {
+                    keycode: Some(Keycode::F11),
+                    ..
+                } => self.toggle_fullscreen(),
+                Event::KeyDown {
+                    keycode: Some(Keycode::Return),
+                    keymod,
+                    ..
+                } if keymod.intersects(Mod::LALTMOD | Mod::RALTMOD) => self.toggle_fullscreen(),
+                Event::KeyDown {
+                    keycode: Some(keycode),
+                    keymod,
+                    ..
+                } if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) => {
+                    hotkeys.extend(cheat_hotkey_for(keycode))
+                }
+                Event::Window {
+                    win_event: WindowEvent::Close,
+                    window_id,
+                    ..
+                } => {
+                    if self.is_memory_window(window_id) {
+                        hotkeys.push(Hotkey::ToggleMemoryViewer);
+                    } else {
+                        input.quit();
+                    }
+                }
+                Event::Window {
+                    win_event: WindowEvent::SizeChanged(..),
+                    window_id,
+                    ..
+                } if !self.is_memory_window(window_id) => self.resized = true,
+                Event::KeyDown {
+                    keycode: Some(Keycode::Tab),
+                    ..
+                } => hotkeys.push(Hotkey::ToggleMemoryViewer),
+                Event::KeyDown {
+                    keycode: Some(keycode),
+                    ..
+                } => {
+                    let memory_hotkey = || {
+                        let view = self.memory.as_ref()?;
+                        memory_hotkey_for(keycode, view)
+                    };
+                    if let Some(hotkey) = hotkey_for(keycode).or_else(memory_hotkey) {
+                        hotkeys.push(hotkey);
+                    } else if let Some(key) = key_for(&self.keymap, keycode) {
+                        input.set_pressed(key, true);
+                    }
+                }
+                Event::KeyUp {
+                    keycode: Some(keycode),
+                    ..
+                } => {
+                    if let Some(key) = key_for(&self.keymap, keycode) {
+                        input.set_pressed(key, false);
+                    }
+                }
+                _ => {}
+            }
+        }
+        hotkeys
+    }
+
+    /// Uploads the frame into a texture and draws it as large as it fits in the window. The size of the frame is
+    /// checked every time, so the layout is ready to follow a switch between resolutions, though the emulator only has
+    /// the 64x32 screen of CHIP-8 so far.
+    fn draw(&mut self, frame: &Frame) {
+        let screen = (frame.width as u32, frame.height as u32);
+        let window = self.canvas.output_size().unwrap_or(screen);
+        let viewport = Viewport::fit(window, screen, self.stretch);
+
+        // Filters work on the scaled up image, so they are applied at about the size the screen is shown at and SDL
+        // only has to make up the difference when stretching.
+        let image = if self.filters == Filters::default() {
+            None
+        } else {
+            let scale = (viewport.width / screen.0).max(1);
+            Some(self.filters.apply(frame, scale as usize))
+        };
+        let image = image.as_ref().unwrap_or(frame);
+        let size = (image.width as u32, image.height as u32);
+
+        if self.texture.as_ref().map(|(_, size)| *size) != Some(size) {
+            if let Some((texture, _)) = self.texture.take() {
+                // Safe because the texture was made by this frontend's renderer, which is still alive.
+                unsafe { texture.destroy() };
+            }
+            let texture = self
+                .texture_creator
+                .create_texture_streaming(PixelFormatEnum::RGB24, size.0, size.1)
+                .unwrap();
+            self.texture = Some((texture, size));
+        }
+        let (texture, _) = self.texture.as_mut().unwrap();
+        let _ = texture.update(None, &image.to_rgb_bytes(1), image.width * 3);
+
+        self.canvas.set_draw_color(Color::RGB(0, 0, 0));
+        self.canvas.clear();
+        let target = Rect::new(
+            viewport.x as i32,
+            viewport.y as i32,
+            viewport.width,
+            viewport.height,
+        );
+        let _ = self.canvas.copy(texture, None, target);
+        self.osd.draw(&mut self.canvas, Instant::now());
+        self.canvas.present();
+        self.resized = false;
+    }
+
+    fn needs_redraw(&self) -> bool {
+        self.resized || self.osd.needs_redraw(Instant::now())
+    }
+
+    fn show_message(&mut self, message: &str) {
+        self.osd.show_message(message, Instant::now());
+    }
+
+    fn show_status(&mut self, status: &str) {
+        self.osd.set_status(status);
+    }
+
+    fn update_overlay(&mut self, info: &OverlayInfo) {
+        self.osd.set_info(info);
+    }
+
+    /// Opens the memory window when the viewer is opened and closes it when the viewer is closed, redrawing it whenever
+    /// the view changes.
+    fn show_memory(&mut self, view: Option<&MemoryView>) {
+        if self.memory.as_ref() == view {
+            return;
+        }
+        self.memory = view.cloned();
+        match view {
+            Some(view) => {
+                if self.memory_window.is_none() {
+                    self.memory_window = self.open_memory_window();
+                }
+                if let Some(canvas) = &mut self.memory_window {
+                    draw_memory(canvas, view);
+                }
+            }
+            None => self.memory_window = None,
+        }
+    }
+
+    fn set_tone(&mut self, on: bool) {
+        if let Some(buzzer) = &self.buzzer {
+            if on {
+                buzzer.resume();
+            } else {
+                buzzer.pause();
+            }
+        }
+    }
+}
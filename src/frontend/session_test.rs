@@ -0,0 +1,59 @@
+#[cfg(test)]
+mod run_session_test {
+    use crate::chip8::asm::assemble;
+    use crate::chip8::Chip8;
+    use crate::frontend::headless::HeadlessFrontend;
+    use crate::frontend::{Persistence, Rgb, RunOptions, Session, Settings};
+
+    const ON: Rgb = Rgb(0xFF, 0xFF, 0xFF);
+
+    /// Draws a pixel in the top left corner in the first frame, and in the second frame moves it 8 pixels to the right
+    /// by erasing it and drawing it again, with three instructions in each frame.
+    const MOVE: &str = "
+              LD I, pixel
+              LD V1, 8
+              DRW V0, V0, 1
+              DRW V0, V0, 1
+              DRW V1, V0, 1
+        loop: JP loop
+        pixel: DB 0x80
+    ";
+
+    fn machine() -> Chip8 {
+        let mut emu = Chip8::new();
+        emu.load_rom_bytes(&assemble(MOVE).unwrap()).unwrap();
+        emu
+    }
+
+    #[test]
+    fn persistence_whole_frame_test() {
+        let settings = Settings {
+            persistence: Persistence::Blend,
+            ..Settings::default()
+        };
+        let mut emu = machine();
+        let mut session = Session::new(RunOptions {
+            cycles_per_frame: 3,
+            ..RunOptions::default()
+        });
+        for _ in 0..2 {
+            session.run_frame(&mut emu, &mut HeadlessFrontend, &settings);
+        }
+
+        // The pixel was on where it was at the end of the first frame, so it stays on there for the second.
+        assert_eq!(session.renderer.frame().get(0, 0), ON);
+        assert_eq!(session.renderer.frame().get(8, 0), ON);
+    }
+
+    #[test]
+    fn step_instruction_draw_test() {
+        let settings = Settings::default();
+        let mut emu = machine();
+        let mut session = Session::new(RunOptions::default());
+        for _ in 0..3 {
+            session.run_instruction(&mut emu, &mut HeadlessFrontend, &settings, true);
+        }
+
+        assert_eq!(session.renderer.frame().get(0, 0), ON);
+    }
+}
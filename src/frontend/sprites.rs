@@ -0,0 +1,167 @@
+use super::screenshot::save_png;
+use super::{Frame, Rgb};
+use crate::chip8::instructions::Instruction;
+use crate::chip8::Chip8;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+/// The number of sprites on each row of a sprite sheet.
+const SHEET_COLUMNS: usize = 8;
+
+/// The tallest sprite DXYN can draw, which sets the height of every cell of a sprite sheet.
+const MAX_SPRITE_HEIGHT: usize = 15;
+
+/// The size of each pixel in saved sprite sheets.
+const SHEET_SCALE: u32 = 4;
+
+const SHEET_BACKGROUND: Rgb = Rgb(0x00, 0x00, 0x00);
+const SHEET_GRID: Rgb = Rgb(0x40, 0x40, 0x40);
+const SHEET_PIXEL: Rgb = Rgb(0xFF, 0xFF, 0xFF);
+
+/// The formats sprites can be saved in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SpriteFormat {
+    /// A PNG image with the sprites laid out in a grid.
+    Png,
+    /// Octo source with a label and the bytes of each sprite, ready to be pasted into a program.
+    Octo,
+}
+
+impl SpriteFormat {
+    /// Picks the format from the extension of a path.
+    pub fn from_path(path: &Path) -> Result<Self, String> {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("png") => Ok(SpriteFormat::Png),
+            Some("8o") => Ok(SpriteFormat::Octo),
+            _ => Err(format!(
+                "Can't tell the sprite format of {}, expected a .png or .8o file",
+                path.display()
+            )),
+        }
+    }
+}
+
+/// A sprite as it was first drawn: the address I pointed at and the bytes DXYN read from there.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sprite {
+    pub address: u16,
+    pub bytes: Vec<u8>,
+}
+
+/// Collects the sprites a game draws, by looking at each DXYN before it runs. Each address and height is only kept
+/// once, with the bytes it held the first time it was drawn.
+#[derive(Default)]
+pub struct SpriteRipper {
+    sprites: BTreeMap<(u16, usize), Vec<u8>>,
+}
+
+impl SpriteRipper {
+    /// Records the sprite about to be drawn, if the next instruction draws one.
+    pub fn observe(&mut self, emu: &Chip8) {
+        let height = match emu.next_instruction() {
+            Ok(Instruction::DrawSprite(_, _, height)) if height > 0 => height,
+            _ => return,
+        };
+        let memory = emu.memory();
+        let address = emu.index();
+        self.sprites.entry((address, height)).or_insert_with(|| {
+            (0..height)
+                .map(|row| memory[(address as usize + row) % memory.len()])
+                .collect()
+        });
+    }
+
+    /// The sprites drawn so far, in the order of their addresses.
+    pub fn sprites(&self) -> Vec<Sprite> {
+        self.sprites
+            .iter()
+            .map(|(&(address, _), bytes)| Sprite {
+                address,
+                bytes: bytes.clone(),
+            })
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.sprites.len()
+    }
+
+    /// Draws every sprite as text, with `#` for the pixels that are set.
+    pub fn gallery(&self) -> String {
+        let mut text = String::new();
+        for sprite in self.sprites() {
+            let _ = writeln!(text, "{:03X} (8x{})", sprite.address, sprite.bytes.len());
+            for byte in sprite.bytes {
+                text.extend((0..8).map(|bit| if byte & (0x80 >> bit) != 0 { '#' } else { '.' }));
+                text.push('\n');
+            }
+        }
+        text
+    }
+
+    /// Lays the sprites out in a grid, in the order of their addresses, with a line between each cell.
+    pub fn sheet(&self) -> Frame {
+        let (cell_width, cell_height) = (8 + 1, MAX_SPRITE_HEIGHT + 1);
+        let columns = self.len().clamp(1, SHEET_COLUMNS);
+        let rows = self.len().div_ceil(SHEET_COLUMNS).max(1);
+        let mut frame = Frame::new(
+            columns * cell_width + 1,
+            rows * cell_height + 1,
+            SHEET_BACKGROUND,
+        );
+        for x in 0..frame.width {
+            for y in (0..frame.height).step_by(cell_height) {
+                frame.set(x, y, SHEET_GRID);
+            }
+        }
+        for y in 0..frame.height {
+            for x in (0..frame.width).step_by(cell_width) {
+                frame.set(x, y, SHEET_GRID);
+            }
+        }
+
+        for (index, sprite) in self.sprites().iter().enumerate() {
+            let left = index % SHEET_COLUMNS * cell_width + 1;
+            let top = index / SHEET_COLUMNS * cell_height + 1;
+            for (row, byte) in sprite.bytes.iter().enumerate() {
+                for bit in 0..8 {
+                    if byte & (0x80 >> bit) != 0 {
+                        frame.set(left + bit, top + row, SHEET_PIXEL);
+                    }
+                }
+            }
+        }
+        frame
+    }
+
+    /// Writes the sprites as Octo data, one byte per line in binary so that their shapes can be seen.
+    pub fn to_octo(&self) -> String {
+        let mut source = String::new();
+        for sprite in self.sprites() {
+            let _ = writeln!(
+                source,
+                ": sprite_{:03X}_{} # 8x{} at 0x{:03X}",
+                sprite.address,
+                sprite.bytes.len(),
+                sprite.bytes.len(),
+                sprite.address
+            );
+            for byte in sprite.bytes {
+                let _ = writeln!(source, "\t0b{:08b}", byte);
+            }
+            source.push('\n');
+        }
+        source
+    }
+
+    /// Saves the sprites in the format given by the extension of `path`.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let result = match SpriteFormat::from_path(path)? {
+            SpriteFormat::Png => save_png(&self.sheet(), path, SHEET_SCALE),
+            SpriteFormat::Octo => fs::write(path, self.to_octo()),
+        };
+        result.map_err(|err| format!("Could not save {}: {}", path.display(), err))
+    }
+}
@@ -0,0 +1,66 @@
+#[cfg(test)]
+mod sprite_ripper_test {
+    use crate::chip8::Chip8;
+    use crate::frontend::sprites::{Sprite, SpriteFormat, SpriteRipper};
+    use std::path::Path;
+
+    /// Draws the sprite for the digit 0 twice, then a 2 byte sprite from the ROM.
+    const ROM: &[u8] = &[
+        0x60, 0x00, // V0 = 0
+        0xF0, 0x29, // I = the sprite for V0
+        0xD0, 0x05, // Draw 5 bytes
+        0xD0, 0x05, // Draw them again
+        0xA2, 0x0E, // I = 0x20E
+        0xD0, 0x02, // Draw 2 bytes
+        0x12, 0x0C, // Loop forever
+        0x81, 0xFF,
+    ];
+
+    fn rip() -> SpriteRipper {
+        let mut emu = Chip8::new();
+        emu.load_rom_bytes(ROM).unwrap();
+        let mut ripper = SpriteRipper::default();
+        for _ in 0..8 {
+            ripper.observe(&emu);
+            emu.emulate_cycle().unwrap();
+        }
+        ripper
+    }
+
+    #[test]
+    fn sprite_dedup_test() {
+        let ripper = rip();
+        assert_eq!(
+            ripper.sprites(),
+            vec![
+                Sprite {
+                    address: 0x50,
+                    bytes: vec![0xF0, 0x90, 0x90, 0x90, 0xF0],
+                },
+                Sprite {
+                    address: 0x20E,
+                    bytes: vec![0x81, 0xFF],
+                },
+            ]
+        );
+        assert!(ripper.gallery().contains("20E (8x2)\n#......#\n########\n"));
+    }
+
+    #[test]
+    fn sprite_export_test() {
+        let ripper = rip();
+        let sheet = ripper.sheet();
+        assert_eq!((sheet.width, sheet.height), (2 * 9 + 1, 16 + 1));
+        assert!(sheet.is_lit(10, 1));
+        assert!(!sheet.is_lit(11, 1));
+
+        let octo = ripper.to_octo();
+        assert!(octo.contains(": sprite_20E_2 # 8x2 at 0x20E\n\t0b10000001\n\t0b11111111\n"));
+
+        assert_eq!(
+            SpriteFormat::from_path(Path::new("sprites.8o")),
+            Ok(SpriteFormat::Octo)
+        );
+        assert!(SpriteFormat::from_path(Path::new("sprites.gif")).is_err());
+    }
+}
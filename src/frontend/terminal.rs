@@ -0,0 +1,550 @@
+use super::keymap::KEYPAD_LAYOUT;
+use super::memview::{Highlight, MemoryView, Segment, BYTES_PER_ROW, VISIBLE_ROWS};
+use super::{Frame, Frontend, Hotkey, KeyMap, OverlayInfo, Rgb};
+use crate::chip8::input::{Input, CHIP8_NUM_KEYS};
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{
+    self, Event, KeyCode, KeyEventKind, KeyModifiers, KeyboardEnhancementFlags,
+    PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+};
+use crossterm::style::{
+    Attribute, Color, Print, ResetColor, SetAttribute, SetBackgroundColor, SetForegroundColor,
+};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use std::io::{self, Stdout, Write};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Most terminals only report key presses, so a pressed key is released after this many frames unless the terminal's
+/// key repeat presses it again. Half a second covers the delay before key repeat starts in most terminals, so held keys
+/// don't drop out before the first repeat arrives.
+const KEY_HOLD_FRAMES: u8 = 30;
+
+/// How the framebuffer is packed into character cells.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RenderMode {
+    /// Each cell shows 1x2 pixels using the upper and lower half block characters.
+    HalfBlock,
+    /// Each cell shows 2x4 pixels using braille patterns, which makes the image a quarter of the size.
+    Braille,
+}
+
+impl RenderMode {
+    /// The number of pixels packed into a character cell, as (columns, rows).
+    fn cell_size(self) -> (usize, usize) {
+        match self {
+            RenderMode::HalfBlock => (1, 2),
+            RenderMode::Braille => (2, 4),
+        }
+    }
+}
+
+impl FromStr for RenderMode {
+    type Err = String;
+
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode {
+            "half-block" => Ok(RenderMode::HalfBlock),
+            "braille" => Ok(RenderMode::Braille),
+            _ => Err(format!(
+                "Unknown render mode '{}', expected 'half-block' or 'braille'",
+                mode
+            )),
+        }
+    }
+}
+
+/// A character cell of the rendered screen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cell {
+    pub glyph: char,
+    pub foreground: Rgb,
+    pub background: Rgb,
+}
+
+/// Presents the emulator inside a terminal using ANSI escape sequences, so it can be used over SSH and inside tmux.
+/// The terminal is put into raw mode for the lifetime of the frontend and restored when it is dropped.
+pub struct TerminalFrontend {
+    stdout: Stdout,
+    mode: RenderMode,
+    keymap: KeyMap,
+    /// The terminal size in (columns, rows).
+    size: (u16, u16),
+    /// Whether the terminal is currently showing a notice that it is too small to fit the screen.
+    too_small: bool,
+    /// The lines drawn on the previous frame, so that only lines that changed get redrawn.
+    last_lines: Vec<Vec<Cell>>,
+    /// The number of frames left before each key is released, for terminals that don't report key releases.
+    key_timeouts: [u8; CHIP8_NUM_KEYS],
+    reports_releases: bool,
+    /// The status shown in the top right corner, such as "Paused".
+    status: String,
+    /// The counter and keypad shown in the top left corner.
+    overlay: OverlayInfo,
+    /// The memory viewer shown to the right of the screen, when it is open.
+    memory: Option<MemoryView>,
+    /// Whether the memory viewer changed since it was last drawn.
+    memory_changed: bool,
+}
+
+impl TerminalFrontend {
+    pub fn new(mode: RenderMode, keymap: KeyMap) -> io::Result<Self> {
+        let mut stdout = io::stdout();
+        terminal::enable_raw_mode()?;
+        execute!(stdout, EnterAlternateScreen, Hide, Clear(ClearType::All))?;
+
+        // Terminals implementing the kitty keyboard protocol can tell us when keys are released, which is much better
+        // than guessing. Anything else (including tmux) falls back to releasing keys after a timeout.
+        let reports_releases = terminal::supports_keyboard_enhancement().unwrap_or(false);
+        if reports_releases {
+            execute!(
+                stdout,
+                PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)
+            )?;
+        }
+
+        Ok(TerminalFrontend {
+            stdout,
+            mode,
+            keymap,
+            size: terminal::size()?,
+            too_small: false,
+            last_lines: Vec::new(),
+            key_timeouts: [0; CHIP8_NUM_KEYS],
+            reports_releases,
+            status: String::new(),
+            overlay: OverlayInfo::default(),
+            memory: None,
+            memory_changed: false,
+        })
+    }
+
+    fn handle_event(&mut self, event: Event, input: &mut Input, hotkeys: &mut Vec<Hotkey>) {
+        if let (Event::Key(key_event), Some(view)) = (&event, &self.memory) {
+            if key_event.kind != KeyEventKind::Release {
+                if let Some(hotkey) = memory_hotkey_for(key_event.code, view) {
+                    hotkeys.push(hotkey);
+                    return;
+                }
+            }
+        }
+
+        match event {
+            Event::Key(key_event) => match key_event.code {
+                KeyCode::Esc => input.quit(),
+                KeyCode::F(number) if key_event.kind != KeyEventKind::Release => {
+                    hotkeys.extend(hotkey_for(number))
+                }
+                KeyCode::Char('c') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                    input.quit()
+                }
+                KeyCode::Char(c @ '1'..='9')
+                    if key_event.modifiers.contains(KeyModifiers::CONTROL)
+                        && key_event.kind != KeyEventKind::Release =>
+                {
+                    hotkeys.push(Hotkey::ToggleCheat(c as usize - '1' as usize))
+                }
+                KeyCode::Tab if key_event.kind != KeyEventKind::Release => {
+                    hotkeys.push(Hotkey::ToggleMemoryViewer)
+                }
+                KeyCode::Char(c) => {
+                    if let Some(key) = self.keymap.key_for(c) {
+                        let pressed = key_event.kind != KeyEventKind::Release;
+                        input.set_pressed(key, pressed);
+                        self.key_timeouts[key] = if pressed { KEY_HOLD_FRAMES } else { 0 };
+                    }
+                }
+                _ => {}
+            },
+            Event::Resize(columns, rows) => {
+                self.size = (columns, rows);
+                self.too_small = false;
+                self.last_lines.clear();
+                let _ = queue!(self.stdout, ResetColor, Clear(ClearType::All));
+            }
+            _ => {}
+        }
+    }
+
+    /// Draws a notice in place of the screen when the terminal is too small to fit it.
+    fn draw_too_small(&mut self) -> io::Result<()> {
+        if !self.too_small {
+            queue!(
+                self.stdout,
+                ResetColor,
+                Clear(ClearType::All),
+                MoveTo(0, 0),
+                Print("Terminal too small")
+            )?;
+            self.too_small = true;
+            self.last_lines.clear();
+        }
+        self.stdout.flush()
+    }
+
+    fn draw_lines(&mut self, lines: Vec<Vec<Cell>>) -> io::Result<()> {
+        let (columns, rows) = self.size;
+        let width = lines[0].len() as u16;
+        let height = lines.len() as u16;
+        if width > columns || height > rows {
+            return self.draw_too_small();
+        }
+
+        // Anything left of lines that got shorter has to be cleared, such as the memory viewer once it is closed.
+        let resized = self.last_lines.len() != lines.len()
+            || self
+                .last_lines
+                .first()
+                .is_some_and(|line| !line.is_empty() && line.len() != lines[0].len());
+        if self.too_small || resized {
+            queue!(self.stdout, ResetColor, Clear(ClearType::All))?;
+            self.too_small = false;
+            self.last_lines = vec![Vec::new(); lines.len()];
+        }
+
+        let left = (columns - width) / 2;
+        let top = (rows - height) / 2;
+        for (row, line) in lines.into_iter().enumerate() {
+            if self.last_lines[row] == line {
+                continue;
+            }
+
+            queue!(self.stdout, MoveTo(left, top + row as u16))?;
+            // Colours are only changed when they differ from the previous cell, which keeps the output small enough to
+            // be drawn at full speed over slow connections.
+            let mut current = None;
+            for cell in line.iter() {
+                if current != Some((cell.foreground, cell.background)) {
+                    queue!(
+                        self.stdout,
+                        SetForegroundColor(to_color(cell.foreground)),
+                        SetBackgroundColor(to_color(cell.background))
+                    )?;
+                    current = Some((cell.foreground, cell.background));
+                }
+                queue!(self.stdout, Print(cell.glyph))?;
+            }
+            self.last_lines[row] = line;
+        }
+        queue!(self.stdout, ResetColor)?;
+        self.draw_overlay()?;
+        self.stdout.flush()
+    }
+
+    /// Draws the status in the top right corner, and the counter and keypad in the top left corner. They are drawn
+    /// after the screen, so they stay visible even when the screen fills the whole terminal.
+    fn draw_overlay(&mut self) -> io::Result<()> {
+        let columns = self.size.0 as usize;
+        let status: String = self.status.chars().take(columns).collect();
+        if !status.is_empty() {
+            let left = (columns - status.chars().count()) as u16;
+            queue!(self.stdout, MoveTo(left, 0), Print(status))?;
+        }
+
+        let mut row = 0;
+        if let Some((frames, instructions)) = self.overlay.counter {
+            let counter = format!("{} FPS {} IPS", frames, instructions);
+            queue!(self.stdout, MoveTo(0, row), Print(counter))?;
+            row += 1;
+        }
+        if let Some(state) = self.overlay.keypad {
+            for keys in KEYPAD_LAYOUT.iter() {
+                queue!(self.stdout, MoveTo(0, row))?;
+                for &key in keys {
+                    let attribute = if state & (1 << key) != 0 {
+                        Attribute::Reverse
+                    } else {
+                        Attribute::NoReverse
+                    };
+                    queue!(
+                        self.stdout,
+                        SetAttribute(attribute),
+                        Print(format!("{:X}", key))
+                    )?;
+                }
+                queue!(self.stdout, SetAttribute(Attribute::NoReverse))?;
+                row += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Redraws everything on the next frame, so that overlay text that got shorter or was hidden doesn't leave
+    /// anything behind.
+    fn clear_overlay(&mut self) {
+        if !self.too_small {
+            self.last_lines.clear();
+        }
+    }
+}
+
+impl Frontend for TerminalFrontend {
+    fn poll_input(&mut self, input: &mut Input) -> Vec<Hotkey> {
+        let mut hotkeys = Vec::new();
+        while let Ok(true) = event::poll(Duration::from_secs(0)) {
+            match event::read() {
+                Ok(event) => self.handle_event(event, input, &mut hotkeys),
+                Err(_) => break,
+            }
+        }
+
+        if !self.reports_releases {
+            for (key, timeout) in self.key_timeouts.iter_mut().enumerate() {
+                if *timeout > 0 {
+                    *timeout -= 1;
+                    if *timeout == 0 {
+                        input.set_pressed(key, false);
+                    }
+                }
+            }
+        }
+        hotkeys
+    }
+
+    fn draw(&mut self, frame: &Frame) {
+        let mut lines = render_cells(frame, self.mode);
+        if let Some(view) = &self.memory {
+            lines = beside(lines, render_memory(view));
+        }
+        self.memory_changed = false;
+        let _ = self.draw_lines(lines);
+    }
+
+    fn needs_redraw(&self) -> bool {
+        (self.last_lines.is_empty() && !self.too_small) || self.memory_changed
+    }
+
+    /// Shows the message on the bottom line of the terminal. If the screen reaches the bottom line, the message stays
+    /// until that line of the screen gets redrawn.
+    fn show_message(&mut self, message: &str) {
+        let (columns, rows) = self.size;
+        let message: String = message.chars().take(columns as usize).collect();
+        let _ = queue!(
+            self.stdout,
+            ResetColor,
+            MoveTo(0, rows.saturating_sub(1)),
+            Clear(ClearType::CurrentLine),
+            Print(message)
+        );
+        let _ = self.stdout.flush();
+    }
+
+    fn show_status(&mut self, status: &str) {
+        self.status = status.to_string();
+        self.clear_overlay();
+    }
+
+    fn update_overlay(&mut self, info: &OverlayInfo) {
+        if self.overlay == *info {
+            return;
+        }
+
+        let counter_width = |info: &OverlayInfo| {
+            info.counter.map_or(0, |(frames, instructions)| {
+                format!("{} FPS {} IPS", frames, instructions).len()
+            })
+        };
+        let shrunk = counter_width(info) < counter_width(&self.overlay)
+            || (self.overlay.keypad.is_some() && info.keypad.is_none());
+        self.overlay = info.clone();
+        if shrunk {
+            self.clear_overlay();
+        } else if !self.too_small && !self.last_lines.is_empty() {
+            let _ = self.draw_overlay();
+            let _ = self.stdout.flush();
+        }
+    }
+
+    fn show_memory(&mut self, view: Option<&MemoryView>) {
+        if self.memory.as_ref() != view {
+            self.memory = view.cloned();
+            self.memory_changed = true;
+        }
+    }
+
+    /// Rings the terminal bell when the buzzer starts. Terminals can't hold a tone, so stopping it does nothing.
+    fn set_tone(&mut self, on: bool) {
+        if on {
+            let _ = queue!(self.stdout, Print('\x07'));
+            let _ = self.stdout.flush();
+        }
+    }
+}
+
+impl Drop for TerminalFrontend {
+    fn drop(&mut self) {
+        if self.reports_releases {
+            let _ = execute!(self.stdout, PopKeyboardEnhancementFlags);
+        }
+        let _ = execute!(self.stdout, ResetColor, Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Returns the hotkey bound to a function key.
+fn hotkey_for(number: u8) -> Option<Hotkey> {
+    match number {
+        1 => Some(Hotkey::Reset),
+        2 => Some(Hotkey::CyclePalette),
+        3 => Some(Hotkey::ToggleCounter),
+        4 => Some(Hotkey::ToggleKeypad),
+        5 => Some(Hotkey::TogglePause),
+        6 => Some(Hotkey::AdvanceFrame),
+        7 => Some(Hotkey::AdvanceInstruction),
+        8 => Some(Hotkey::ToggleFastForward),
+        9 => Some(Hotkey::ToggleSlowMotion),
+        10 => Some(Hotkey::ToggleRecording),
+        12 => Some(Hotkey::Screenshot),
+        _ => None,
+    }
+}
+
+/// Returns the hotkey bound to a key while the memory viewer is open. Hex digits edit memory only while the viewer
+/// allows it, and otherwise still press keys on the keypad.
+fn memory_hotkey_for(code: KeyCode, view: &MemoryView) -> Option<Hotkey> {
+    match code {
+        KeyCode::Left => Some(Hotkey::MoveMemoryCursor(-1)),
+        KeyCode::Right => Some(Hotkey::MoveMemoryCursor(1)),
+        KeyCode::Up => Some(Hotkey::MoveMemoryCursor(-(BYTES_PER_ROW as isize))),
+        KeyCode::Down => Some(Hotkey::MoveMemoryCursor(BYTES_PER_ROW as isize)),
+        KeyCode::PageUp => Some(Hotkey::MoveMemoryCursor(
+            -((BYTES_PER_ROW * VISIBLE_ROWS) as isize),
+        )),
+        KeyCode::PageDown => Some(Hotkey::MoveMemoryCursor(
+            (BYTES_PER_ROW * VISIBLE_ROWS) as isize,
+        )),
+        KeyCode::Char(c) if view.editable => {
+            c.to_digit(16).map(|digit| Hotkey::EditMemory(digit as u8))
+        }
+        _ => None,
+    }
+}
+
+fn to_color(Rgb(r, g, b): Rgb) -> Color {
+    Color::Rgb { r, g, b }
+}
+
+/// Packs a frame into lines of character cells.
+pub fn render_cells(frame: &Frame, mode: RenderMode) -> Vec<Vec<Cell>> {
+    let (cell_width, cell_height) = mode.cell_size();
+
+    (0..frame.height / cell_height)
+        .map(|row| {
+            (0..frame.width / cell_width)
+                .map(|column| {
+                    let (x, y) = (column * cell_width, row * cell_height);
+                    match mode {
+                        RenderMode::HalfBlock => half_block(frame, x, y),
+                        RenderMode::Braille => braille(frame, x, y),
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Lays out the memory viewer as lines of character cells, with the title on the first line. Each byte's pixels are
+/// packed into four cells of left and right half blocks.
+pub fn render_memory(view: &MemoryView) -> Vec<Vec<Cell>> {
+    let text = |text: &str, foreground: Rgb, background: Rgb| {
+        text.chars()
+            .map(|glyph| Cell {
+                glyph,
+                foreground,
+                background,
+            })
+            .collect::<Vec<_>>()
+    };
+    let (title, _) = Highlight::Plain.colors();
+    let mut lines = vec![text(view.title(), title, Rgb(0, 0, 0))];
+    for row in &view.rows {
+        let mut line = Vec::new();
+        for segment in row.segments() {
+            match segment {
+                Segment::Text(string, foreground, background) => {
+                    line.extend(text(&string, foreground, background))
+                }
+                Segment::Bitmap(byte, foreground, background) => line.extend((0..4).map(|pair| {
+                    let bits = byte >> (6 - 2 * pair) & 0b11;
+                    Cell {
+                        glyph: [' ', '▐', '▌', '█'][bits as usize],
+                        foreground,
+                        background,
+                    }
+                })),
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Puts two blocks of lines side by side with a gap between them, padding them with blank cells so that every line has
+/// the same width.
+fn beside(left: Vec<Vec<Cell>>, right: Vec<Vec<Cell>>) -> Vec<Vec<Cell>> {
+    const GAP: usize = 2;
+    let blank = Cell {
+        glyph: ' ',
+        foreground: Rgb(0, 0, 0),
+        background: Rgb(0, 0, 0),
+    };
+    let width = |lines: &[Vec<Cell>]| lines.iter().map(Vec::len).max().unwrap_or(0);
+    let (left_width, right_width) = (width(&left), width(&right));
+    let height = left.len().max(right.len());
+    let pad = |line: Option<&Vec<Cell>>, width: usize| {
+        let mut line = line.cloned().unwrap_or_default();
+        line.resize(width, blank);
+        line
+    };
+    (0..height)
+        .map(|row| {
+            let mut line = pad(left.get(row), left_width + GAP);
+            line.extend(pad(right.get(row), right_width));
+            line
+        })
+        .collect()
+}
+
+/// Draws the top pixel with the foreground colour of an upper half block, and the bottom pixel with its background.
+fn half_block(frame: &Frame, x: usize, y: usize) -> Cell {
+    let (top, bottom) = (frame.get(x, y), frame.get(x, y + 1));
+    Cell {
+        glyph: if top == bottom { ' ' } else { '▀' },
+        foreground: top,
+        background: bottom,
+    }
+}
+
+/// Draws the lit pixels as braille dots. A cell can only have one foreground colour, so when lit pixels of different
+/// colours share a cell, the one that stands out the most from the background is used.
+fn braille(frame: &Frame, x: usize, y: usize) -> Cell {
+    // The bit for each dot of a braille pattern, indexed by [row][column]. The bottom row was added to the standard
+    // after the others, which is why its bits come last.
+    const DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+    let mut pattern = 0;
+    let mut foreground = frame.background;
+    for (row, dots) in DOTS.iter().enumerate() {
+        for (column, dot) in dots.iter().enumerate() {
+            if frame.is_lit(x + column, y + row) {
+                let color = frame.get(x + column, y + row);
+                pattern |= dot;
+                if distance(color, frame.background) > distance(foreground, frame.background) {
+                    foreground = color;
+                }
+            }
+        }
+    }
+
+    Cell {
+        glyph: std::char::from_u32(0x2800 + pattern).unwrap(),
+        foreground,
+        background: frame.background,
+    }
+}
+
+fn distance(Rgb(r0, g0, b0): Rgb, Rgb(r1, g1, b1): Rgb) -> u32 {
+    let diff = |a: u8, b: u8| (a as i32 - b as i32).unsigned_abs();
+    diff(r0, r1) + diff(g0, g1) + diff(b0, b1)
+}
@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod terminal_render_test {
+    use crate::frontend::terminal::{render_cells, RenderMode};
+    use crate::frontend::{Frame, KeyMap, Rgb};
+
+    const OFF: Rgb = Rgb(0, 0, 0);
+    const ON: Rgb = Rgb(0xFF, 0xFF, 0xFF);
+    const DIM: Rgb = Rgb(0x55, 0x55, 0x55);
+
+    #[test]
+    fn render_half_block_test() {
+        let mut frame = Frame::new(64, 32, OFF);
+        frame.set(0, 0, ON);
+        frame.set(1, 1, ON);
+        frame.set(2, 0, ON);
+        frame.set(2, 1, ON);
+
+        let lines = render_cells(&frame, RenderMode::HalfBlock);
+        assert_eq!(lines.len(), 16);
+        assert_eq!(lines[0].len(), 64);
+
+        let cells: Vec<(char, Rgb, Rgb)> = lines[0][..4]
+            .iter()
+            .map(|cell| (cell.glyph, cell.foreground, cell.background))
+            .collect();
+        assert_eq!(
+            cells,
+            vec![
+                ('▀', ON, OFF),
+                ('▀', OFF, ON),
+                (' ', ON, ON),
+                (' ', OFF, OFF)
+            ]
+        );
+        assert!(lines[1].iter().all(|cell| cell.glyph == ' '));
+    }
+
+    #[test]
+    fn render_braille_test() {
+        let mut frame = Frame::new(64, 32, OFF);
+        frame.set(0, 0, DIM);
+        frame.set(1, 3, ON);
+
+        let lines = render_cells(&frame, RenderMode::Braille);
+        assert_eq!(lines.len(), 8);
+        assert_eq!(lines[0].len(), 32);
+        assert_eq!(lines[0][0].glyph, '\u{2881}');
+        assert_eq!(lines[0][0].foreground, ON);
+        assert_eq!(lines[0][1].glyph, '\u{2800}');
+    }
+
+    #[test]
+    fn keymap_parse_test() {
+        let keymap: KeyMap = "0123456789ABCDEF".parse().unwrap();
+        assert_eq!(keymap.key_for('a'), Some(0xA));
+        assert_eq!(keymap.key_for('7'), Some(0x7));
+        assert_eq!(keymap.key_for('g'), None);
+
+        assert!("0123".parse::<KeyMap>().is_err());
+        assert!("0023456789abcdef".parse::<KeyMap>().is_err());
+    }
+
+    #[test]
+    fn keymap_default_test() {
+        let keymap = KeyMap::default();
+        assert_eq!(keymap.key_for('x'), Some(0x0));
+        assert_eq!(keymap.key_for('4'), Some(0xC));
+        assert_eq!(keymap.key_for('V'), Some(0xF));
+    }
+}
@@ -0,0 +1,69 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often the ROM file is checked for changes.
+const CHECK_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Reads a ROM from a file, turning it into the bytes to load.
+pub type RomReader = Box<dyn Fn(&Path) -> Result<Vec<u8>, String>>;
+
+/// Watches a ROM file so that it can be loaded again whenever it changes on disk.
+///
+/// A change is only reported once the file has stayed the same for one check, so that a ROM isn't loaded while an
+/// assembler or editor is still halfway through writing it.
+pub struct RomWatcher {
+    path: PathBuf,
+    read: RomReader,
+    /// When the file was last modified, and how long it was, when the ROM was last read.
+    loaded: Option<(SystemTime, u64)>,
+    /// A change that was seen on the last check and is waiting to settle.
+    pending: Option<(SystemTime, u64)>,
+    next_check: Instant,
+}
+
+impl RomWatcher {
+    /// Starts watching a ROM that has just been read with `read`.
+    pub fn new(path: &Path, read: RomReader) -> Self {
+        RomWatcher {
+            path: path.to_path_buf(),
+            read,
+            loaded: stamp(path),
+            pending: None,
+            next_check: Instant::now(),
+        }
+    }
+
+    /// The file being watched.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Checks the file if it is time to. Returns the new ROM if the file changed, or why it couldn't be read.
+    pub fn poll(&mut self, now: Instant) -> Option<Result<Vec<u8>, String>> {
+        if now < self.next_check {
+            return None;
+        }
+        self.next_check = now + CHECK_INTERVAL;
+
+        // A file that has gone missing is usually being replaced, so it is checked again later.
+        let stamp = stamp(&self.path)?;
+        if self.loaded == Some(stamp) {
+            self.pending = None;
+            return None;
+        }
+        if self.pending != Some(stamp) {
+            self.pending = Some(stamp);
+            return None;
+        }
+        self.loaded = Some(stamp);
+        self.pending = None;
+        Some((self.read)(&self.path))
+    }
+}
+
+/// When a file was last modified and how long it is, or `None` if it can't be read.
+fn stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
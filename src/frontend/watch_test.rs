@@ -0,0 +1,34 @@
+#[cfg(test)]
+mod rom_watcher_test {
+    use crate::frontend::watch::RomWatcher;
+    use std::fs;
+    use std::path::Path;
+    use std::time::{Duration, Instant};
+
+    fn read(path: &Path) -> Result<Vec<u8>, String> {
+        fs::read(path).map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn watch_settle_test() {
+        let path = std::env::temp_dir().join("rusty-chip-watch-test.ch8");
+        fs::write(&path, [0x00, 0xE0]).unwrap();
+        let mut watcher = RomWatcher::new(&path, Box::new(read));
+        let start = Instant::now();
+        let later = |millis| start + Duration::from_millis(millis);
+        assert_eq!(watcher.poll(later(0)), None);
+
+        fs::write(&path, [0x12, 0x00, 0x00, 0xE0]).unwrap();
+        // Checks are spaced out, and the change has to last from one check to the next.
+        assert_eq!(watcher.poll(later(100)), None);
+        assert_eq!(watcher.poll(later(300)), None);
+        assert_eq!(
+            watcher.poll(later(600)),
+            Some(Ok(vec![0x12, 0x00, 0x00, 0xE0]))
+        );
+        assert_eq!(watcher.poll(later(900)), None);
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(watcher.poll(later(1200)), None);
+    }
+}
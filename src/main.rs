@@ -1,33 +1,576 @@
+mod cartridge;
+mod cartridge_test;
 mod chip8;
+mod cli;
+mod config;
+mod config_test;
+mod debugger;
+mod debugger_test;
+mod frontend;
+mod patch;
+mod patch_test;
+mod rom;
+mod rom_test;
+mod romdb;
+mod romdb_test;
 
-use std::env;
-use std::thread;
-use std::time::Duration;
+use cartridge::Cartridge;
+use chip8::quirks::Platform;
+use chip8::symbols::Symbols;
+use chip8::{Chip8, CHIP8_MAX_ROM_SIZE};
+use clap::Parser;
+use cli::{AsmArgs, Cli, Command, DebugArgs, MachineArgs, RunArgs, TestArgs};
+use config::{Config, Profile};
+use debugger::Debugger;
+use frontend::cheats::Cheats;
+use frontend::headless::HeadlessFrontend;
+use frontend::recording::VideoRecorder;
+use frontend::replay::{InputRecorder, InputReplay};
+use frontend::sprites::SpriteFormat;
+use frontend::terminal::{RenderMode, TerminalFrontend};
+use frontend::watch::RomWatcher;
+use frontend::{RunOptions, Settings};
+use romdb::Database;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::process;
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
+/// Explains why a file couldn't be read, in terms of what the file was for.
+fn read_error(what: &str, path: &Path, err: io::Error) -> String {
+    match err.kind() {
+        ErrorKind::NotFound => format!("The {} {} does not exist", what, path.display()),
+        ErrorKind::PermissionDenied => format!(
+            "The {} {} can't be read, because you don't have permission to read it",
+            what,
+            path.display()
+        ),
+        _ if path.is_dir() => format!("The {} {} is a directory, not a file", what, path.display()),
+        _ => format!("The {} {} can't be read: {}", what, path.display(), err),
+    }
+}
 
-    if args.len() == 1 {
-        panic!("No rom given");
+/// Returns the name of a ROM file without its directory or extension.
+fn rom_name(rom_path: &Path) -> String {
+    if rom::is_stdin(rom_path) {
+        return String::from("stdin");
     }
+    rom_path.file_stem().map_or(String::from("rom"), |stem| {
+        stem.to_string_lossy().into_owned()
+    })
+}
 
-    let rom_path = args.get(1).unwrap().as_str();
-    let mut emu = chip8::Chip8::new();
-    emu.load_rom(rom_path);
+/// Reads a ROM from a file, stdin or an archive. Octo cartridges are compiled into ROMs, and the settings they ask for
+/// are returned along with the ROM.
+fn read_rom(path: &Path) -> Result<(Vec<u8>, Option<Profile>), String> {
+    let bytes = rom::read(path).map_err(|err| read_error("ROM", path, err))?;
+    if !cartridge::is_cartridge(&bytes) {
+        return Ok((bytes, None));
+    }
+    let cartridge = Cartridge::decode(&bytes)
+        .map_err(|err| format!("The cartridge {} can't be loaded: {}", path.display(), err))?;
+    let rom = cartridge.compile().map_err(|err| {
+        format!(
+            "The program in the cartridge {} can't be compiled: {}",
+            path.display(),
+            err
+        )
+    })?;
+    Ok((rom, Some(cartridge.profile())))
+}
 
-    loop {
-        emu.display.set_should_draw(false);
-        emu.emulate_cycle();
+/// Reads the configuration file given on the command line, or else the usual one if there is one.
+fn load_config(path: Option<PathBuf>) -> Result<(Config, Option<PathBuf>), String> {
+    match path {
+        Some(path) => {
+            if !path.exists() {
+                return Err(format!("The config file {} does not exist", path.display()));
+            }
+            Ok((Config::load(&path)?, Some(path)))
+        }
+        None => match Config::default_path() {
+            Some(path) => Ok((Config::load(&path)?, Some(path))),
+            None => Ok((Config::default(), None)),
+        },
+    }
+}
+
+/// A ROM along with the settings to run it with.
+struct Rom {
+    bytes: Vec<u8>,
+    /// The SHA-1 hash of the ROM, which identifies it in the configuration file and the ROM database.
+    hash: String,
+    profile: Profile,
+}
 
-        if emu.display.should_draw() {
-            emu.display.draw_screen();
+impl Rom {
+    /// Reads a ROM, applies any patches and works out its settings. The options of an Octo cartridge, or else what the
+    /// ROM database knows about the ROM, are used unless the configuration file says otherwise, and ROMs the database
+    /// doesn't know are checked for instructions that only later platforms have. A patched ROM the database doesn't
+    /// know is looked up as it was before it was patched.
+    fn read(path: &Path, patches: &[PathBuf], config: &Config) -> Result<Rom, String> {
+        let (bytes, cartridge) = read_rom(path)?;
+        let original_hash = config::rom_hash(&bytes);
+        let bytes = patch_rom(bytes, patches)?;
+        let hash = config::rom_hash(&bytes);
+        if !patches.is_empty() {
+            eprintln!("The patched ROM has the SHA-1 hash {}", hash);
         }
 
-        if emu.input.should_quit() {
-            break;
+        let database = Database::embedded();
+        let entry = database
+            .lookup(&hash)
+            .or_else(|| database.lookup(&original_hash));
+        let known = match (cartridge, entry) {
+            (Some(profile), _) => profile,
+            (None, Some(entry)) => entry.rom.profile(),
+            (None, None) => Profile {
+                platform: Platform::detect(&bytes),
+                ..Profile::default()
+            },
+        };
+        let profile = config.profile(&hash, &known);
+        Ok(Rom {
+            bytes,
+            hash,
+            profile,
+        })
+    }
+}
+
+/// Applies IPS and BPS patches to a ROM, in order.
+fn patch_rom(rom: Vec<u8>, patches: &[PathBuf]) -> Result<Vec<u8>, String> {
+    patches.iter().try_fold(rom, |rom, path| {
+        let patch = fs::read(path).map_err(|err| read_error("patch", path, err))?;
+        patch::apply(&rom, &patch)
+            .map_err(|err| format!("The patch {} can't be applied: {}", path.display(), err))
+    })
+}
+
+/// Fails for platforms whose instructions aren't emulated, as their ROMs would stop at the first instruction only the
+/// platform has. Their behaviour can still be picked with `--quirks`. `picked_by` says where the platform came from.
+fn check_platform(platform: Platform, picked_by: &str) -> Result<(), String> {
+    let preset = match platform {
+        Platform::Chip8 => return Ok(()),
+        Platform::Schip => "schip",
+        Platform::XoChip => "xochip",
+    };
+    Err(format!(
+        "{} was picked by {}, but its instructions aren't emulated, so its ROMs can't be run. To run a CHIP-8 ROM \
+         with the behaviour of {}, use --quirks {}",
+        platform, picked_by, platform, preset
+    ))
+}
+
+/// Sets up the machine with the ROM loaded.
+fn load(machine: &MachineArgs, rom: &Rom) -> Result<Chip8, String> {
+    match machine.platform {
+        Some(platform) => check_platform(platform, "--platform")?,
+        // ROMs the database or a cartridge puts on a later platform often only rely on its quirks, which are emulated,
+        // so only ROMs whose code uses the instructions of a later platform are turned away. The options can still
+        // insist on running them, in case those instructions are never reached.
+        None => {
+            if let Some(platform) = Platform::detect(&rom.bytes) {
+                return Err(format!(
+                    "The ROM uses instructions only {} has, which aren't emulated, so it can't be run. \
+                     --platform chip8 runs it as CHIP-8 anyway",
+                    platform
+                ));
+            }
+        }
+    }
+    let mut emu = Chip8::new();
+    emu.set_quirks(machine.quirks(&rom.profile));
+    emu.set_stack_depth(machine.stack_depth(&rom.profile));
+    if let Some(path) = &machine.symbols {
+        let source = fs::read_to_string(path).map_err(|err| read_error("program", path, err))?;
+        let symbols = Symbols::from_source(path, &source)
+            .map_err(|err| format!("The labels of {} can't be read: {}", path.display(), err))?;
+        emu.set_symbols(symbols);
+    }
+    emu.load_rom_bytes(&rom.bytes)?;
+    if let Some(seed) = machine.seed {
+        emu.set_seed(seed);
+    }
+    Ok(emu)
+}
+
+fn run(args: RunArgs, config: &Config, config_path: Option<&Path>) -> Result<(), String> {
+    let rom = Rom::read(&args.machine.rom, &args.machine.patches, config)?;
+    let mut emu = load(&args.machine, &rom)?;
+    let cycles_per_frame = args.machine.cycles_per_frame(&rom.profile);
+    let profile = rom.profile;
+
+    let mut settings = Settings {
+        palette: args
+            .palette
+            .or(profile.palette)
+            .unwrap_or_default()
+            .with_colors(args.bg.or(profile.bg), args.fg.or(profile.fg)),
+        persistence: args.persistence.or(profile.persistence).unwrap_or_default(),
+        show_counter: args.show_counter || profile.show_counter.unwrap_or_default(),
+        show_keypad: args.show_keypad || profile.show_keypad.unwrap_or_default(),
+        mute: args.mute || profile.mute.unwrap_or_default(),
+    };
+    let mut run_options = RunOptions {
+        rom_name: rom_name(&args.machine.rom),
+        cycles_per_frame,
+        frame_limit: args.frames,
+        screenshot_at_frame: args.screenshot_at_frame,
+        ..RunOptions::default()
+    };
+    if let Some(dir) = args.screenshot_dir {
+        run_options.screenshot_dir = dir;
+    }
+    if let Some(scale) = args.screenshot_scale {
+        run_options.screenshot_scale = scale;
+    }
+    if let Some(scale) = args.record_scale {
+        run_options.recording_scale = scale;
+    }
+    if let Some(filters) = args.filter.or(profile.filter) {
+        run_options.filters = filters;
+    }
+    if args.paused {
+        run_options.playback.pause();
+    }
+    if let Some(speed) = args.fast_forward.or(profile.fast_forward) {
+        run_options.playback.fast_forward_speed = speed;
+    }
+    if let Some(speed) = args.slow_motion.or(profile.slow_motion) {
+        run_options.playback.slow_motion_speed = speed;
+    }
+    let cheats_path = match &args.cheats {
+        Some(path) => Some(path.clone()),
+        None => Some(args.machine.rom.with_extension("cht")).filter(|path| path.is_file()),
+    };
+    if let Some(path) = cheats_path {
+        run_options.cheats = Cheats::load(&path)?;
+    }
+    if args.watch {
+        if rom::is_stdin(&args.machine.rom) {
+            return Err(String::from("--watch needs a ROM file, not stdin"));
+        }
+        let patches = args.machine.patches.clone();
+        run_options.rom_watcher = Some(RomWatcher::new(
+            &args.machine.rom,
+            Box::new(move |path| patch_rom(read_rom(path)?.0, &patches)),
+        ));
+    }
+    if let Some(path) = &args.replay_input {
+        run_options.input_replay = Some(InputReplay::load(path)?);
+    }
+    // Without a limit a headless run would never end, so stop once there's nothing left to do.
+    if args.headless && run_options.frame_limit.is_none() {
+        run_options.frame_limit = match &run_options.input_replay {
+            Some(replay) => Some(replay.length),
+            None => run_options.screenshot_at_frame,
+        };
+        if run_options.frame_limit.is_none() {
+            return Err(String::from(
+                "A headless run needs --frames N, --screenshot-at-frame N or --replay-input FILE to know when to stop",
+            ));
+        }
+    }
+    if let Some(path) = &args.rip_sprites {
+        SpriteFormat::from_path(path)?;
+        run_options.rip_sprites = Some(path.clone());
+    }
+    run_options.profile = args.profile.clone();
+    if let Some(path) = &args.coverage {
+        if path.extension().is_some_and(|extension| extension == "png") {
+            return Err(String::from(
+                "--coverage is given the file for the disassembly, and saves the image next to it as a .png",
+            ));
         }
+        run_options.coverage = Some(path.clone());
+    }
 
-        emu.input.get_inputs();
-        thread::sleep(Duration::from_millis(1));
+    // Replays only play out the same way if the random number generator does, so the seed is always known.
+    let replay_seed = run_options
+        .input_replay
+        .as_ref()
+        .and_then(|replay| replay.seed);
+    let seed = args
+        .machine
+        .seed
+        .or(replay_seed)
+        .unwrap_or_else(rand::random);
+    emu.set_seed(seed);
+
+    if let Some(path) = &args.record {
+        run_options.video_recorder = Some(VideoRecorder::create(
+            path,
+            run_options.recording_scale,
+            run_options.filters.clone(),
+        )?);
+    }
+    if let Some(path) = &args.record_input {
+        run_options.input_recorder = Some(
+            InputRecorder::create(path, seed)
+                .map_err(|err| format!("Could not create {}: {}", path.display(), err))?,
+        );
+    }
+
+    let keymap = args.keymap.or(profile.keymap).unwrap_or_default();
+    if args.headless {
+        run_options.uncapped = true;
+        // Nobody is watching to look into an error, so the run fails with it.
+        run_options.stop_on_error = true;
+        frontend::run(&mut emu, &mut HeadlessFrontend, &mut settings, run_options)?;
+    } else if args.tty || args.render.is_some() || cfg!(not(feature = "sdl")) {
+        let mode = args.render.unwrap_or(RenderMode::HalfBlock);
+        let mut tty = TerminalFrontend::new(mode, keymap)
+            .map_err(|err| format!("Could not set up the terminal: {}", err))?;
+        frontend::run(&mut emu, &mut tty, &mut settings, run_options)?;
+    } else {
+        #[cfg(feature = "sdl")]
+        frontend::run(
+            &mut emu,
+            &mut frontend::sdl::SdlFrontend::new(
+                frontend::sdl::WindowOptions {
+                    scale: args.scale.or(profile.scale).unwrap_or(10),
+                    fullscreen: args.fullscreen,
+                    stretch: args.stretch,
+                    filters: run_options.filters.clone(),
+                },
+                keymap,
+            ),
+            &mut settings,
+            run_options,
+        )?;
+    }
+
+    if let (true, Some(path)) = (config.save_settings || args.save_settings, config_path) {
+        config::save_settings(path, &rom.hash, &rom_name(&args.machine.rom), &settings)?;
+    }
+    Ok(())
+}
+
+fn debug(args: DebugArgs, config: &Config) -> Result<(), String> {
+    if rom::is_stdin(&args.machine.rom) {
+        return Err(String::from(
+            "The debugger reads its commands from stdin, so the ROM can't be read from there too",
+        ));
+    }
+    let rom = Rom::read(&args.machine.rom, &args.machine.patches, config)?;
+    let emu = load(&args.machine, &rom)?;
+    let mut debugger = Debugger::new(emu, args.machine.cycles_per_frame(&rom.profile));
+    for addr in args.breakpoints {
+        debugger.add_breakpoint(addr);
+    }
+    debugger
+        .run(io::stdin().lock(), &mut io::stdout())
+        .map_err(|err| err.to_string())
+}
+
+fn disasm(rom: &Path) -> Result<(), String> {
+    let (rom, _) = read_rom(rom)?;
+    print!("{}", chip8::disasm::disassemble(&rom));
+    Ok(())
+}
+
+fn asm(args: AsmArgs) -> Result<(), String> {
+    let source =
+        fs::read_to_string(&args.source).map_err(|err| read_error("program", &args.source, err))?;
+    let rom = chip8::asm::assemble(&source)
+        .map_err(|err| format!("{}: {}", args.source.display(), err))?;
+    let output = match args.output {
+        Some(output) => output,
+        None => args.source.with_extension("ch8"),
+    };
+    fs::write(&output, &rom)
+        .map_err(|err| format!("Could not write {}: {}", output.display(), err))?;
+    println!("Assembled {} bytes into {}", rom.len(), output.display());
+    Ok(())
+}
+
+/// Lists addresses for the report of `info`, e.g. "0x2A0, 0x2B4".
+fn address_list(addresses: &[u16]) -> String {
+    if addresses.is_empty() {
+        return String::from("none");
+    }
+    let addresses: Vec<_> = addresses
+        .iter()
+        .map(|addr| format!("{:#05X}", addr))
+        .collect();
+    addresses.join(", ")
+}
+
+fn info(path: &Path, patches: &[PathBuf]) -> Result<(), String> {
+    let (rom, cartridge) = read_rom(path)?;
+    let original_hash = config::rom_hash(&rom);
+    let rom = patch_rom(rom, patches)?;
+    let hash = config::rom_hash(&rom);
+    let database = Database::embedded();
+    let entry = database
+        .lookup(&hash)
+        .or_else(|| database.lookup(&original_hash));
+
+    println!("Name: {}", rom_name(path));
+    if let Some(entry) = &entry {
+        let program = entry.program;
+        let mut credits = program.authors.clone();
+        credits.extend(program.release.clone());
+        if credits.is_empty() {
+            println!("Title: {}", program.title);
+        } else {
+            println!("Title: {} ({})", program.title, credits.join(", "));
+        }
+    }
+    println!("SHA-1: {}", hash);
+    if !patches.is_empty() {
+        println!("SHA-1 before patching: {}", original_hash);
+    }
+    println!("Size: {} bytes", rom.len());
+    if rom.len() <= CHIP8_MAX_ROM_SIZE {
+        println!("Free memory: {} bytes", CHIP8_MAX_ROM_SIZE - rom.len());
+    } else {
+        println!("Too large by {} bytes", rom.len() - CHIP8_MAX_ROM_SIZE);
+    }
+
+    let cartridge_platform = cartridge.and_then(|profile| profile.platform);
+    let database_platform = entry.as_ref().and_then(|entry| entry.rom.platform());
+    let platform = match (cartridge_platform, database_platform) {
+        (Some(platform), _) => format!("{} (from the Octo cartridge)", platform),
+        (None, Some(platform)) => format!("{} (from the ROM database)", platform),
+        (None, None) => match Platform::detect(&rom) {
+            Some(platform) => format!("{} (from the instructions it uses)", platform),
+            None => format!("{} (no instructions of later platforms)", Platform::Chip8),
+        },
+    };
+    println!("Platform: {}", platform);
+    if let Some(platform) = Platform::detect(&rom) {
+        println!(
+            "  Its code uses instructions only {} has, which aren't emulated, so it can't be run without \
+             --platform chip8",
+            platform
+        );
+    }
+
+    let analysis = chip8::analysis::analyze(&rom);
+    println!();
+    for region in &analysis.regions {
+        println!(
+            "{} {:#05X}-{:#05X} ({} bytes)",
+            if region.code { "Code" } else { "Data" },
+            region.start,
+            region.end,
+            region.end - region.start + 1
+        );
+    }
+
+    let keys: Vec<_> = analysis
+        .keys
+        .iter()
+        .map(|key| format!("{:X}", key))
+        .collect();
+    let mut keys = if keys.is_empty() {
+        String::from("none found")
+    } else {
+        keys.join(", ")
+    };
+    if analysis.unknown_key_checks > 0 {
+        keys += &format!(
+            " (and {} checks of keys that couldn't be worked out)",
+            analysis.unknown_key_checks
+        );
+    }
+    println!();
+    println!("Keys checked: {}", keys);
+    println!(
+        "Waits for a key: {}",
+        if analysis.waits_for_key { "yes" } else { "no" }
+    );
+    println!(
+        "Computed jumps (BNNN, see the jump quirk): {}",
+        address_list(&analysis.computed_jumps)
+    );
+    println!(
+        "VF checked after FX1E (relies on I overflowing): {}",
+        address_list(&analysis.index_overflow_checks)
+    );
+    println!(
+        "I pointed into code while storing registers (self-modifying): {}",
+        address_list(&analysis.code_writes)
+    );
+    let invalid: Vec<_> = analysis
+        .invalid
+        .iter()
+        .map(|(addr, opcode)| format!("{:#05X}: {:04X}", addr, opcode))
+        .collect();
+    println!(
+        "Invalid instructions: {}",
+        if invalid.is_empty() {
+            String::from("none")
+        } else {
+            invalid.join(", ")
+        }
+    );
+
+    let mut opcodes: Vec<_> = analysis.opcodes.iter().collect();
+    opcodes.sort_by(|a, b| b.1.cmp(a.1));
+    println!();
+    println!("Instructions used:");
+    for (name, count) in opcodes {
+        println!("  {:<20} {}", name, count);
+    }
+    Ok(())
+}
+
+fn test(args: TestArgs, config: &Config) -> Result<(), String> {
+    let rom = Rom::read(&args.machine.rom, &args.machine.patches, config)?;
+    let mut emu = load(&args.machine, &rom)?;
+    let run_options = RunOptions {
+        rom_name: rom_name(&args.machine.rom),
+        cycles_per_frame: args.machine.cycles_per_frame(&rom.profile),
+        uncapped: true,
+        frame_limit: Some(args.frames),
+        stop_on_error: true,
+        ..RunOptions::default()
+    };
+    frontend::run(
+        &mut emu,
+        &mut HeadlessFrontend,
+        &mut Settings::default(),
+        run_options,
+    )?;
+
+    let screen = emu.display.to_text();
+    print!("{}", screen);
+    if let Some(path) = &args.expect {
+        let expected =
+            fs::read_to_string(path).map_err(|err| read_error("expected screen", path, err))?;
+        if expected.trim() != screen.trim() {
+            return Err(format!(
+                "The screen after {} frames doesn't match {}",
+                args.frames,
+                path.display()
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let (config, config_path) = match load_config(cli.config) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            process::exit(1);
+        }
+    };
+    let result = match cli.command {
+        Command::Run(args) => run(*args, &config, config_path.as_deref()),
+        Command::Debug(args) => debug(args, &config),
+        Command::Disasm { rom } => disasm(&rom),
+        Command::Asm(args) => asm(args),
+        Command::Info { rom, patches } => info(&rom, &patches),
+        Command::Test(args) => test(args, &config),
+    };
+    if let Err(err) = result {
+        eprintln!("error: {}", err);
+        process::exit(1);
     }
 }
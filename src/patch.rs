@@ -0,0 +1,206 @@
+use crc32fast::hash as crc32;
+
+/// The magic number IPS patches start with.
+const IPS_MAGIC: &[u8] = b"PATCH";
+
+/// The marker that ends the records of an IPS patch.
+const IPS_END: &[u8] = b"EOF";
+
+/// The magic number BPS patches start with.
+const BPS_MAGIC: &[u8] = b"BPS1";
+
+/// The length of the three CRC-32 checksums that end a BPS patch.
+const BPS_FOOTER_LENGTH: usize = 12;
+
+/// Applies an IPS or BPS patch to a ROM, telling the formats apart by their magic numbers.
+pub fn apply(rom: &[u8], patch: &[u8]) -> Result<Vec<u8>, String> {
+    if patch.starts_with(IPS_MAGIC) {
+        apply_ips(rom, &patch[IPS_MAGIC.len()..])
+    } else if patch.starts_with(BPS_MAGIC) {
+        apply_bps(rom, patch)
+    } else {
+        Err(String::from("it isn't an IPS or BPS patch"))
+    }
+}
+
+/// Reads the bytes of a patch in order, failing if the patch ends early.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Reader { bytes, position: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.position >= self.bytes.len()
+    }
+
+    /// Whether the bytes that haven't been read yet start with the given bytes.
+    fn starts_with(&self, bytes: &[u8]) -> bool {
+        self.bytes[self.position..].starts_with(bytes)
+    }
+
+    fn take(&mut self, length: usize) -> Result<&'a [u8], String> {
+        let bytes = slice(self.bytes, self.position, length).ok_or("the patch ends too early")?;
+        self.position += length;
+        Ok(bytes)
+    }
+
+    /// Reads a big-endian number of up to four bytes, as IPS uses.
+    fn big_endian(&mut self, length: usize) -> Result<usize, String> {
+        let bytes = self.take(length)?;
+        Ok(bytes
+            .iter()
+            .fold(0, |number, &byte| number << 8 | byte as usize))
+    }
+
+    /// Reads a number in the variable-length encoding BPS uses, where each byte holds seven bits and the last byte has
+    /// its top bit set.
+    fn varint(&mut self) -> Result<usize, String> {
+        let mut number: usize = 0;
+        let mut shift: usize = 1;
+        loop {
+            let byte = self.take(1)?[0] as usize;
+            number = (byte & 0x7F)
+                .checked_mul(shift)
+                .and_then(|bits| number.checked_add(bits))
+                .ok_or("the patch holds a number that is too large")?;
+            if byte & 0x80 != 0 {
+                return Ok(number);
+            }
+            shift = shift
+                .checked_mul(0x80)
+                .ok_or("the patch holds a number that is too large")?;
+            number = number
+                .checked_add(shift)
+                .ok_or("the patch holds a number that is too large")?;
+        }
+    }
+}
+
+/// Applies the records of an IPS patch, which each overwrite a run of bytes, growing the ROM if they reach past its
+/// end.
+fn apply_ips(rom: &[u8], patch: &[u8]) -> Result<Vec<u8>, String> {
+    let mut rom = rom.to_vec();
+    let mut reader = Reader::new(patch);
+    loop {
+        if reader.starts_with(IPS_END) {
+            reader.take(IPS_END.len())?;
+            break;
+        }
+        let offset = reader.big_endian(3)?;
+        let length = reader.big_endian(2)?;
+        // Records with no length repeat a single byte.
+        let data = if length == 0 {
+            let count = reader.big_endian(2)?;
+            vec![reader.take(1)?[0]; count]
+        } else {
+            reader.take(length)?.to_vec()
+        };
+        if rom.len() < offset + data.len() {
+            rom.resize(offset + data.len(), 0);
+        }
+        rom[offset..offset + data.len()].copy_from_slice(&data);
+    }
+
+    // An extension of the format cuts the ROM down to the length given after the end marker.
+    if !reader.is_empty() {
+        let length = reader.big_endian(3)?;
+        rom.truncate(length);
+    }
+    Ok(rom)
+}
+
+/// Applies a BPS patch, which builds the patched ROM from runs of the original ROM, of the patch and of what has been
+/// built so far. The checksums at the end of the patch make sure it is applied to the ROM it was made for.
+fn apply_bps(source: &[u8], patch: &[u8]) -> Result<Vec<u8>, String> {
+    if patch.len() < BPS_MAGIC.len() + BPS_FOOTER_LENGTH {
+        return Err(String::from("the patch ends too early"));
+    }
+    let (body, footer) = patch.split_at(patch.len() - BPS_FOOTER_LENGTH);
+    let checksum = |index: usize| {
+        let bytes = &footer[index * 4..index * 4 + 4];
+        u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+    };
+    if crc32(&patch[..patch.len() - 4]) != checksum(2) {
+        return Err(String::from(
+            "the patch is damaged (its checksum doesn't match)",
+        ));
+    }
+
+    let mut reader = Reader::new(body);
+    reader.take(BPS_MAGIC.len())?;
+    let source_size = reader.varint()?;
+    let target_size = reader.varint()?;
+    let metadata_size = reader.varint()?;
+    reader.take(metadata_size)?;
+    if source_size != source.len() || crc32(source) != checksum(0) {
+        return Err(String::from("the patch was made for a different ROM"));
+    }
+
+    let mut target = Vec::new();
+    let mut source_offset: usize = 0;
+    let mut target_offset: usize = 0;
+    while !reader.is_empty() {
+        let action = reader.varint()?;
+        let length = (action >> 2) + 1;
+        match action & 3 {
+            // Copies the bytes at the same position in the original ROM.
+            0 => {
+                let bytes = slice(source, target.len(), length)
+                    .ok_or("the patch reads past the end of the ROM")?;
+                target.extend_from_slice(bytes);
+            }
+            // Copies bytes from the patch itself.
+            1 => target.extend_from_slice(reader.take(length)?),
+            // Copies bytes from elsewhere in the original ROM.
+            2 => {
+                source_offset = relative_offset(source_offset, reader.varint()?)?;
+                let bytes = slice(source, source_offset, length)
+                    .ok_or("the patch reads past the end of the ROM")?;
+                target.extend_from_slice(bytes);
+                source_offset += length;
+            }
+            // Copies bytes already written, one at a time, since the copy can overlap what it writes.
+            _ => {
+                target_offset = relative_offset(target_offset, reader.varint()?)?;
+                for _ in 0..length {
+                    let byte = *target
+                        .get(target_offset)
+                        .ok_or("the patch reads past what it has written")?;
+                    target.push(byte);
+                    target_offset += 1;
+                }
+            }
+        }
+        if target.len() > target_size {
+            return Err(String::from("the patch writes more than it says it will"));
+        }
+    }
+
+    if target.len() != target_size || crc32(&target) != checksum(1) {
+        return Err(String::from(
+            "the patched ROM isn't the one the patch was meant to make (its checksum doesn't match)",
+        ));
+    }
+    Ok(target)
+}
+
+/// The bytes at an offset, or `None` if they reach past the end.
+fn slice(bytes: &[u8], offset: usize, length: usize) -> Option<&[u8]> {
+    bytes.get(offset..)?.get(..length)
+}
+
+/// Moves an offset by the signed amount a BPS copy starts with, whose lowest bit is the sign.
+fn relative_offset(offset: usize, data: usize) -> Result<usize, String> {
+    let distance = data >> 1;
+    let moved = if data & 1 == 0 {
+        offset.checked_add(distance)
+    } else {
+        offset.checked_sub(distance)
+    };
+    moved.ok_or_else(|| String::from("the patch copies from before the start of the ROM"))
+}
@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod rom_patch_test {
+    use crate::patch::apply;
+
+    /// Encodes a number the way BPS patches do.
+    fn varint(mut number: usize) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        loop {
+            let bits = (number & 0x7F) as u8;
+            number >>= 7;
+            if number == 0 {
+                bytes.push(0x80 | bits);
+                return bytes;
+            }
+            bytes.push(bits);
+            number -= 1;
+        }
+    }
+
+    fn bps(source: &[u8], target: &[u8], actions: &[&[usize]], data: &[u8]) -> Vec<u8> {
+        let mut patch = b"BPS1".to_vec();
+        for number in [source.len(), target.len(), 0] {
+            patch.extend(varint(number));
+        }
+        for action in actions {
+            for &number in *action {
+                patch.extend(varint(number));
+            }
+            if action[0] & 3 == 1 {
+                patch.extend(&data[..(action[0] >> 2) + 1]);
+            }
+        }
+        patch.extend(crc32fast::hash(source).to_le_bytes());
+        patch.extend(crc32fast::hash(target).to_le_bytes());
+        let checksum = crc32fast::hash(&patch);
+        patch.extend(checksum.to_le_bytes());
+        patch
+    }
+
+    #[test]
+    fn ips_patch_test() {
+        let mut patch = b"PATCH".to_vec();
+        // Two bytes at 1, then four copies of 0xEE at 6, past the end of the ROM.
+        patch.extend([0, 0, 1, 0, 2, 0xAA, 0xBB]);
+        patch.extend([0, 0, 6, 0, 0, 0, 4, 0xEE]);
+        patch.extend(b"EOF");
+        assert_eq!(
+            apply(&[1, 2, 3, 4], &patch),
+            Ok(vec![1, 0xAA, 0xBB, 4, 0, 0, 0xEE, 0xEE, 0xEE, 0xEE])
+        );
+
+        // The ROM can be cut short after the end marker.
+        patch.extend([0, 0, 3]);
+        assert_eq!(apply(&[1, 2, 3, 4], &patch), Ok(vec![1, 0xAA, 0xBB]));
+
+        assert!(apply(&[1, 2], b"PATCH\x00\x00\x01\x00\x05\x01").is_err());
+        assert!(apply(&[1, 2], b"not a patch").is_err());
+    }
+
+    #[test]
+    fn bps_patch_test() {
+        let source = [1, 2, 3, 4, 5, 6];
+        let target = [1, 2, 9, 9, 9, 9, 5, 6, 1, 2];
+        let actions: [&[usize]; 5] = [
+            // Two bytes of the source, a byte of the patch, three copies of that byte, then two bytes from the end of
+            // the source and two from its start.
+            &[1 << 2],
+            &[1],
+            &[2 << 2 | 3, 2 << 1],
+            &[1 << 2 | 2, 4 << 1],
+            &[1 << 2 | 2, 6 << 1 | 1],
+        ];
+        let patch = bps(&source, &target, &actions, &[9]);
+        assert_eq!(apply(&source, &patch), Ok(target.to_vec()));
+
+        assert_eq!(
+            apply(&[1, 2, 3, 4, 5, 7], &patch),
+            Err(String::from("the patch was made for a different ROM"))
+        );
+        let mut damaged = patch.clone();
+        damaged[8] ^= 1;
+        assert_eq!(
+            apply(&source, &damaged),
+            Err(String::from(
+                "the patch is damaged (its checksum doesn't match)"
+            ))
+        );
+    }
+}
@@ -0,0 +1,61 @@
+use std::ffi::OsStr;
+use std::fs::{self, File};
+use std::io::{self, ErrorKind, Read};
+use std::path::Path;
+use zip::ZipArchive;
+
+/// The extensions of ROM files for each platform, which is how ROMs are found inside archives.
+const ROM_EXTENSIONS: [&str; 4] = ["ch8", "c8", "sc8", "xo8"];
+
+/// Whether a path stands for stdin rather than a file.
+pub fn is_stdin(path: &Path) -> bool {
+    path == Path::new("-")
+}
+
+/// Reads a ROM from a file, from stdin if the path is `-`, or from the first ROM file inside a ZIP archive.
+pub fn read(path: &Path) -> io::Result<Vec<u8>> {
+    if is_stdin(path) {
+        let mut rom = Vec::new();
+        io::stdin().lock().read_to_end(&mut rom)?;
+        return Ok(rom);
+    }
+
+    if has_extension(path, &["zip"]) {
+        read_zip(File::open(path)?)
+    } else {
+        fs::read(path)
+    }
+}
+
+/// Reads the first entry of a ZIP archive with the extension of a ROM.
+pub fn read_zip(archive: impl Read + io::Seek) -> io::Result<Vec<u8>> {
+    let invalid = |err: zip::result::ZipError| io::Error::new(ErrorKind::InvalidData, err);
+    let mut archive = ZipArchive::new(archive).map_err(invalid)?;
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index).map_err(invalid)?;
+        let is_rom = has_extension(Path::new(entry.name()), &ROM_EXTENSIONS);
+        if entry.is_file() && is_rom {
+            let mut rom = Vec::new();
+            entry.read_to_end(&mut rom)?;
+            return Ok(rom);
+        }
+    }
+    Err(io::Error::new(
+        ErrorKind::InvalidData,
+        format!(
+            "the archive has no file ending in .{}",
+            ROM_EXTENSIONS.join(", .")
+        ),
+    ))
+}
+
+/// Whether a path ends in one of the extensions, ignoring case.
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(OsStr::to_str)
+        .is_some_and(|extension| {
+            extensions
+                .iter()
+                .any(|expected| extension.eq_ignore_ascii_case(expected))
+        })
+}
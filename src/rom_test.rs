@@ -0,0 +1,34 @@
+#[cfg(test)]
+mod rom_file_test {
+    use crate::rom::read_zip;
+    use std::io::{Cursor, ErrorKind, Write};
+    use zip::write::SimpleFileOptions;
+    use zip::{CompressionMethod, ZipWriter};
+
+    /// Makes a ZIP archive holding the given files.
+    fn archive(files: &[(&str, &[u8])]) -> Cursor<Vec<u8>> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        for (name, contents) in files {
+            writer.start_file(*name, options).unwrap();
+            writer.write_all(contents).unwrap();
+        }
+        let mut archive = writer.finish().unwrap();
+        archive.set_position(0);
+        archive
+    }
+
+    #[test]
+    fn archive_rom_test() {
+        let zip = archive(&[
+            ("README.txt", b"Press 5 to start"),
+            ("games/Pong.CH8", &[0x12, 0x00]),
+            ("other.xo8", &[0x00, 0xE0]),
+        ]);
+        assert_eq!(read_zip(zip).unwrap(), vec![0x12, 0x00]);
+
+        let err = read_zip(archive(&[("README.txt", b"")])).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert!(err.to_string().contains(".ch8"), "{}", err);
+    }
+}
@@ -0,0 +1,191 @@
+use crate::chip8::quirks::Platform;
+use crate::config::{Profile, QuirkFlags, QuirksConfig};
+use crate::frontend::KeyMap;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// The database built into the emulator. It uses the format of the programs.json file of the community CHIP-8 database
+/// (https://github.com/chip-8/chip-8-database), with each program listing its ROMs by their SHA-1 hash.
+const EMBEDDED: &str = include_str!("romdb.json");
+
+/// The keyboard keys the controls of a game are moved to, by the names the database gives the controls. Player one
+/// uses WASD with E and Q as buttons, and player two uses IJKL with O and U.
+const CONTROL_KEYS: [(&str, char); 12] = [
+    ("up", 'w'),
+    ("left", 'a'),
+    ("down", 's'),
+    ("right", 'd'),
+    ("a", 'e'),
+    ("b", 'q'),
+    ("player2Up", 'i'),
+    ("player2Left", 'j'),
+    ("player2Down", 'k'),
+    ("player2Right", 'l'),
+    ("player2A", 'o'),
+    ("player2B", 'u'),
+];
+
+/// A game or other program, which may have several ROMs, e.g. different versions.
+#[derive(Debug, Deserialize)]
+pub struct Program {
+    pub title: String,
+    #[serde(default)]
+    pub authors: Vec<String>,
+    pub release: Option<String>,
+    roms: HashMap<String, RomInfo>,
+}
+
+/// What the database knows about one ROM of a program.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RomInfo {
+    /// The platforms the ROM runs on, by their IDs in the database, best first.
+    #[serde(default)]
+    platforms: Vec<String>,
+    /// Quirks that differ from the usual behaviour of a platform, by platform ID.
+    #[serde(default)]
+    quirky_platforms: HashMap<String, QuirkOverrides>,
+    /// The number of instructions the ROM should run each frame.
+    tickrate: Option<u32>,
+    /// The hex key each control of the game is on, e.g. `"left": 4`.
+    #[serde(default)]
+    keys: HashMap<String, usize>,
+    colors: Option<Colors>,
+}
+
+/// Quirks in the database's terms, each of which is on when it differs from the original CHIP-8.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct QuirkOverrides {
+    shift: Option<bool>,
+    memory_leave_i_unchanged: Option<bool>,
+    jump: Option<bool>,
+    logic: Option<bool>,
+    wrap: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Colors {
+    /// Hex codes for pixels that are off, then pixels that are on.
+    #[serde(default)]
+    pixels: Vec<String>,
+}
+
+/// A program found in the database, along with the ROM of it that was looked up.
+pub struct Entry<'a> {
+    pub program: &'a Program,
+    pub rom: &'a RomInfo,
+}
+
+/// ROMs known to the emulator, so that they can be run with the right settings without having to set them by hand.
+pub struct Database {
+    programs: Vec<Program>,
+    /// The index of the program each ROM belongs to, by the ROM's SHA-1 hash.
+    hashes: HashMap<String, usize>,
+}
+
+impl Database {
+    /// The database built into the emulator.
+    pub fn embedded() -> Database {
+        EMBEDDED
+            .parse()
+            .expect("the built-in ROM database is invalid")
+    }
+
+    /// Looks up a ROM by its SHA-1 hash.
+    pub fn lookup(&self, hash: &str) -> Option<Entry<'_>> {
+        let program = &self.programs[*self.hashes.get(&hash.to_lowercase())?];
+        let rom = program
+            .roms
+            .iter()
+            .find(|(rom_hash, _)| rom_hash.eq_ignore_ascii_case(hash))
+            .map(|(_, rom)| rom)?;
+        Some(Entry { program, rom })
+    }
+}
+
+impl FromStr for Database {
+    type Err = String;
+
+    fn from_str(json: &str) -> Result<Self, Self::Err> {
+        let programs: Vec<Program> = serde_json::from_str(json).map_err(|err| err.to_string())?;
+        let mut hashes = HashMap::new();
+        for (index, program) in programs.iter().enumerate() {
+            for hash in program.roms.keys() {
+                hashes.insert(hash.to_lowercase(), index);
+            }
+        }
+        Ok(Database { programs, hashes })
+    }
+}
+
+impl RomInfo {
+    /// The best platform for the ROM that the emulator knows, along with its ID in the database.
+    fn platform_id(&self) -> Option<(&str, Platform)> {
+        self.platforms.iter().find_map(|id| {
+            let platform = match id.as_str() {
+                "originalChip8" | "hybridVIP" | "modernChip8" | "chip8x" => Platform::Chip8,
+                "chip48" | "superchip1" | "superchip" | "megachip8" => Platform::Schip,
+                "xochip" => Platform::XoChip,
+                _ => return None,
+            };
+            Some((id.as_str(), platform))
+        })
+    }
+
+    pub fn platform(&self) -> Option<Platform> {
+        self.platform_id().map(|(_, platform)| platform)
+    }
+
+    /// The settings the ROM should be run with.
+    pub fn profile(&self) -> Profile {
+        let quirks = self
+            .platform_id()
+            .and_then(|(id, _)| self.quirky_platforms.get(id))
+            .map(|overrides| {
+                QuirksConfig::Flags(QuirkFlags {
+                    shift_uses_vy: overrides.shift.map(|shift| !shift),
+                    load_store_increments_i: overrides.memory_leave_i_unchanged.map(|leave| !leave),
+                    jump_uses_vx: overrides.jump,
+                    logic_resets_vf: overrides.logic,
+                    clip_sprites: overrides.wrap.map(|wrap| !wrap),
+                })
+            });
+        let colors = self
+            .colors
+            .as_ref()
+            .map_or(&[][..], |colors| &colors.pixels);
+
+        Profile {
+            platform: self.platform(),
+            quirks,
+            ips: self.tickrate.map(|tickrate| tickrate * 60),
+            bg: colors.first().and_then(|color| color.parse().ok()),
+            fg: colors.get(1).and_then(|color| color.parse().ok()),
+            keymap: self.keymap(),
+            ..Profile::default()
+        }
+    }
+
+    /// The default key map with the controls of the game moved onto WASD and IJKL, or `None` if the database doesn't
+    /// know the controls.
+    fn keymap(&self) -> Option<KeyMap> {
+        if self.keys.is_empty() {
+            return None;
+        }
+        let mut keymap = KeyMap::default();
+        let mut bound = [false; 16];
+        for (control, c) in CONTROL_KEYS {
+            match self.keys.get(control) {
+                // Two controls can share a key, in which case the first one keeps it.
+                Some(&key) if key < bound.len() && !bound[key] => {
+                    keymap.bind(c, key);
+                    bound[key] = true;
+                }
+                _ => {}
+            }
+        }
+        Some(keymap)
+    }
+}
@@ -0,0 +1,57 @@
+#[cfg(test)]
+mod rom_database_test {
+    use crate::chip8::quirks::{Platform, Quirks};
+    use crate::romdb::Database;
+
+    #[test]
+    fn romdb_lookup_test() {
+        let database = Database::embedded();
+        let entry = database
+            .lookup("B232EF880BD6060FB45FA6EFFED7EDF0AE95670E")
+            .unwrap();
+        assert_eq!(entry.program.title, "Pong");
+        assert_eq!(entry.rom.platform(), Some(Platform::Chip8));
+
+        // The paddles are moved from 1 and 4 onto W and S, and the keys that were there take their place.
+        let keymap = entry.rom.profile().keymap.unwrap();
+        assert_eq!(keymap.key_for('w'), Some(0x1));
+        assert_eq!(keymap.key_for('s'), Some(0x4));
+        assert_eq!(keymap.key_for('1'), Some(0x5));
+        assert_eq!(keymap.key_for('q'), Some(0x8));
+
+        assert!(database
+            .lookup("0000000000000000000000000000000000000000")
+            .is_none());
+    }
+
+    #[test]
+    fn romdb_profile_test() {
+        let database: Database = r##"[{
+            "title": "Test",
+            "roms": {
+                "abc": {
+                    "platforms": ["unknownPlatform", "superchip"],
+                    "quirkyPlatforms": { "superchip": { "wrap": true, "memoryLeaveIUnchanged": false } },
+                    "tickrate": 20,
+                    "colors": { "pixels": ["#102030", "#FFCC00"] }
+                }
+            }
+        }]"##
+            .parse()
+            .unwrap();
+        let profile = database.lookup("ABC").unwrap().rom.profile();
+
+        assert_eq!(profile.platform, Some(Platform::Schip));
+        assert_eq!(profile.ips, Some(1200));
+        assert_eq!(profile.bg.unwrap(), "#102030".parse().unwrap());
+        assert_eq!(profile.fg.unwrap(), "#FFCC00".parse().unwrap());
+        assert_eq!(
+            profile.quirks.unwrap().apply(Platform::Schip),
+            Quirks {
+                clip_sprites: false,
+                load_store_increments_i: true,
+                ..Quirks::for_platform(Platform::Schip)
+            }
+        );
+    }
+}